@@ -0,0 +1,157 @@
+use std::time::Duration;
+
+/// Accumulator driving a simulation at a fixed rate, independent of the
+/// render frame rate.
+///
+/// Each frame, pass the elapsed wall-clock time to [`advance`]: it runs the
+/// update callback once per whole step of accumulated time and returns the
+/// interpolation alpha for rendering between the last two simulated states.
+///
+/// At most [`max_steps`] updates run per frame. When a long frame (a stall,
+/// a debugger break) accumulates more than that, the backlog is dropped:
+/// running slow beats a catch-up spiral where each frame falls further
+/// behind.
+///
+/// [`advance`]: FixedTimestep::advance
+/// [`max_steps`]: FixedTimestep::with_max_steps
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FixedTimestep {
+    step: Duration,
+    accumulator: Duration,
+    max_steps: u32,
+}
+
+impl FixedTimestep {
+    /// Default cap on updates run by a single [`advance`](Self::advance).
+    pub const DEFAULT_MAX_STEPS: u32 = 5;
+
+    /// Creates an accumulator updating every `step`, running at most
+    /// [`DEFAULT_MAX_STEPS`](Self::DEFAULT_MAX_STEPS) updates per frame.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `step` is zero.
+    pub fn new(step: Duration) -> Self {
+        assert!(!step.is_zero(), "fixed timestep must be non-zero");
+        Self {
+            step,
+            accumulator: Duration::ZERO,
+            max_steps: Self::DEFAULT_MAX_STEPS,
+        }
+    }
+
+    /// Sets the maximum number of updates run per frame.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_steps` is zero.
+    pub fn with_max_steps(mut self, max_steps: u32) -> Self {
+        assert!(max_steps > 0, "max steps per frame must be non-zero");
+        self.max_steps = max_steps;
+        self
+    }
+
+    /// The duration of one update.
+    pub fn step(&self) -> Duration {
+        self.step
+    }
+
+    /// Adds `elapsed` to the accumulated time and calls `update` with the
+    /// step duration zero or more times to consume it.
+    ///
+    /// Returns the leftover time as a fraction of a step in `[0, 1)`, for
+    /// interpolating the rendered state.
+    pub fn advance(&mut self, elapsed: Duration, mut update: impl FnMut(Duration)) -> f32 {
+        for _ in 0..self.accumulate(elapsed) {
+            update(self.step);
+        }
+        self.alpha()
+    }
+
+    /// Adds `elapsed` to the accumulated time and returns how many updates
+    /// to run for it, consuming their time. Use this instead of
+    /// [`advance`](Self::advance) when the caller drives the updates itself.
+    ///
+    /// When more than the maximum number of steps has accumulated, returns
+    /// the maximum and discards the rest.
+    pub fn accumulate(&mut self, elapsed: Duration) -> u32 {
+        self.accumulator += elapsed;
+        let steps = self.accumulator.as_nanos() / self.step.as_nanos();
+        if steps > u128::from(self.max_steps) {
+            self.accumulator = Duration::ZERO;
+            self.max_steps
+        } else {
+            let steps = steps as u32;
+            self.accumulator -= self.step * steps;
+            steps
+        }
+    }
+
+    /// The accumulated time not yet consumed by an update, as a fraction of
+    /// a step in `[0, 1)`.
+    pub fn alpha(&self) -> f32 {
+        self.accumulator.as_secs_f32() / self.step.as_secs_f32()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_whole_steps_are_consumed_exactly() {
+        let mut timestep = FixedTimestep::new(Duration::from_millis(10));
+        let mut updates = 0;
+        let alpha = timestep.advance(Duration::from_millis(50), |step| {
+            assert_eq!(step, Duration::from_millis(10));
+            updates += 1;
+        });
+        assert_eq!(updates, 5);
+        assert_eq!(alpha, 0.0);
+    }
+
+    #[test]
+    fn test_residual_carries_over_between_frames() {
+        let mut timestep = FixedTimestep::new(Duration::from_millis(10));
+        let mut updates = 0;
+
+        let alpha = timestep.advance(Duration::from_millis(53), |_| updates += 1);
+        assert_eq!(updates, 5);
+        assert!((alpha - 0.3).abs() < 1e-4, "alpha {alpha}");
+
+        // A short frame runs no update and only grows the alpha.
+        let alpha = timestep.advance(Duration::from_millis(4), |_| updates += 1);
+        assert_eq!(updates, 5);
+        assert!((alpha - 0.7).abs() < 1e-4, "alpha {alpha}");
+
+        let alpha = timestep.advance(Duration::from_millis(3), |_| updates += 1);
+        assert_eq!(updates, 6);
+        assert_eq!(alpha, 0.0);
+        assert_eq!(timestep.alpha(), 0.0);
+    }
+
+    #[test]
+    fn test_long_frame_is_capped_and_backlog_dropped() {
+        let mut timestep = FixedTimestep::new(Duration::from_millis(10));
+        let mut updates = 0;
+
+        // A 500ms stall runs only the capped number of updates and starts
+        // the next frame with an empty accumulator.
+        let alpha = timestep.advance(Duration::from_millis(500), |_| updates += 1);
+        assert_eq!(updates, FixedTimestep::DEFAULT_MAX_STEPS);
+        assert_eq!(alpha, 0.0);
+        assert_eq!(timestep.accumulate(Duration::ZERO), 0);
+        assert_eq!(timestep.accumulate(Duration::from_millis(10)), 1);
+
+        let mut timestep = FixedTimestep::new(Duration::from_millis(10)).with_max_steps(2);
+        assert_eq!(timestep.accumulate(Duration::from_millis(25)), 2);
+        assert_eq!(timestep.accumulate(Duration::from_millis(35)), 2);
+        assert_eq!(timestep.alpha(), 0.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "non-zero")]
+    fn test_zero_step_panics() {
+        let _ = FixedTimestep::new(Duration::ZERO);
+    }
+}
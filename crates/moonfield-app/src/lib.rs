@@ -4,14 +4,17 @@
 //! - [`Plugin`] trait and function-pointer plugin support.
 //! - [`App`] container for registering plugins and running the application.
 //! - [`PluginGroup`] for bundling and configuring plugins (set/disable).
+//! - [`FixedTimestep`] for running simulation updates at a fixed rate.
 
 #![forbid(unsafe_code)]
 
 mod app;
+mod fixed_timestep;
 mod plugin;
 mod plugin_group;
 
 pub use app::{App, AppError, Plugins, Runner};
+pub use fixed_timestep::FixedTimestep;
 pub use moonfield_ecs::Resource;
 pub use plugin::Plugin;
 pub use plugin_group::{PluginGroup, PluginGroupBuilder};
@@ -97,12 +97,30 @@ impl Entity {
         id: u32::MAX,
     };
 
+    /// Whether this is the [`DANGLING`](Entity::DANGLING) sentinel.
+    ///
+    /// Thanks to the non-zero generation, `Option<Entity>` is the same size
+    /// as `Entity`; the sentinel is for places where an `Option` cannot be
+    /// used, such as packed or serialized data.
+    pub const fn is_dangling(&self) -> bool {
+        self.generation.get() == u32::MAX && self.id == u32::MAX
+    }
+
+    /// Pack the entity into a stable `u64` (see the [`Entity`] layout).
+    ///
+    /// The bits only identify an entity within the [`World`](crate::World)
+    /// that allocated it; a raw entity from one world is meaningless in
+    /// another.
     pub const fn to_bits(&self) -> NonZeroU64 {
         unsafe {
             NonZeroU64::new_unchecked(((self.generation.get() as u64) << 32) | (self.id as u64))
         }
     }
 
+    /// Reconstruct an entity packed by [`to_bits`](Entity::to_bits).
+    ///
+    /// Returns `None` when the generation bits are zero, which no entity
+    /// can have.
     pub const fn from_bits(bits: u64) -> Option<Self> {
         Some(Self {
             generation: match NonZeroU32::new((bits >> 32) as u32) {
@@ -129,6 +147,27 @@ impl fmt::Debug for Entity {
     }
 }
 
+/// Entities serialize as their [`to_bits`](Entity::to_bits) value.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Entity {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(self.to_bits().get())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Entity {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let bits = u64::deserialize(deserializer)?;
+        Entity::from_bits(bits).ok_or_else(|| {
+            serde::de::Error::invalid_value(
+                serde::de::Unexpected::Unsigned(bits),
+                &"entity bits with a non-zero generation",
+            )
+        })
+    }
+}
+
 pub struct ReserveEntitiesIterator<'a> {
     meta: &'a [EntityMeta],
     // the ids to be used from the free list
@@ -621,3 +660,58 @@ impl EntityId {
         self.alive.iter().copied().filter_map(Entity::from_bits)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entity(generation: u32, id: u32) -> Entity {
+        Entity {
+            generation: NonZeroU32::new(generation).unwrap(),
+            id,
+        }
+    }
+
+    #[test]
+    fn test_entity_bits_round_trip() {
+        for e in [
+            entity(1, 0),
+            entity(u32::MAX - 1, u32::MAX - 1),
+            entity(0x8000_0001, 0xdead_beef),
+            Entity::DANGLING,
+        ] {
+            assert_eq!(Entity::from_bits(e.to_bits().get()), Some(e));
+        }
+        assert_eq!(entity(7, 3).to_bits().get(), (7 << 32) | 3);
+        assert_eq!(Entity::from_bits(u32::MAX as u64), None);
+    }
+
+    #[test]
+    fn test_dangling_entity_never_resolves() {
+        assert_eq!(mem::size_of::<Option<Entity>>(), mem::size_of::<Entity>());
+        assert!(Entity::DANGLING.is_dangling());
+
+        let mut entities = Entities::default();
+        let fresh = entities.alloc();
+        assert!(!fresh.is_dangling());
+        assert!(!entities.contains(Entity::DANGLING));
+        assert!(entities.get(Entity::DANGLING).is_err());
+
+        let mut world = crate::World::new();
+        let fresh = world.spawn((1u32,));
+        assert!(!fresh.is_dangling());
+        assert!(!world.entities().is_alive(Entity::DANGLING));
+        assert_eq!(world.get_component::<u32>(Entity::DANGLING), None);
+        assert!(!world.despawn(Entity::DANGLING));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_entity_serde_round_trip() {
+        let e = entity(u32::MAX - 1, 0xdead_beef);
+        let json = serde_json::to_string(&e).unwrap();
+        assert_eq!(json, e.to_bits().get().to_string());
+        assert_eq!(serde_json::from_str::<Entity>(&json).unwrap(), e);
+        assert!(serde_json::from_str::<Entity>("42").is_err());
+    }
+}
@@ -80,13 +80,12 @@ impl Viewport {
                 color: [0.0, 0.0, 1.0],
             },
         ];
-        let vertex_buffer = Buffer::new(
+        let vertex_buffer = Buffer::new_with_data(
             instance,
             device,
-            std::mem::size_of_val(&vertices) as vk::DeviceSize,
+            &vertices,
             vk::BufferUsageFlags::VERTEX_BUFFER,
         )?;
-        vertex_buffer.upload(&vertices)?;
 
         let (descriptor_set_layout, descriptor_pool, descriptor_set) =
             create_descriptor_bindings(device.raw(), target.image_view(), target.sampler())?;
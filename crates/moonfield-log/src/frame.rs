@@ -0,0 +1,103 @@
+use tracing::Span;
+
+/// Create the `INFO`-level span wrapping one rendered frame.
+///
+/// The frame index is recorded as the `frame_index` field, so every event
+/// emitted while the span is entered can be filtered by frame in a log
+/// viewer.
+pub fn frame_span(frame_index: u64) -> Span {
+    tracing::info_span!("frame", frame_index)
+}
+
+/// Emit a structured `DEBUG` event reporting how long a GPU pass took.
+///
+/// The event carries a `label` field naming the pass and a `duration_us`
+/// field with the duration in microseconds. Call it inside a
+/// [`frame_span`] to attribute the timing to a frame.
+pub fn log_gpu_timing(label: &str, micros: u64) {
+    tracing::debug!(label, duration_us = micros, "gpu timing");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fmt;
+    use std::sync::{Arc, Mutex};
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id};
+    use tracing::{Event, Subscriber};
+    use tracing_subscriber::layer::{Context, Layer};
+    use tracing_subscriber::prelude::*;
+    use tracing_subscriber::Registry;
+
+    /// A span or event name with its recorded `(field, value)` pairs.
+    type Record = (String, Vec<(String, String)>);
+
+    #[derive(Clone, Default)]
+    struct CaptureLayer {
+        records: Arc<Mutex<Vec<Record>>>,
+    }
+
+    struct FieldVisitor<'a>(&'a mut Vec<(String, String)>);
+
+    impl Visit for FieldVisitor<'_> {
+        fn record_str(&mut self, field: &Field, value: &str) {
+            self.0.push((field.name().to_string(), value.to_string()));
+        }
+
+        fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+            self.0
+                .push((field.name().to_string(), format!("{value:?}")));
+        }
+    }
+
+    impl<S: Subscriber> Layer<S> for CaptureLayer {
+        fn on_new_span(&self, attrs: &Attributes<'_>, _id: &Id, _ctx: Context<'_, S>) {
+            let mut fields = Vec::new();
+            attrs.record(&mut FieldVisitor(&mut fields));
+            let name = attrs.metadata().name().to_string();
+            self.records.lock().unwrap().push((name, fields));
+        }
+
+        fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+            let mut fields = Vec::new();
+            event.record(&mut FieldVisitor(&mut fields));
+            self.records
+                .lock()
+                .unwrap()
+                .push(("event".to_string(), fields));
+        }
+    }
+
+    fn field<'a>(record: &'a Record, name: &str) -> Option<&'a str> {
+        record
+            .1
+            .iter()
+            .find(|(field, _)| field == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    #[test]
+    fn test_frame_span_and_gpu_timing_fields() {
+        let layer = CaptureLayer::default();
+        let subscriber = Registry::default().with(layer.clone());
+
+        tracing::subscriber::with_default(subscriber, || {
+            let span = frame_span(42);
+            assert_eq!(span.metadata().unwrap().level(), &tracing::Level::INFO);
+            let _guard = span.enter();
+            log_gpu_timing("shadow_pass", 1250);
+        });
+
+        let records = layer.records.lock().unwrap();
+        assert_eq!(records.len(), 2);
+
+        let span = &records[0];
+        assert_eq!(span.0, "frame");
+        assert_eq!(field(span, "frame_index"), Some("42"));
+
+        let event = &records[1];
+        assert_eq!(field(event, "label"), Some("shadow_pass"));
+        assert_eq!(field(event, "duration_us"), Some("1250"));
+    }
+}
@@ -5,9 +5,12 @@
 //! identically to it.
 //!
 //! By default, the [`LogPlugin`] sets up a `tracing-subscriber` collector that
-//! logs to `stderr`. Log level can be controlled via the `RUST_LOG` environment
-//! variable or programmatically through [`LogPlugin`] configuration.
+//! logs to `stderr`. Log level can be controlled via the `MOONFIELD_LOG` (or
+//! `RUST_LOG`) environment variable or programmatically through [`LogPlugin`]
+//! configuration. Apps that do not use [`LogPlugin`] can call
+//! [`init_logging_with_filter`] instead.
 
+mod frame;
 mod once;
 
 /// The log prelude.
@@ -23,6 +26,7 @@ pub mod prelude {
     pub use crate::{debug_once, error_once, info_once, once, trace_once, warn_once};
 }
 
+pub use crate::frame::{frame_span, log_gpu_timing};
 pub use crate::once::*;
 pub use tracing::{
     self, debug, debug_span, error, error_span, event, info, info_span, trace, trace_span, warn,
@@ -34,6 +38,21 @@ use moonfield_app::{App, Plugin};
 use tracing_log::LogTracer;
 use tracing_subscriber::{layer::Layered, prelude::*, registry::Registry, EnvFilter, Layer};
 
+/// Environment variable consulted for [`EnvFilter`] directives before
+/// falling back to `RUST_LOG`.
+pub const LOG_ENV: &str = "MOONFIELD_LOG";
+
+/// Errors that can occur while installing the global logger.
+#[derive(Debug, PartialEq, Eq, thiserror::Error)]
+pub enum LoggingError {
+    /// The filter string is not a valid [`EnvFilter`] directive list.
+    #[error("invalid log filter {filter:?}: {reason}")]
+    InvalidFilter { filter: String, reason: String },
+    /// A global tracing subscriber was already installed.
+    #[error("a global tracing subscriber is already set")]
+    AlreadyInitialized,
+}
+
 /// A boxed [`Layer`] that can be used with [`LogPlugin::custom_layer`].
 pub type BoxedLayer = Box<dyn Layer<Registry> + Send + Sync + 'static>;
 
@@ -76,11 +95,12 @@ pub const DEFAULT_FILTER: &str = concat!(
 /// }
 /// ```
 ///
-/// Log level can also be changed using the `RUST_LOG` environment variable.
-/// For example, using `RUST_LOG=wgpu=error,moonfield_render=info cargo run ..`
+/// Log level can also be changed using the `MOONFIELD_LOG` environment
+/// variable, or `RUST_LOG` when `MOONFIELD_LOG` is unset.
+/// For example, using `MOONFIELD_LOG=wgpu=error,moonfield_render=info cargo run ..`
 ///
-/// If you define the `RUST_LOG` environment variable, the [`LogPlugin`] settings
-/// will be ignored.
+/// Directives from the environment are added on top of the [`LogPlugin`]
+/// settings, so they override them for the targets they mention.
 ///
 /// To disable color terminal output (ANSI escape codes), set the environment
 /// variable `NO_COLOR` to any value. See [no-color.org](https://no-color.org/).
@@ -161,7 +181,9 @@ impl LogPlugin {
             EnvFilter::builder().parse_lossy(format!("{},{}", self.level, self.filter));
         // We must manually parse and add the directives individually because `EnvFilter` has no helper methods for adding
         // multiple directives at once.
-        let env_filters = std::env::var(EnvFilter::DEFAULT_ENV).unwrap_or_default();
+        let env_filters = std::env::var(LOG_ENV)
+            .or_else(|_| std::env::var(EnvFilter::DEFAULT_ENV))
+            .unwrap_or_default();
         let result = env_filters
             .split(',')
             .filter(|s| !s.is_empty())
@@ -188,6 +210,46 @@ impl LogPlugin {
     }
 }
 
+/// Install a global `stderr` logger filtered by `filter`, for apps that do
+/// not use [`LogPlugin`].
+///
+/// `filter` uses the [`EnvFilter`] directive syntax, e.g.
+/// `"moonfield_render=debug,info"`. Directives from the `MOONFIELD_LOG`
+/// environment variable are added on top, so the filter can be tweaked
+/// without editing source. Unlike [`LogPlugin`], a malformed `filter` or a
+/// second initialization is reported as an error instead of being logged.
+pub fn init_logging_with_filter(filter: &str) -> Result<(), LoggingError> {
+    let mut env_filter = parse_filter(filter)?;
+    if let Ok(env) = std::env::var(LOG_ENV) {
+        for directive in env.split(',').filter(|s| !s.is_empty()) {
+            match directive.parse() {
+                Ok(directive) => env_filter = env_filter.add_directive(directive),
+                Err(e) => eprintln!("ignoring malformed {LOG_ENV} directive {directive:?}: {e}"),
+            }
+        }
+    }
+
+    let subscriber = Registry::default()
+        .with(env_filter)
+        .with(tracing_subscriber::fmt::Layer::default().with_writer(std::io::stderr));
+    tracing::subscriber::set_global_default(subscriber)
+        .map_err(|_| LoggingError::AlreadyInitialized)?;
+    // Bridging `log` records is best-effort: another `log` logger may
+    // already be installed, which only means its records bypass tracing.
+    let _ = LogTracer::init();
+    Ok(())
+}
+
+/// Parse an [`EnvFilter`] directive string, rejecting malformed directives.
+fn parse_filter(filter: &str) -> Result<EnvFilter, LoggingError> {
+    EnvFilter::builder()
+        .parse(filter)
+        .map_err(|e| LoggingError::InvalidFilter {
+            filter: filter.to_string(),
+            reason: e.to_string(),
+        })
+}
+
 /// Call [`trace!`](crate::trace) once per call site.
 ///
 /// Useful for logging within systems which are called every frame.
@@ -237,3 +299,35 @@ macro_rules! error_once {
         $crate::once!($crate::error!($($arg)+))
     });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_filter_accepts_directives() {
+        let filter = parse_filter("moonfield_render=debug,info").unwrap();
+        let rendered = filter.to_string();
+        assert!(rendered.contains("moonfield_render=debug"));
+        assert!(rendered.contains("info"));
+    }
+
+    #[test]
+    fn test_parse_filter_rejects_malformed_directive() {
+        let err = parse_filter("moonfield_render=loud").unwrap_err();
+        assert!(matches!(err, LoggingError::InvalidFilter { .. }));
+    }
+
+    /// The only test that touches the process-wide subscriber.
+    #[test]
+    fn test_init_logging_with_filter_rejects_double_init() {
+        assert_eq!(
+            init_logging_with_filter("moonfield_render=debug,info"),
+            Ok(())
+        );
+        assert_eq!(
+            init_logging_with_filter("info"),
+            Err(LoggingError::AlreadyInitialized)
+        );
+    }
+}
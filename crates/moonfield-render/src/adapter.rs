@@ -0,0 +1,206 @@
+//! Human- and machine-readable description of a physical device.
+//!
+//! [`AdapterInfo`] captures the identifying properties of the GPU a
+//! [`Device`](crate::Device) runs on, for logs and bug reports. With the
+//! `serde` feature it can be serialized, e.g. to JSON.
+
+use ash::vk;
+use std::fmt;
+
+/// Kind of physical device.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum DeviceType {
+    Other,
+    IntegratedGpu,
+    DiscreteGpu,
+    VirtualGpu,
+    Cpu,
+}
+
+impl DeviceType {
+    /// Map a Vulkan device type, treating unknown values as [`Self::Other`].
+    pub fn from_vk(device_type: vk::PhysicalDeviceType) -> Self {
+        match device_type {
+            vk::PhysicalDeviceType::INTEGRATED_GPU => Self::IntegratedGpu,
+            vk::PhysicalDeviceType::DISCRETE_GPU => Self::DiscreteGpu,
+            vk::PhysicalDeviceType::VIRTUAL_GPU => Self::VirtualGpu,
+            vk::PhysicalDeviceType::CPU => Self::Cpu,
+            _ => Self::Other,
+        }
+    }
+}
+
+/// Identifying properties of a physical device.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct AdapterInfo {
+    /// Device name reported by the driver.
+    pub name: String,
+    /// PCI vendor id.
+    pub vendor_id: u32,
+    /// Vendor-specific device id.
+    pub device_id: u32,
+    pub device_type: DeviceType,
+    /// Driver version, decoded with the vendor's versioning scheme.
+    pub driver_version: String,
+    /// Highest Vulkan version the device supports, e.g. `1.3.260`.
+    pub api_version: String,
+}
+
+impl AdapterInfo {
+    /// Describe the device with the given properties.
+    pub fn from_properties(properties: &vk::PhysicalDeviceProperties) -> Self {
+        let name = properties
+            .device_name_as_c_str()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        Self {
+            name,
+            vendor_id: properties.vendor_id,
+            device_id: properties.device_id,
+            device_type: DeviceType::from_vk(properties.device_type),
+            driver_version: driver_version(properties.vendor_id, properties.driver_version),
+            api_version: format!(
+                "{}.{}.{}",
+                vk::api_version_major(properties.api_version),
+                vk::api_version_minor(properties.api_version),
+                vk::api_version_patch(properties.api_version)
+            ),
+        }
+    }
+
+    /// Name of the device vendor, if its PCI id is a known one.
+    pub fn vendor_name(&self) -> Option<&'static str> {
+        vendor_name(self.vendor_id)
+    }
+
+    /// One-line summary, e.g.
+    /// `NVIDIA GeForce RTX 3060 (DiscreteGpu), NVIDIA (Vulkan 1.3.260), driver 535.104.5`.
+    pub fn summary(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl fmt::Display for AdapterInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({:?}), ", self.name, self.device_type)?;
+        match self.vendor_name() {
+            Some(vendor) => write!(f, "{vendor}")?,
+            None => write!(f, "vendor {:#06x}", self.vendor_id)?,
+        }
+        write!(
+            f,
+            " (Vulkan {}), driver {}",
+            self.api_version, self.driver_version
+        )
+    }
+}
+
+/// Name of the GPU vendor with PCI id `vendor_id`, for the common vendors.
+pub fn vendor_name(vendor_id: u32) -> Option<&'static str> {
+    match vendor_id {
+        0x10DE => Some("NVIDIA"),
+        0x1002 => Some("AMD"),
+        0x8086 => Some("Intel"),
+        0x13B5 => Some("ARM"),
+        0x5143 => Some("Qualcomm"),
+        0x106B => Some("Apple"),
+        0x10005 => Some("Mesa"),
+        _ => None,
+    }
+}
+
+/// Decode a driver version. NVIDIA packs it as 10.8.8.6 bits; other vendors
+/// follow the Vulkan version encoding.
+fn driver_version(vendor_id: u32, version: u32) -> String {
+    if vendor_id == 0x10DE {
+        format!(
+            "{}.{}.{}",
+            (version >> 22) & 0x3ff,
+            (version >> 14) & 0xff,
+            (version >> 6) & 0xff
+        )
+    } else {
+        format!(
+            "{}.{}.{}",
+            vk::api_version_major(version),
+            vk::api_version_minor(version),
+            vk::api_version_patch(version)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn properties(name: &str, vendor_id: u32, driver_version: u32) -> vk::PhysicalDeviceProperties {
+        let mut properties = vk::PhysicalDeviceProperties {
+            vendor_id,
+            device_id: 0x2504,
+            device_type: vk::PhysicalDeviceType::DISCRETE_GPU,
+            driver_version,
+            api_version: vk::make_api_version(0, 1, 3, 260),
+            ..Default::default()
+        };
+        for (dst, src) in properties.device_name.iter_mut().zip(name.bytes()) {
+            *dst = src as std::ffi::c_char;
+        }
+        properties
+    }
+
+    #[test]
+    fn test_summary_decodes_vendor_and_versions() {
+        // NVIDIA 535.104.5
+        let nvidia_driver = (535 << 22) | (104 << 14) | (5 << 6);
+        let info = AdapterInfo::from_properties(&properties(
+            "NVIDIA GeForce RTX 3060",
+            0x10DE,
+            nvidia_driver,
+        ));
+        assert_eq!(info.name, "NVIDIA GeForce RTX 3060");
+        assert_eq!(info.device_type, DeviceType::DiscreteGpu);
+        assert_eq!(
+            info.summary(),
+            "NVIDIA GeForce RTX 3060 (DiscreteGpu), NVIDIA (Vulkan 1.3.260), driver 535.104.5"
+        );
+
+        let unknown = AdapterInfo::from_properties(&properties(
+            "llvmpipe",
+            0x1234,
+            vk::make_api_version(0, 24, 2, 8),
+        ));
+        assert_eq!(
+            unknown.summary(),
+            "llvmpipe (DiscreteGpu), vendor 0x1234 (Vulkan 1.3.260), driver 24.2.8"
+        );
+    }
+
+    #[test]
+    fn test_vendor_name_lookup() {
+        assert_eq!(vendor_name(0x10DE), Some("NVIDIA"));
+        assert_eq!(vendor_name(0x1002), Some("AMD"));
+        assert_eq!(vendor_name(0x8086), Some("Intel"));
+        assert_eq!(vendor_name(0xFFFF), None);
+        assert_eq!(
+            DeviceType::from_vk(vk::PhysicalDeviceType::CPU),
+            DeviceType::Cpu
+        );
+        assert_eq!(
+            DeviceType::from_vk(vk::PhysicalDeviceType::from_raw(42)),
+            DeviceType::Other
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_adapter_info_serializes_to_json() {
+        let info = AdapterInfo::from_properties(&properties("GPU", 0x1002, 0));
+        let json = serde_json::to_value(&info).expect("serialize");
+        assert_eq!(json["name"], "GPU");
+        assert_eq!(json["vendor_id"], 0x1002);
+        assert_eq!(json["device_type"], "DiscreteGpu");
+        assert_eq!(json["api_version"], "1.3.260");
+    }
+}
@@ -3,13 +3,17 @@
 use crate::device::Device;
 use crate::error::{Error, Result};
 use crate::instance::Instance;
+use crate::sync::Fence;
 use ash::vk;
+use std::ops::Range;
+use std::time::Duration;
 
 /// A Vulkan buffer backed by device memory.
 pub struct Buffer {
     buffer: vk::Buffer,
     memory: vk::DeviceMemory,
     size: vk::DeviceSize,
+    usage: vk::BufferUsageFlags,
     device: ash::Device,
 }
 
@@ -64,10 +68,33 @@ impl Buffer {
             buffer,
             memory,
             size,
+            usage,
             device: device.raw().clone(),
         })
     }
 
+    /// Create a buffer sized to `data` and upload `data` into it.
+    ///
+    /// Convenience for the common create-then-[`upload`](Self::upload)
+    /// sequence used for vertex and index buffers.
+    pub fn new_with_data<T: Copy>(
+        instance: &Instance,
+        device: &Device,
+        data: &[T],
+        usage: vk::BufferUsageFlags,
+    ) -> Result<Self> {
+        let size = std::mem::size_of_val(data) as vk::DeviceSize;
+        if size == 0 {
+            return Err(Error::Validation(
+                "buffer data must not be empty".to_string(),
+            ));
+        }
+
+        let buffer = Self::new(instance, device, size, usage)?;
+        buffer.upload(data)?;
+        Ok(buffer)
+    }
+
     /// Access the raw `vk::Buffer` handle.
     pub fn raw(&self) -> vk::Buffer {
         self.buffer
@@ -78,6 +105,11 @@ impl Buffer {
         self.size
     }
 
+    /// The usages the buffer was created with.
+    pub fn usage(&self) -> vk::BufferUsageFlags {
+        self.usage
+    }
+
     /// Upload data to the buffer.
     ///
     /// # Safety
@@ -109,6 +141,87 @@ impl Buffer {
 
         Ok(())
     }
+
+    /// Read the whole buffer back to the host.
+    ///
+    /// The caller must ensure the GPU has finished writing to the buffer,
+    /// e.g. by waiting on the fence of the submission that wrote it, and that
+    /// the submission recorded [`host_read_barrier`](Self::host_read_barrier)
+    /// after its last write.
+    pub fn download(&self) -> Result<Vec<u8>> {
+        self.read_range(0..self.size)
+    }
+
+    /// A barrier making transfer writes to this buffer visible to host
+    /// reads, to record with `TRANSFER` → `HOST` stages after copying into a
+    /// readback buffer.
+    pub fn host_read_barrier(&self) -> vk::BufferMemoryBarrier<'static> {
+        vk::BufferMemoryBarrier::default()
+            .src_access_mask(vk::AccessFlags::TRANSFER_WRITE)
+            .dst_access_mask(vk::AccessFlags::HOST_READ)
+            .src_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+            .dst_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+            .buffer(self.buffer)
+            .offset(0)
+            .size(vk::WHOLE_SIZE)
+    }
+
+    /// Wait for the submission signaling `fence` to finish, then read
+    /// `range` of the buffer back to the host.
+    ///
+    /// Combines the wait, map, copy and unmap steps of a readback. The buffer
+    /// must have been created with `TRANSFER_DST` usage so a copy can write
+    /// into it, and the submission must record
+    /// [`host_read_barrier`](Self::host_read_barrier) after the copy. Fails
+    /// with [`Error::Timeout`] if the fence is not signaled within `timeout`,
+    /// leaving the buffer untouched.
+    pub fn read_blocking(
+        &self,
+        fence: &Fence,
+        range: Range<vk::DeviceSize>,
+        timeout: Duration,
+    ) -> Result<Vec<u8>> {
+        if !self.usage.contains(vk::BufferUsageFlags::TRANSFER_DST) {
+            return Err(Error::Validation(
+                "readback buffer must have TRANSFER_DST usage".to_string(),
+            ));
+        }
+        if range.start > range.end || range.end > self.size {
+            return Err(Error::Validation(format!(
+                "readback range {:?} is out of bounds for a buffer of {} bytes",
+                range, self.size
+            )));
+        }
+
+        let timeout_ns = u64::try_from(timeout.as_nanos()).unwrap_or(u64::MAX);
+        fence.wait(timeout_ns)?;
+        self.read_range(range)
+    }
+
+    /// Map `range` of the buffer and copy it out.
+    fn read_range(&self, range: Range<vk::DeviceSize>) -> Result<Vec<u8>> {
+        let mut bytes = vec![0u8; (range.end - range.start) as usize];
+        if bytes.is_empty() {
+            return Ok(bytes);
+        }
+        unsafe {
+            let ptr = self
+                .device
+                .map_memory(
+                    self.memory,
+                    range.start,
+                    range.end - range.start,
+                    vk::MemoryMapFlags::empty(),
+                )
+                .map_err(|e| Error::Backend(format!("failed to map buffer memory: {:?}", e)))?;
+
+            std::ptr::copy_nonoverlapping(ptr as *const u8, bytes.as_mut_ptr(), bytes.len());
+
+            self.device.unmap_memory(self.memory);
+        }
+
+        Ok(bytes)
+    }
 }
 
 impl Drop for Buffer {
@@ -144,3 +257,108 @@ fn find_memory_type(
 
     Err(Error::Unsupported)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Needs a Vulkan device; skipped when no driver is available.
+    #[test]
+    fn test_new_with_data_sizes_buffer_to_data() {
+        let Some((instance, device, _allocator)) = crate::test_util::headless_gpu() else {
+            return;
+        };
+
+        let data = [1.0f32, 2.0, 3.0, 4.0, 5.0];
+        let buffer = Buffer::new_with_data(
+            &instance,
+            &device,
+            &data,
+            vk::BufferUsageFlags::VERTEX_BUFFER,
+        )
+        .expect("buffer with data");
+        assert_eq!(
+            buffer.size(),
+            std::mem::size_of_val(&data) as vk::DeviceSize
+        );
+
+        let empty: [f32; 0] = [];
+        assert!(Buffer::new_with_data(
+            &instance,
+            &device,
+            &empty,
+            vk::BufferUsageFlags::VERTEX_BUFFER
+        )
+        .is_err());
+    }
+
+    /// Needs a Vulkan device; skipped when no driver is available.
+    #[test]
+    fn test_read_blocking_returns_copied_range() {
+        let Some((instance, device, _allocator)) = crate::test_util::headless_gpu() else {
+            return;
+        };
+
+        let data: Vec<u8> = (0..64).collect();
+        let source = Buffer::new_with_data(
+            &instance,
+            &device,
+            &data,
+            vk::BufferUsageFlags::TRANSFER_SRC,
+        )
+        .expect("source buffer");
+        let readback = Buffer::new(&instance, &device, 64, vk::BufferUsageFlags::TRANSFER_DST)
+            .expect("readback buffer");
+
+        // Nothing signals this fence, so the read must time out.
+        let idle = Fence::new(&device, false).expect("fence");
+        assert_eq!(
+            readback.read_blocking(&idle, 0..64, Duration::from_millis(1)),
+            Err(Error::Timeout)
+        );
+        assert!(source.read_blocking(&idle, 0..64, Duration::ZERO).is_err());
+        assert!(readback
+            .read_blocking(&idle, 0..65, Duration::ZERO)
+            .is_err());
+
+        let command_pool = crate::CommandPool::new(&device, device.queue_family_indices().graphics)
+            .expect("command pool");
+        let mut command_buffer = command_pool
+            .allocate_command_buffer()
+            .expect("command buffer");
+        command_buffer
+            .begin(vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT)
+            .expect("begin");
+        command_buffer
+            .copy_buffer_to_buffer(&source, 0, &readback, 0, 64)
+            .expect("copy");
+        command_buffer.pipeline_barrier(
+            vk::PipelineStageFlags::TRANSFER,
+            vk::PipelineStageFlags::HOST,
+            vk::DependencyFlags::empty(),
+            &[],
+            &[readback.host_read_barrier()],
+            &[],
+        );
+        command_buffer.end().expect("end");
+
+        let fence = Fence::new(&device, false).expect("fence");
+        let command_buffers = [command_buffer.raw()];
+        let submit_info = vk::SubmitInfo::default().command_buffers(&command_buffers);
+        unsafe {
+            device
+                .raw()
+                .queue_submit(
+                    device.graphics_queue(),
+                    std::slice::from_ref(&submit_info),
+                    fence.raw(),
+                )
+                .expect("submit");
+        }
+
+        let bytes = readback
+            .read_blocking(&fence, 16..48, Duration::from_secs(5))
+            .expect("readback");
+        assert_eq!(bytes, &data[16..48]);
+    }
+}
@@ -0,0 +1,306 @@
+//! Linear RGBA color used for clear values, its packed 8-bit forms, and
+//! gradients for debug visualizations.
+
+use crate::error::{Error, Result};
+use ash::vk;
+
+/// An RGBA color with `f32` channels in `[0, 1]`.
+///
+/// The type does not track its color space; [`to_linear`](Self::to_linear)
+/// and [`to_srgb`](Self::to_srgb) convert the RGB channels between the sRGB
+/// transfer function and linear light, leaving alpha untouched.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Color {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+    pub a: f32,
+}
+
+impl Color {
+    pub const BLACK: Self = Self::new(0.0, 0.0, 0.0, 1.0);
+    pub const WHITE: Self = Self::new(1.0, 1.0, 1.0, 1.0);
+    pub const TRANSPARENT: Self = Self::new(0.0, 0.0, 0.0, 0.0);
+
+    /// Create a color from its channels.
+    pub const fn new(r: f32, g: f32, b: f32, a: f32) -> Self {
+        Self { r, g, b, a }
+    }
+
+    /// Create a color from 8-bit channels, mapping `0..=255` to `0.0..=1.0`.
+    pub fn from_rgba8(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Self::new(
+            r as f32 / 255.0,
+            g as f32 / 255.0,
+            b as f32 / 255.0,
+            a as f32 / 255.0,
+        )
+    }
+
+    /// Convert to 8-bit channels, rounding to the nearest value.
+    pub fn to_rgba8(self) -> [u8; 4] {
+        self.to_array_f32()
+            .map(|channel| (channel.clamp(0.0, 1.0) * 255.0).round() as u8)
+    }
+
+    /// The channels as `[r, g, b, a]`.
+    pub fn to_array_f32(self) -> [f32; 4] {
+        [self.r, self.g, self.b, self.a]
+    }
+
+    /// The channels as `[r, g, b, a]` widened to `f64`.
+    pub fn to_array_f64(self) -> [f64; 4] {
+        self.to_array_f32().map(f64::from)
+    }
+
+    /// Linearly interpolate each channel towards `other`; `t = 0` gives
+    /// `self` and `t = 1` gives `other`.
+    pub fn lerp(self, other: Self, t: f32) -> Self {
+        let mix = |a: f32, b: f32| a + (b - a) * t;
+        Self::new(
+            mix(self.r, other.r),
+            mix(self.g, other.g),
+            mix(self.b, other.b),
+            mix(self.a, other.a),
+        )
+    }
+
+    /// Decode sRGB-encoded RGB channels to linear light.
+    pub fn to_linear(self) -> Self {
+        Self::new(
+            srgb_to_linear(self.r),
+            srgb_to_linear(self.g),
+            srgb_to_linear(self.b),
+            self.a,
+        )
+    }
+
+    /// Encode linear RGB channels with the sRGB transfer function.
+    pub fn to_srgb(self) -> Self {
+        Self::new(
+            linear_to_srgb(self.r),
+            linear_to_srgb(self.g),
+            linear_to_srgb(self.b),
+            self.a,
+        )
+    }
+
+    /// A color clear value for a render pass attachment.
+    pub fn to_clear_value(self) -> vk::ClearValue {
+        vk::ClearValue {
+            color: vk::ClearColorValue {
+                float32: self.to_array_f32(),
+            },
+        }
+    }
+}
+
+impl From<[f32; 4]> for Color {
+    fn from([r, g, b, a]: [f32; 4]) -> Self {
+        Self::new(r, g, b, a)
+    }
+}
+
+/// Pack a color into a `u32` as `0xRRGGBBAA`: red in the most significant
+/// byte, alpha in the least. Channels are clamped to `[0, 1]` and rounded to
+/// 8 bits.
+///
+/// The in-memory byte order of the result follows the target's endianness;
+/// use `to_be_bytes` to get `[r, g, b, a]` in memory.
+pub fn pack_rgba8(color: Color) -> u32 {
+    u32::from_be_bytes(color.to_rgba8())
+}
+
+/// Unpack a `0xRRGGBBAA` value produced by [`pack_rgba8`].
+pub fn unpack_rgba8(packed: u32) -> Color {
+    let [r, g, b, a] = packed.to_be_bytes();
+    Color::from_rgba8(r, g, b, a)
+}
+
+/// Pack a color into a `u32` as `0xBBGGRRAA`: blue in the most significant
+/// byte, alpha in the least. Channels are clamped and rounded as in
+/// [`pack_rgba8`].
+pub fn pack_bgra8(color: Color) -> u32 {
+    let [r, g, b, a] = color.to_rgba8();
+    u32::from_be_bytes([b, g, r, a])
+}
+
+/// Unpack a `0xBBGGRRAA` value produced by [`pack_bgra8`].
+pub fn unpack_bgra8(packed: u32) -> Color {
+    let [b, g, r, a] = packed.to_be_bytes();
+    Color::from_rgba8(r, g, b, a)
+}
+
+/// A color gradient sampled by a normalized value, e.g. for heatmaps.
+///
+/// Channels are interpolated linearly in whatever space the stop colors are
+/// in; samples outside the first and last stop clamp to their colors.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Gradient {
+    stops: Vec<(f32, Color)>,
+}
+
+impl Gradient {
+    /// Create a gradient from `(stop, color)` pairs in any order.
+    ///
+    /// Fails if there are no stops or a stop is not finite.
+    pub fn new(stops: impl IntoIterator<Item = (f32, Color)>) -> Result<Self> {
+        let mut stops: Vec<(f32, Color)> = stops.into_iter().collect();
+        if stops.is_empty() {
+            return Err(Error::Validation(
+                "gradient needs at least one stop".to_string(),
+            ));
+        }
+        if let Some((stop, _)) = stops.iter().find(|(stop, _)| !stop.is_finite()) {
+            return Err(Error::Validation(format!(
+                "gradient stop {} is not finite",
+                stop
+            )));
+        }
+        stops.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+        Ok(Self { stops })
+    }
+
+    /// An approximation of matplotlib's perceptually uniform viridis
+    /// colormap, from dark purple at 0 to yellow at 1, in sRGB.
+    pub fn viridis() -> Self {
+        const STOPS: [(f32, [u8; 3]); 9] = [
+            (0.0, [0x44, 0x01, 0x54]),
+            (0.125, [0x48, 0x28, 0x78]),
+            (0.25, [0x3e, 0x49, 0x89]),
+            (0.375, [0x31, 0x68, 0x8e]),
+            (0.5, [0x26, 0x82, 0x8e]),
+            (0.625, [0x1f, 0x9e, 0x89]),
+            (0.75, [0x35, 0xb7, 0x79]),
+            (0.875, [0x6e, 0xce, 0x58]),
+            (1.0, [0xfd, 0xe7, 0x25]),
+        ];
+        Self {
+            stops: STOPS
+                .iter()
+                .map(|&(stop, [r, g, b])| (stop, Color::from_rgba8(r, g, b, 255)))
+                .collect(),
+        }
+    }
+
+    /// The stops, sorted by position.
+    pub fn stops(&self) -> &[(f32, Color)] {
+        &self.stops
+    }
+
+    /// The color at `t`, interpolated between the surrounding stops.
+    pub fn sample(&self, t: f32) -> Color {
+        let upper = self.stops.partition_point(|(stop, _)| *stop <= t);
+        match (
+            upper.checked_sub(1).map(|lower| self.stops[lower]),
+            self.stops.get(upper),
+        ) {
+            (Some((start, from)), Some(&(end, to))) => from.lerp(to, (t - start) / (end - start)),
+            (Some((_, color)), None) | (None, Some(&(_, color))) => color,
+            (None, None) => unreachable!("gradients have at least one stop"),
+        }
+    }
+}
+
+fn srgb_to_linear(channel: f32) -> f32 {
+    if channel <= 0.04045 {
+        channel / 12.92
+    } else {
+        ((channel + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(channel: f32) -> f32 {
+    if channel <= 0.003_130_8 {
+        channel * 12.92
+    } else {
+        1.055 * channel.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_rgba8_divides_by_255() {
+        let color = Color::from_rgba8(255, 0, 51, 255);
+        assert_eq!(color.to_array_f32(), [1.0, 0.0, 0.2, 1.0]);
+        assert_eq!(color.to_array_f64()[2], 0.2f32 as f64);
+        assert_eq!(color.to_rgba8(), [255, 0, 51, 255]);
+    }
+
+    #[test]
+    fn test_srgb_round_trip() {
+        let half = Color::new(0.5, 0.5, 0.5, 0.5).to_linear();
+        // sRGB 0.5 is roughly 21.4% linear light; alpha is not converted.
+        assert!((half.r - 0.214_041).abs() < 1e-5);
+        assert_eq!(half.a, 0.5);
+
+        let round_trip = half.to_srgb();
+        assert!((round_trip.r - 0.5).abs() < 1e-5);
+        for value in [0, 1, 10, 128, 200, 255] {
+            let color = Color::from_rgba8(value, value, value, 255);
+            assert_eq!(color.to_linear().to_srgb().to_rgba8(), color.to_rgba8());
+        }
+    }
+
+    #[test]
+    fn test_packed_byte_layout() {
+        let red = Color::new(1.0, 0.0, 0.0, 1.0);
+        assert_eq!(pack_rgba8(red), 0xFF00_00FF);
+        assert_eq!(pack_bgra8(red), 0x0000_FFFF);
+        assert_eq!(pack_rgba8(red).to_be_bytes(), [255, 0, 0, 255]);
+
+        // Out-of-range channels clamp before quantizing.
+        assert_eq!(pack_rgba8(Color::new(2.0, -1.0, 0.5, 1.0)), 0xFF00_80FF);
+
+        for color in [
+            red,
+            Color::TRANSPARENT,
+            Color::WHITE,
+            Color::from_rgba8(12, 34, 56, 78),
+        ] {
+            assert_eq!(unpack_rgba8(pack_rgba8(color)), color);
+            assert_eq!(unpack_bgra8(pack_bgra8(color)), color);
+        }
+        assert_eq!(unpack_bgra8(0x3822_0C4E), Color::from_rgba8(12, 34, 56, 78));
+    }
+
+    #[test]
+    fn test_gradient_sampling() {
+        let red = Color::new(1.0, 0.0, 0.0, 1.0);
+        let blue = Color::new(0.0, 0.0, 1.0, 1.0);
+        let gradient = Gradient::new([(1.0, blue), (0.0, red), (0.5, Color::WHITE)]).unwrap();
+        assert_eq!(gradient.stops()[0], (0.0, red));
+
+        // At, between and outside the stops.
+        assert_eq!(gradient.sample(0.0), red);
+        assert_eq!(gradient.sample(0.5), Color::WHITE);
+        assert_eq!(gradient.sample(1.0), blue);
+        assert_eq!(gradient.sample(0.25), Color::new(1.0, 0.5, 0.5, 1.0));
+        assert_eq!(gradient.sample(0.75), Color::new(0.5, 0.5, 1.0, 1.0));
+        assert_eq!(gradient.sample(-3.0), red);
+        assert_eq!(gradient.sample(7.5), blue);
+
+        let single = Gradient::new([(0.3, red)]).unwrap();
+        assert_eq!(single.sample(0.0), red);
+        assert_eq!(single.sample(1.0), red);
+
+        assert!(Gradient::new([]).is_err());
+        assert!(Gradient::new([(f32::NAN, red)]).is_err());
+    }
+
+    #[test]
+    fn test_viridis_runs_dark_purple_to_yellow() {
+        let viridis = Gradient::viridis();
+        assert_eq!(viridis.sample(0.0).to_rgba8(), [0x44, 0x01, 0x54, 255]);
+        assert_eq!(viridis.sample(1.0).to_rgba8(), [0xfd, 0xe7, 0x25, 255]);
+        // Brightness increases monotonically along the map.
+        let luma = |c: Color| 0.2126 * c.r + 0.7152 * c.g + 0.0722 * c.b;
+        for i in 0..20 {
+            let (a, b) = (i as f32 / 20.0, (i + 1) as f32 / 20.0);
+            assert!(luma(viridis.sample(a)) < luma(viridis.sample(b)));
+        }
+    }
+}
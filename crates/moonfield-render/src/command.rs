@@ -1,13 +1,24 @@
 //! Vulkan command pool and command buffer abstractions.
 
+use crate::buffer::Buffer;
+use crate::color::Color;
+use crate::descriptor::{validate_dynamic_offset, DynamicOffset};
 use crate::device::Device;
 use crate::error::{Error, Result};
+use crate::format;
+use crate::pipeline::{self, GraphicsPipeline};
+use crate::query::QueryPool;
+use crate::viewport::{Scissor, Viewport};
 use ash::vk;
+use std::cell::Cell;
+use std::ffi::CString;
 
 /// A Vulkan command pool.
 pub struct CommandPool {
     pool: vk::CommandPool,
     device: ash::Device,
+    debug_utils: Option<ash::ext::debug_utils::Device>,
+    limits: vk::PhysicalDeviceLimits,
 }
 
 impl CommandPool {
@@ -27,6 +38,8 @@ impl CommandPool {
         Ok(Self {
             pool,
             device: device.raw().clone(),
+            debug_utils: device.debug_utils().cloned(),
+            limits: device.properties().limits,
         })
     }
 
@@ -54,7 +67,11 @@ impl CommandPool {
             buffer: buffers[0],
             pool: self.pool,
             device: self.device.clone(),
+            debug_utils: self.debug_utils.clone(),
+            limits: self.limits,
             recording: false,
+            debug_group_depth: 0,
+            bound_uses_blend_constant: Cell::new(None),
         })
     }
 }
@@ -72,7 +89,14 @@ pub struct CommandBuffer {
     buffer: vk::CommandBuffer,
     pool: vk::CommandPool,
     device: ash::Device,
+    debug_utils: Option<ash::ext::debug_utils::Device>,
+    /// Limits of the device, for validating dynamic state.
+    limits: vk::PhysicalDeviceLimits,
     recording: bool,
+    debug_group_depth: u32,
+    /// Whether the pipeline bound with [`CommandBuffer::bind_pipeline`]
+    /// reads the blend constant; `None` when unknown.
+    bound_uses_blend_constant: Cell<Option<bool>>,
 }
 
 impl CommandBuffer {
@@ -94,7 +118,13 @@ impl CommandBuffer {
     }
 
     /// End recording this command buffer.
+    ///
+    /// Every debug group pushed while recording must have been popped.
     pub fn end(&mut self) -> Result<()> {
+        debug_assert_eq!(
+            self.debug_group_depth, 0,
+            "command buffer ended with unpopped debug groups"
+        );
         unsafe {
             self.device
                 .end_command_buffer(self.buffer)
@@ -104,6 +134,61 @@ impl CommandBuffer {
         Ok(())
     }
 
+    /// Open a labeled region of commands, shown in GPU capture tools such
+    /// as RenderDoc. Regions nest and must be closed with
+    /// [`pop_debug_group`](Self::pop_debug_group).
+    ///
+    /// Without `VK_EXT_debug_utils` only the nesting depth is tracked.
+    pub fn push_debug_group(&mut self, label: &str) {
+        if let Some(debug_utils) = &self.debug_utils {
+            let label = debug_label(label);
+            let label_info = vk::DebugUtilsLabelEXT::default().label_name(&label);
+            unsafe {
+                debug_utils.cmd_begin_debug_utils_label(self.buffer, &label_info);
+            }
+        }
+        self.debug_group_depth += 1;
+    }
+
+    /// Close the innermost region opened by
+    /// [`push_debug_group`](Self::push_debug_group).
+    ///
+    /// An unmatched pop is ignored in release builds rather than recording
+    /// an end label with no begin, which is invalid usage.
+    pub fn pop_debug_group(&mut self) {
+        debug_assert!(
+            self.debug_group_depth > 0,
+            "pop_debug_group without a matching push_debug_group"
+        );
+        if self.debug_group_depth == 0 {
+            return;
+        }
+        self.debug_group_depth -= 1;
+        if let Some(debug_utils) = &self.debug_utils {
+            unsafe {
+                debug_utils.cmd_end_debug_utils_label(self.buffer);
+            }
+        }
+    }
+
+    /// Insert a single labeled marker between commands.
+    ///
+    /// A no-op without `VK_EXT_debug_utils`.
+    pub fn insert_debug_marker(&self, label: &str) {
+        if let Some(debug_utils) = &self.debug_utils {
+            let label = debug_label(label);
+            let label_info = vk::DebugUtilsLabelEXT::default().label_name(&label);
+            unsafe {
+                debug_utils.cmd_insert_debug_utils_label(self.buffer, &label_info);
+            }
+        }
+    }
+
+    /// Number of debug groups currently open.
+    pub fn debug_group_depth(&self) -> u32 {
+        self.debug_group_depth
+    }
+
     /// Begin a render pass.
     pub fn begin_render_pass(
         &self,
@@ -124,13 +209,129 @@ impl CommandBuffer {
     }
 
     /// Bind a graphics pipeline.
+    pub fn bind_pipeline(&self, pipeline: &GraphicsPipeline) {
+        self.bind_graphics_pipeline(pipeline.raw());
+        self.bound_uses_blend_constant
+            .set(Some(pipeline.uses_blend_constant()));
+    }
+
+    /// Bind a graphics pipeline by its raw handle.
     pub fn bind_graphics_pipeline(&self, pipeline: vk::Pipeline) {
+        self.bound_uses_blend_constant.set(None);
         unsafe {
             self.device
                 .cmd_bind_pipeline(self.buffer, vk::PipelineBindPoint::GRAPHICS, pipeline);
         }
     }
 
+    /// Set the viewport of pipelines created with
+    /// [`PipelineState::dynamic_viewport`](crate::PipelineState::dynamic_viewport),
+    /// validated against the device's limits.
+    pub fn set_viewport(&self, viewport: &Viewport) -> Result<()> {
+        viewport.validate(&self.limits)?;
+        unsafe {
+            self.device
+                .cmd_set_viewport(self.buffer, 0, &[viewport.to_vk()]);
+        }
+        Ok(())
+    }
+
+    /// Set the scissor of pipelines created with
+    /// [`PipelineState::dynamic_viewport`](crate::PipelineState::dynamic_viewport).
+    pub fn set_scissor(&self, scissor: &Scissor) -> Result<()> {
+        scissor.validate()?;
+        unsafe {
+            self.device
+                .cmd_set_scissor(self.buffer, 0, &[scissor.to_vk()]);
+        }
+        Ok(())
+    }
+
+    /// Set the stencil reference value for both faces, used by pipelines
+    /// whose [`StencilState`](crate::StencilState) needs a reference.
+    pub fn set_stencil_reference(&self, reference: u32) {
+        unsafe {
+            self.device.cmd_set_stencil_reference(
+                self.buffer,
+                vk::StencilFaceFlags::FRONT_AND_BACK,
+                reference,
+            );
+        }
+    }
+
+    /// Set the blend constant read by `CONSTANT_*` blend factors.
+    ///
+    /// Only pipelines whose blend state uses the constant accept it; in
+    /// debug builds, setting it while a pipeline bound with
+    /// [`bind_pipeline`](Self::bind_pipeline) does not use it panics.
+    pub fn set_blend_constant(&self, color: Color) {
+        debug_assert_ne!(
+            self.bound_uses_blend_constant.get(),
+            Some(false),
+            "the bound pipeline's blend state does not use the blend constant"
+        );
+        unsafe {
+            self.device
+                .cmd_set_blend_constants(self.buffer, &color.to_array_f32());
+        }
+    }
+
+    /// Update `data` in the push constants of `pipeline`'s layout for
+    /// `stages`, starting at byte `offset`.
+    ///
+    /// The offset and data size must be multiples of 4. Every byte must lie
+    /// in one of the pipeline's push constant ranges for each of `stages`,
+    /// and `stages` must include all stages of any range the bytes overlap.
+    pub fn set_push_constants(
+        &self,
+        pipeline: &GraphicsPipeline,
+        stages: vk::ShaderStageFlags,
+        offset: u32,
+        data: &[u8],
+    ) -> Result<()> {
+        pipeline::validate_push_constants(
+            pipeline.push_constant_ranges(),
+            stages,
+            offset,
+            u32::try_from(data.len()).unwrap_or(u32::MAX),
+        )?;
+        unsafe {
+            self.device
+                .cmd_push_constants(self.buffer, pipeline.layout(), stages, offset, data);
+        }
+        Ok(())
+    }
+
+    /// Bind descriptor sets for graphics pipelines using `layout`, starting
+    /// at set index `first_set`.
+    ///
+    /// `dynamic_offsets` supplies one offset per dynamic descriptor in the
+    /// bound sets, in binding order. Each is checked against the range its
+    /// descriptor was written with before anything is recorded.
+    pub fn bind_descriptor_sets(
+        &self,
+        layout: vk::PipelineLayout,
+        first_set: u32,
+        descriptor_sets: &[vk::DescriptorSet],
+        dynamic_offsets: &[DynamicOffset],
+    ) -> Result<()> {
+        for dynamic_offset in dynamic_offsets {
+            validate_dynamic_offset(&self.limits, dynamic_offset)?;
+        }
+        let dynamic_offsets: Vec<u32> = dynamic_offsets.iter().map(|d| d.offset).collect();
+        unsafe {
+            self.device.cmd_bind_descriptor_sets(
+                self.buffer,
+                vk::PipelineBindPoint::GRAPHICS,
+                layout,
+                first_set,
+                descriptor_sets,
+                &dynamic_offsets,
+            );
+        }
+        Ok(())
+    }
+
     /// Bind vertex buffers.
     pub fn bind_vertex_buffers(
         &self,
@@ -163,6 +364,281 @@ impl CommandBuffer {
         }
     }
 
+    /// Bind an index buffer for subsequent indexed draws.
+    ///
+    /// Only `UINT16` and `UINT32` indices are supported, and `offset` must be
+    /// a multiple of the index size. With primitive restart enabled, the
+    /// restart value is [`format::primitive_restart_index`] of `index_type`.
+    pub fn bind_index_buffer(
+        &self,
+        buffer: vk::Buffer,
+        offset: vk::DeviceSize,
+        index_type: vk::IndexType,
+    ) -> Result<()> {
+        let index_size = format::index_size(index_type)
+            .ok_or_else(|| Error::Validation(format!("unsupported index type {:?}", index_type)))?;
+        if !offset.is_multiple_of(index_size as vk::DeviceSize) {
+            return Err(Error::Validation(format!(
+                "index buffer offset {} is not a multiple of the {}-byte index size",
+                offset, index_size
+            )));
+        }
+
+        unsafe {
+            self.device
+                .cmd_bind_index_buffer(self.buffer, buffer, offset, index_type);
+        }
+        Ok(())
+    }
+
+    /// Draw indexed vertices from the bound index buffer.
+    pub fn draw_indexed(
+        &self,
+        index_count: u32,
+        instance_count: u32,
+        first_index: u32,
+        vertex_offset: i32,
+        first_instance: u32,
+    ) {
+        unsafe {
+            self.device.cmd_draw_indexed(
+                self.buffer,
+                index_count,
+                instance_count,
+                first_index,
+                vertex_offset,
+                first_instance,
+            );
+        }
+    }
+
+    /// Draw with the `vk::DrawIndirectCommand` stored at `offset` in
+    /// `buffer`.
+    ///
+    /// The buffer needs `INDIRECT_BUFFER` usage, and `offset` must be 4-byte
+    /// aligned with the whole command inside the buffer.
+    pub fn draw_indirect(&self, buffer: &Buffer, offset: vk::DeviceSize) -> Result<()> {
+        validate_indirect_buffer(
+            buffer,
+            offset,
+            std::mem::size_of::<vk::DrawIndirectCommand>(),
+        )?;
+        unsafe {
+            self.device
+                .cmd_draw_indirect(self.buffer, buffer.raw(), offset, 1, 0);
+        }
+        Ok(())
+    }
+
+    /// Draw indexed vertices from the bound index buffer with the
+    /// `vk::DrawIndexedIndirectCommand` stored at `offset` in `buffer`.
+    ///
+    /// Same requirements as [`draw_indirect`](Self::draw_indirect).
+    pub fn draw_indexed_indirect(&self, buffer: &Buffer, offset: vk::DeviceSize) -> Result<()> {
+        validate_indirect_buffer(
+            buffer,
+            offset,
+            std::mem::size_of::<vk::DrawIndexedIndirectCommand>(),
+        )?;
+        unsafe {
+            self.device
+                .cmd_draw_indexed_indirect(self.buffer, buffer.raw(), offset, 1, 0);
+        }
+        Ok(())
+    }
+
+    /// Reset `query_count` queries of `queries` starting at `first_query`.
+    ///
+    /// Queries must be reset before they are written, outside a render pass.
+    pub fn reset_query_pool(
+        &self,
+        queries: &QueryPool,
+        first_query: u32,
+        query_count: u32,
+    ) -> Result<()> {
+        queries.validate_range(first_query, query_count)?;
+        unsafe {
+            self.device
+                .cmd_reset_query_pool(self.buffer, queries.raw(), first_query, query_count);
+        }
+        Ok(())
+    }
+
+    /// Write a timestamp into `query` of the timestamp query pool `queries`
+    /// once all previously submitted commands reach `stage`.
+    pub fn write_timestamp(
+        &self,
+        stage: vk::PipelineStageFlags,
+        queries: &QueryPool,
+        query: u32,
+    ) -> Result<()> {
+        queries.validate_type(vk::QueryType::TIMESTAMP)?;
+        queries.validate_range(query, 1)?;
+        unsafe {
+            self.device
+                .cmd_write_timestamp(self.buffer, stage, queries.raw(), query);
+        }
+        Ok(())
+    }
+
+    /// Start counting samples that pass the depth and stencil tests into
+    /// `query` of the occlusion query pool `queries`.
+    ///
+    /// The query must have been reset and is ended with
+    /// [`end_occlusion_query`](Self::end_occlusion_query) in the same
+    /// subpass.
+    pub fn begin_occlusion_query(&self, queries: &QueryPool, query: u32) -> Result<()> {
+        queries.validate_type(vk::QueryType::OCCLUSION)?;
+        queries.validate_range(query, 1)?;
+        unsafe {
+            self.device.cmd_begin_query(
+                self.buffer,
+                queries.raw(),
+                query,
+                vk::QueryControlFlags::empty(),
+            );
+        }
+        Ok(())
+    }
+
+    /// Stop counting samples into `query` of `queries`.
+    pub fn end_occlusion_query(&self, queries: &QueryPool, query: u32) -> Result<()> {
+        queries.validate_type(vk::QueryType::OCCLUSION)?;
+        queries.validate_range(query, 1)?;
+        unsafe {
+            self.device.cmd_end_query(self.buffer, queries.raw(), query);
+        }
+        Ok(())
+    }
+
+    /// Copy `query_count` results starting at `first_query` into `buffer`
+    /// at `offset` as 64-bit values, waiting on the GPU for them to become
+    /// available.
+    ///
+    /// The buffer needs `TRANSFER_DST` usage and the offset must be a
+    /// multiple of 8. Must be recorded outside a render pass.
+    pub fn copy_query_results(
+        &self,
+        queries: &QueryPool,
+        first_query: u32,
+        query_count: u32,
+        buffer: &Buffer,
+        offset: vk::DeviceSize,
+    ) -> Result<()> {
+        queries.validate_range(first_query, query_count)?;
+        const RESULT_SIZE: vk::DeviceSize = std::mem::size_of::<u64>() as vk::DeviceSize;
+        if !buffer.usage().contains(vk::BufferUsageFlags::TRANSFER_DST) {
+            return Err(Error::Validation(
+                "query result buffer must have TRANSFER_DST usage".to_string(),
+            ));
+        }
+        if !offset.is_multiple_of(RESULT_SIZE) {
+            return Err(Error::Validation(format!(
+                "query result offset {} is not a multiple of {}",
+                offset, RESULT_SIZE
+            )));
+        }
+        let size = query_count as vk::DeviceSize * RESULT_SIZE;
+        if offset.saturating_add(size) > buffer.size() {
+            return Err(Error::Validation(format!(
+                "{} query results at offset {} overrun the {}-byte buffer",
+                query_count,
+                offset,
+                buffer.size()
+            )));
+        }
+        unsafe {
+            self.device.cmd_copy_query_pool_results(
+                self.buffer,
+                queries.raw(),
+                first_query,
+                query_count,
+                buffer.raw(),
+                offset,
+                RESULT_SIZE,
+                vk::QueryResultFlags::TYPE_64 | vk::QueryResultFlags::WAIT,
+            );
+        }
+        Ok(())
+    }
+
+    /// Copy `size` bytes from `src` at `src_offset` into `dst` at
+    /// `dst_offset`.
+    ///
+    /// `src` needs `TRANSFER_SRC` and `dst` `TRANSFER_DST` usage, both ranges
+    /// must lie within their buffers and, within one buffer, must not
+    /// overlap. Must be recorded outside a render pass.
+    pub fn copy_buffer_to_buffer(
+        &self,
+        src: &Buffer,
+        src_offset: vk::DeviceSize,
+        dst: &Buffer,
+        dst_offset: vk::DeviceSize,
+        size: vk::DeviceSize,
+    ) -> Result<()> {
+        validate_buffer_copy(src, src_offset, dst, dst_offset, size)?;
+        let region = vk::BufferCopy::default()
+            .src_offset(src_offset)
+            .dst_offset(dst_offset)
+            .size(size);
+        unsafe {
+            self.device.cmd_copy_buffer(
+                self.buffer,
+                src.raw(),
+                dst.raw(),
+                std::slice::from_ref(&region),
+            );
+        }
+        Ok(())
+    }
+
+    /// Copy regions of a buffer into an image of format `dst_format`, e.g.
+    /// to upload texels from a staging buffer.
+    ///
+    /// Each region is checked with [`validate_buffer_image_copy`] before
+    /// anything is recorded.
+    pub fn copy_buffer_to_image(
+        &self,
+        src_buffer: vk::Buffer,
+        dst_image: vk::Image,
+        dst_format: vk::Format,
+        dst_layout: vk::ImageLayout,
+        regions: &[vk::BufferImageCopy],
+    ) -> Result<()> {
+        for region in regions {
+            validate_buffer_image_copy(region, dst_format)?;
+        }
+        unsafe {
+            self.device.cmd_copy_buffer_to_image(
+                self.buffer,
+                src_buffer,
+                dst_image,
+                dst_layout,
+                regions,
+            );
+        }
+        Ok(())
+    }
+
+    /// Copy regions of an image into a buffer.
+    pub fn copy_image_to_buffer(
+        &self,
+        src_image: vk::Image,
+        src_layout: vk::ImageLayout,
+        dst_buffer: vk::Buffer,
+        regions: &[vk::BufferImageCopy],
+    ) {
+        unsafe {
+            self.device.cmd_copy_image_to_buffer(
+                self.buffer,
+                src_image,
+                src_layout,
+                dst_buffer,
+                regions,
+            );
+        }
+    }
+
     /// Insert a pipeline barrier.
     pub fn pipeline_barrier(
         &self,
@@ -195,3 +671,320 @@ impl Drop for CommandBuffer {
         }
     }
 }
+
+/// Convert a debug label to a C string, truncating at an interior nul.
+fn debug_label(label: &str) -> CString {
+    let end = label.find('\0').unwrap_or(label.len());
+    CString::new(&label[..end]).expect("label has no interior nul")
+}
+
+fn validate_indirect_buffer(
+    buffer: &Buffer,
+    offset: vk::DeviceSize,
+    command_size: usize,
+) -> Result<()> {
+    if !buffer
+        .usage()
+        .contains(vk::BufferUsageFlags::INDIRECT_BUFFER)
+    {
+        return Err(Error::Validation(
+            "indirect draws need a buffer with INDIRECT_BUFFER usage".to_string(),
+        ));
+    }
+    if !offset.is_multiple_of(4) {
+        return Err(Error::Validation(format!(
+            "indirect draw offset {} is not a multiple of 4",
+            offset
+        )));
+    }
+    if offset.saturating_add(command_size as vk::DeviceSize) > buffer.size() {
+        return Err(Error::Validation(format!(
+            "indirect draw command at offset {} overruns the {}-byte buffer",
+            offset,
+            buffer.size()
+        )));
+    }
+    Ok(())
+}
+
+fn validate_buffer_copy(
+    src: &Buffer,
+    src_offset: vk::DeviceSize,
+    dst: &Buffer,
+    dst_offset: vk::DeviceSize,
+    size: vk::DeviceSize,
+) -> Result<()> {
+    if !src.usage().contains(vk::BufferUsageFlags::TRANSFER_SRC) {
+        return Err(Error::Validation(
+            "copy source buffer must have TRANSFER_SRC usage".to_string(),
+        ));
+    }
+    if !dst.usage().contains(vk::BufferUsageFlags::TRANSFER_DST) {
+        return Err(Error::Validation(
+            "copy destination buffer must have TRANSFER_DST usage".to_string(),
+        ));
+    }
+    if size == 0 {
+        return Err(Error::Validation(
+            "buffer copy size must be non-zero".to_string(),
+        ));
+    }
+    for (role, buffer, offset) in [
+        ("source", src, src_offset),
+        ("destination", dst, dst_offset),
+    ] {
+        if offset
+            .checked_add(size)
+            .is_none_or(|end| end > buffer.size())
+        {
+            return Err(Error::Validation(format!(
+                "copy {} range {}..{} overruns the {}-byte buffer",
+                role,
+                offset,
+                offset as u128 + size as u128,
+                buffer.size()
+            )));
+        }
+    }
+    if src.raw() == dst.raw() && src_offset < dst_offset + size && dst_offset < src_offset + size {
+        return Err(Error::Validation(format!(
+            "copy ranges {}..{} and {}..{} of the same buffer overlap",
+            src_offset,
+            src_offset + size,
+            dst_offset,
+            dst_offset + size
+        )));
+    }
+    Ok(())
+}
+
+/// Check a buffer/image copy region against the Vulkan layout rules for
+/// `format`.
+///
+/// The buffer offset must be a multiple of both the texel size and 4, and a
+/// non-zero `buffer_row_length`/`buffer_image_height` (in texels; zero means
+/// tightly packed) must cover the copied extent.
+pub fn validate_buffer_image_copy(region: &vk::BufferImageCopy, format: vk::Format) -> Result<()> {
+    let texel_size = format::texel_size(format)
+        .ok_or_else(|| Error::Validation(format!("unsupported copy format {:?}", format)))?;
+    // The least common multiple of the texel size and 4.
+    let offset_alignment = match texel_size % 4 {
+        0 => texel_size,
+        2 => texel_size * 2,
+        _ => texel_size * 4,
+    } as vk::DeviceSize;
+    if !region.buffer_offset.is_multiple_of(offset_alignment) {
+        return Err(Error::Validation(format!(
+            "buffer offset {} must be a multiple of {} for {:?}",
+            region.buffer_offset, offset_alignment, format
+        )));
+    }
+    let extent = region.image_extent;
+    if region.buffer_row_length != 0 && region.buffer_row_length < extent.width {
+        return Err(Error::Validation(format!(
+            "buffer row length {} is shorter than the copy width {}",
+            region.buffer_row_length, extent.width
+        )));
+    }
+    if region.buffer_image_height != 0 && region.buffer_image_height < extent.height {
+        return Err(Error::Validation(format!(
+            "buffer image height {} is shorter than the copy height {}",
+            region.buffer_image_height, extent.height
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::InstanceOptions;
+
+    fn copy_region(
+        offset: vk::DeviceSize,
+        row_length: u32,
+        image_height: u32,
+    ) -> vk::BufferImageCopy {
+        vk::BufferImageCopy::default()
+            .buffer_offset(offset)
+            .buffer_row_length(row_length)
+            .buffer_image_height(image_height)
+            .image_extent(vk::Extent3D {
+                width: 16,
+                height: 8,
+                depth: 1,
+            })
+    }
+
+    #[test]
+    fn test_validate_buffer_image_copy() {
+        let rgba8 = vk::Format::R8G8B8A8_UNORM;
+        assert!(validate_buffer_image_copy(&copy_region(0, 0, 0), rgba8).is_ok());
+        assert!(validate_buffer_image_copy(&copy_region(256, 64, 8), rgba8).is_ok());
+
+        // Offsets must be texel- and 4-byte aligned.
+        assert!(validate_buffer_image_copy(&copy_region(2, 0, 0), rgba8).is_err());
+        assert!(validate_buffer_image_copy(&copy_region(2, 0, 0), vk::Format::R8_UNORM).is_err());
+        assert!(
+            validate_buffer_image_copy(&copy_region(8, 0, 0), vk::Format::R32G32B32A32_SFLOAT)
+                .is_err()
+        );
+        let rgb16f = vk::Format::R16G16B16_SFLOAT;
+        assert!(validate_buffer_image_copy(&copy_region(6, 0, 0), rgb16f).is_err());
+        assert!(validate_buffer_image_copy(&copy_region(8, 0, 0), rgb16f).is_err());
+        assert!(validate_buffer_image_copy(&copy_region(12, 0, 0), rgb16f).is_ok());
+
+        // Rows and layers must be at least as large as the copied extent.
+        let err = validate_buffer_image_copy(&copy_region(0, 15, 0), rgba8).unwrap_err();
+        assert!(err.to_string().contains("row length"));
+        assert!(validate_buffer_image_copy(&copy_region(0, 0, 7), rgba8).is_err());
+
+        assert!(validate_buffer_image_copy(
+            &copy_region(0, 0, 0),
+            vk::Format::BC1_RGBA_UNORM_BLOCK
+        )
+        .is_err());
+    }
+
+    /// Needs a Vulkan device; skipped when no driver is available.
+    #[test]
+    fn test_bind_index_buffer_validates_type_and_offset() {
+        let Some((instance, device, _allocator)) = crate::test_util::headless_gpu() else {
+            return;
+        };
+
+        // Two triangles forming a quad.
+        let indices: [u16; 6] = [0, 1, 2, 2, 3, 0];
+        let index_buffer = Buffer::new_with_data(
+            &instance,
+            &device,
+            &indices,
+            vk::BufferUsageFlags::INDEX_BUFFER,
+        )
+        .expect("index buffer");
+
+        let pool = CommandPool::new(&device, device.queue_family_indices().graphics)
+            .expect("command pool");
+        let mut command_buffer = pool.allocate_command_buffer().expect("command buffer");
+        command_buffer
+            .begin(vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT)
+            .expect("begin");
+
+        assert!(command_buffer
+            .bind_index_buffer(index_buffer.raw(), 1, vk::IndexType::UINT16)
+            .is_err());
+        assert!(command_buffer
+            .bind_index_buffer(index_buffer.raw(), 0, vk::IndexType::NONE_KHR)
+            .is_err());
+        command_buffer
+            .bind_index_buffer(index_buffer.raw(), 0, vk::IndexType::UINT16)
+            .expect("bind index buffer");
+        command_buffer.end().expect("end");
+    }
+
+    /// Needs a Vulkan device; skipped when no driver is available.
+    #[test]
+    fn test_validate_indirect_buffer() {
+        let Some((instance, device, _allocator)) = crate::test_util::headless_gpu() else {
+            return;
+        };
+
+        let args = [vk::DrawIndirectCommand {
+            vertex_count: 3,
+            instance_count: 1,
+            first_vertex: 0,
+            first_instance: 0,
+        }];
+        let indirect_buffer = Buffer::new_with_data(
+            &instance,
+            &device,
+            &args,
+            vk::BufferUsageFlags::INDIRECT_BUFFER,
+        )
+        .expect("indirect buffer");
+        let vertex_buffer = Buffer::new_with_data(
+            &instance,
+            &device,
+            &args,
+            vk::BufferUsageFlags::VERTEX_BUFFER,
+        )
+        .expect("vertex buffer");
+
+        let draw_size = std::mem::size_of::<vk::DrawIndirectCommand>();
+        assert!(validate_indirect_buffer(&vertex_buffer, 0, draw_size).is_err());
+        assert!(validate_indirect_buffer(&indirect_buffer, 2, draw_size).is_err());
+        assert!(validate_indirect_buffer(&indirect_buffer, 4, draw_size).is_err());
+        // The indexed command is larger than the buffer holding one draw.
+        assert!(validate_indirect_buffer(
+            &indirect_buffer,
+            0,
+            std::mem::size_of::<vk::DrawIndexedIndirectCommand>(),
+        )
+        .is_err());
+        validate_indirect_buffer(&indirect_buffer, 0, draw_size).expect("valid indirect draw");
+    }
+
+    /// Needs a Vulkan device; skipped when no driver is available.
+    #[test]
+    fn test_validate_buffer_copy() {
+        let Some((instance, device, _allocator)) = crate::test_util::headless_gpu() else {
+            return;
+        };
+
+        let usage = vk::BufferUsageFlags::TRANSFER_SRC | vk::BufferUsageFlags::TRANSFER_DST;
+        let staging = Buffer::new(&instance, &device, 64, usage).expect("staging buffer");
+        let target = Buffer::new(&instance, &device, 32, usage).expect("target buffer");
+        let vertices = Buffer::new(&instance, &device, 64, vk::BufferUsageFlags::VERTEX_BUFFER)
+            .expect("vertex buffer");
+
+        validate_buffer_copy(&staging, 16, &target, 0, 32).expect("valid copy");
+        assert!(validate_buffer_copy(&vertices, 0, &target, 0, 16).is_err());
+        assert!(validate_buffer_copy(&staging, 0, &vertices, 0, 16).is_err());
+        assert!(validate_buffer_copy(&staging, 0, &target, 0, 0).is_err());
+        assert!(validate_buffer_copy(&staging, 48, &target, 0, 32).is_err());
+        let err = validate_buffer_copy(&staging, 0, &target, 8, 32).unwrap_err();
+        assert!(err.to_string().contains("destination"), "{err}");
+        assert!(validate_buffer_copy(&staging, 0, &staging, 32, 32).is_ok());
+        assert!(validate_buffer_copy(&staging, 0, &staging, 16, 32).is_err());
+    }
+
+    #[test]
+    fn test_debug_label_truncates_at_nul() {
+        assert_eq!(debug_label("shadow pass").as_bytes(), b"shadow pass");
+        assert_eq!(debug_label("bad\0label").as_bytes(), b"bad");
+    }
+
+    /// Needs a Vulkan device; skipped when no driver is available.
+    #[test]
+    fn test_nested_debug_groups_balance() {
+        for debug_utils in [true, false] {
+            let options = InstanceOptions {
+                validation: false,
+                debug_utils,
+            };
+            let Some((_instance, device, _allocator)) =
+                crate::test_util::headless_gpu_with(options)
+            else {
+                return;
+            };
+
+            let pool = CommandPool::new(&device, device.queue_family_indices().graphics)
+                .expect("command pool");
+            let mut command_buffer = pool.allocate_command_buffer().expect("command buffer");
+            command_buffer
+                .begin(vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT)
+                .expect("begin");
+
+            command_buffer.push_debug_group("frame");
+            command_buffer.push_debug_group("shadow pass");
+            assert_eq!(command_buffer.debug_group_depth(), 2);
+            command_buffer.insert_debug_marker("cascade 0");
+            command_buffer.pop_debug_group();
+            command_buffer.push_debug_group("main pass");
+            command_buffer.pop_debug_group();
+            command_buffer.pop_debug_group();
+            assert_eq!(command_buffer.debug_group_depth(), 0);
+            command_buffer.end().expect("end");
+        }
+    }
+}
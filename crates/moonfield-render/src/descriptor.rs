@@ -0,0 +1,533 @@
+//! Vulkan descriptor set layout, pool and update helpers.
+
+use crate::buffer::Buffer;
+use crate::device::Device;
+use crate::error::{Error, Result};
+use ash::vk;
+
+/// A Vulkan descriptor set layout.
+pub struct DescriptorSetLayout {
+    layout: vk::DescriptorSetLayout,
+    device: ash::Device,
+}
+
+impl DescriptorSetLayout {
+    /// Create a descriptor set layout from explicit bindings.
+    pub fn new(device: &Device, bindings: &[vk::DescriptorSetLayoutBinding]) -> Result<Self> {
+        let create_info = vk::DescriptorSetLayoutCreateInfo::default().bindings(bindings);
+        let layout = unsafe {
+            device
+                .raw()
+                .create_descriptor_set_layout(&create_info, None)
+                .map_err(|e| {
+                    Error::Backend(format!("failed to create descriptor set layout: {:?}", e))
+                })?
+        };
+
+        Ok(Self {
+            layout,
+            device: device.raw().clone(),
+        })
+    }
+
+    /// Create a layout with a single uniform buffer at `binding`, visible to
+    /// `stages`.
+    pub fn uniform_buffer(
+        device: &Device,
+        binding: u32,
+        stages: vk::ShaderStageFlags,
+    ) -> Result<Self> {
+        let binding = vk::DescriptorSetLayoutBinding::default()
+            .binding(binding)
+            .descriptor_type(vk::DescriptorType::UNIFORM_BUFFER)
+            .descriptor_count(1)
+            .stage_flags(stages);
+        Self::new(device, &[binding])
+    }
+
+    /// Access the raw `vk::DescriptorSetLayout` handle.
+    pub fn raw(&self) -> vk::DescriptorSetLayout {
+        self.layout
+    }
+}
+
+impl Drop for DescriptorSetLayout {
+    fn drop(&mut self) {
+        unsafe {
+            self.device.destroy_descriptor_set_layout(self.layout, None);
+        }
+    }
+}
+
+/// A Vulkan descriptor pool.
+///
+/// Descriptor sets allocated from the pool are freed together with it.
+pub struct DescriptorPool {
+    pool: vk::DescriptorPool,
+    device: ash::Device,
+}
+
+impl DescriptorPool {
+    /// Create a pool able to allocate `max_sets` sets drawing from
+    /// `pool_sizes` descriptors.
+    pub fn new(
+        device: &Device,
+        max_sets: u32,
+        pool_sizes: &[vk::DescriptorPoolSize],
+    ) -> Result<Self> {
+        let create_info = vk::DescriptorPoolCreateInfo::default()
+            .max_sets(max_sets)
+            .pool_sizes(pool_sizes);
+        let pool = unsafe {
+            device
+                .raw()
+                .create_descriptor_pool(&create_info, None)
+                .map_err(|e| Error::Backend(format!("failed to create descriptor pool: {:?}", e)))?
+        };
+
+        Ok(Self {
+            pool,
+            device: device.raw().clone(),
+        })
+    }
+
+    /// Access the raw `vk::DescriptorPool` handle.
+    pub fn raw(&self) -> vk::DescriptorPool {
+        self.pool
+    }
+
+    /// Allocate one descriptor set with the given layout.
+    pub fn allocate(&self, layout: &DescriptorSetLayout) -> Result<vk::DescriptorSet> {
+        let layouts = [layout.raw()];
+        let allocate_info = vk::DescriptorSetAllocateInfo::default()
+            .descriptor_pool(self.pool)
+            .set_layouts(&layouts);
+
+        let sets = unsafe {
+            self.device
+                .allocate_descriptor_sets(&allocate_info)
+                .map_err(|e| {
+                    Error::Backend(format!("failed to allocate descriptor set: {:?}", e))
+                })?
+        };
+        Ok(sets[0])
+    }
+}
+
+impl Drop for DescriptorPool {
+    fn drop(&mut self) {
+        unsafe {
+            self.device.destroy_descriptor_pool(self.pool, None);
+        }
+    }
+}
+
+/// A byte range of a buffer bound to a buffer descriptor.
+#[derive(Clone, Copy)]
+pub struct BufferBinding<'a> {
+    pub buffer: &'a Buffer,
+    /// Start of the range; must be a multiple of the device's offset
+    /// alignment for the descriptor type.
+    pub offset: vk::DeviceSize,
+    /// Length of the range; `None` binds the rest of the buffer.
+    pub size: Option<vk::DeviceSize>,
+}
+
+impl<'a> BufferBinding<'a> {
+    /// Bind all of `buffer`.
+    pub fn whole(buffer: &'a Buffer) -> Self {
+        Self {
+            buffer,
+            offset: 0,
+            size: None,
+        }
+    }
+}
+
+/// Point the buffer descriptor at `binding` of `set` to `buffer`.
+///
+/// `descriptor_type` must be a uniform or storage buffer type, dynamic or
+/// not. The offset must be a multiple of the device's
+/// `min_uniform_buffer_offset_alignment` or
+/// `min_storage_buffer_offset_alignment` respectively (use [`align_up`] when
+/// packing several blocks into one buffer), and the range must fit in the
+/// buffer and in `max_uniform_buffer_range` or `max_storage_buffer_range`.
+pub fn write_buffer(
+    device: &Device,
+    set: vk::DescriptorSet,
+    binding: u32,
+    descriptor_type: vk::DescriptorType,
+    buffer: &BufferBinding,
+) -> Result<()> {
+    let range = check_buffer_range(
+        &device.properties().limits,
+        descriptor_type,
+        buffer.buffer.size(),
+        buffer.buffer.usage(),
+        buffer.offset,
+        buffer.size,
+    )?;
+
+    let buffer_info = vk::DescriptorBufferInfo::default()
+        .buffer(buffer.buffer.raw())
+        .offset(buffer.offset)
+        .range(range);
+    let write = vk::WriteDescriptorSet::default()
+        .dst_set(set)
+        .dst_binding(binding)
+        .descriptor_type(descriptor_type)
+        .buffer_info(std::slice::from_ref(&buffer_info));
+
+    unsafe {
+        device
+            .raw()
+            .update_descriptor_sets(std::slice::from_ref(&write), &[]);
+    }
+    Ok(())
+}
+
+/// Point the uniform buffer descriptor at `binding` of `set` to `range`
+/// bytes of `buffer` starting at `offset`.
+///
+/// Shorthand for [`write_buffer`] with `UNIFORM_BUFFER`.
+pub fn write_uniform_buffer(
+    device: &Device,
+    set: vk::DescriptorSet,
+    binding: u32,
+    buffer: &Buffer,
+    offset: vk::DeviceSize,
+    range: vk::DeviceSize,
+) -> Result<()> {
+    write_buffer(
+        device,
+        set,
+        binding,
+        vk::DescriptorType::UNIFORM_BUFFER,
+        &BufferBinding {
+            buffer,
+            offset,
+            size: Some(range),
+        },
+    )
+}
+
+/// A dynamic offset for a dynamic buffer descriptor, passed to
+/// [`CommandBuffer::bind_descriptor_sets`](crate::CommandBuffer::bind_descriptor_sets)
+/// together with the range the descriptor was written with so the offset
+/// can be checked.
+#[derive(Clone, Copy)]
+pub struct DynamicOffset<'a> {
+    /// `UNIFORM_BUFFER_DYNAMIC` or `STORAGE_BUFFER_DYNAMIC`.
+    pub descriptor_type: vk::DescriptorType,
+    /// The range the descriptor was written with.
+    pub binding: BufferBinding<'a>,
+    /// Bytes added to the range's offset.
+    pub offset: u32,
+}
+
+/// Check a dynamic offset passed when binding a set.
+///
+/// The offset must keep the descriptor's alignment, and the shifted range
+/// must still fit in the buffer.
+pub(crate) fn validate_dynamic_offset(
+    limits: &vk::PhysicalDeviceLimits,
+    dynamic_offset: &DynamicOffset,
+) -> Result<()> {
+    let DynamicOffset {
+        descriptor_type,
+        binding: buffer,
+        offset: dynamic_offset,
+    } = *dynamic_offset;
+    if !matches!(
+        descriptor_type,
+        vk::DescriptorType::UNIFORM_BUFFER_DYNAMIC | vk::DescriptorType::STORAGE_BUFFER_DYNAMIC
+    ) {
+        return Err(Error::Validation(format!(
+            "{:?} descriptors take no dynamic offset",
+            descriptor_type
+        )));
+    }
+    check_dynamic_range(
+        limits,
+        descriptor_type,
+        buffer.buffer.size(),
+        buffer.buffer.usage(),
+        buffer.offset,
+        buffer.size,
+        dynamic_offset,
+    )
+}
+
+/// Validate a buffer descriptor range and resolve its length.
+fn check_buffer_range(
+    limits: &vk::PhysicalDeviceLimits,
+    descriptor_type: vk::DescriptorType,
+    buffer_size: vk::DeviceSize,
+    buffer_usage: vk::BufferUsageFlags,
+    offset: vk::DeviceSize,
+    size: Option<vk::DeviceSize>,
+) -> Result<vk::DeviceSize> {
+    let (kind, usage, alignment, max_range) = match descriptor_type {
+        vk::DescriptorType::UNIFORM_BUFFER | vk::DescriptorType::UNIFORM_BUFFER_DYNAMIC => (
+            "uniform",
+            vk::BufferUsageFlags::UNIFORM_BUFFER,
+            limits.min_uniform_buffer_offset_alignment,
+            limits.max_uniform_buffer_range,
+        ),
+        vk::DescriptorType::STORAGE_BUFFER | vk::DescriptorType::STORAGE_BUFFER_DYNAMIC => (
+            "storage",
+            vk::BufferUsageFlags::STORAGE_BUFFER,
+            limits.min_storage_buffer_offset_alignment,
+            limits.max_storage_buffer_range,
+        ),
+        _ => {
+            return Err(Error::Validation(format!(
+                "{:?} is not a buffer descriptor type",
+                descriptor_type
+            )))
+        }
+    };
+    if !buffer_usage.contains(usage) {
+        return Err(Error::Validation(format!(
+            "{} buffer binding needs {:?} usage",
+            kind, usage
+        )));
+    }
+    if !offset.is_multiple_of(alignment) {
+        return Err(Error::Validation(format!(
+            "{} buffer offset {} is not a multiple of the required alignment {}",
+            kind, offset, alignment
+        )));
+    }
+    let range = size.unwrap_or_else(|| buffer_size.saturating_sub(offset));
+    if range == 0 {
+        return Err(Error::Validation(format!(
+            "{} buffer binding at offset {} is empty",
+            kind, offset
+        )));
+    }
+    if range > max_range as vk::DeviceSize {
+        return Err(Error::Validation(format!(
+            "{} buffer range {} exceeds the device limit {}",
+            kind, range, max_range
+        )));
+    }
+    if offset
+        .checked_add(range)
+        .is_none_or(|end| end > buffer_size)
+    {
+        return Err(Error::Validation(format!(
+            "{} buffer range {}..{} exceeds the buffer size {}",
+            kind,
+            offset,
+            offset.saturating_add(range),
+            buffer_size
+        )));
+    }
+    Ok(range)
+}
+
+/// Validate a dynamic offset applied to a descriptor range: the shifted
+/// range must pass the same checks as the written one.
+fn check_dynamic_range(
+    limits: &vk::PhysicalDeviceLimits,
+    descriptor_type: vk::DescriptorType,
+    buffer_size: vk::DeviceSize,
+    buffer_usage: vk::BufferUsageFlags,
+    offset: vk::DeviceSize,
+    size: Option<vk::DeviceSize>,
+    dynamic_offset: u32,
+) -> Result<()> {
+    let range = check_buffer_range(
+        limits,
+        descriptor_type,
+        buffer_size,
+        buffer_usage,
+        offset,
+        size,
+    )?;
+    check_buffer_range(
+        limits,
+        descriptor_type,
+        buffer_size,
+        buffer_usage,
+        offset + dynamic_offset as vk::DeviceSize,
+        Some(range),
+    )?;
+    Ok(())
+}
+
+/// Round `value` up to the next multiple of `alignment`.
+///
+/// `alignment` must be a power of two, as Vulkan guarantees for
+/// `min_uniform_buffer_offset_alignment`.
+pub fn align_up(value: vk::DeviceSize, alignment: vk::DeviceSize) -> vk::DeviceSize {
+    debug_assert!(alignment.is_power_of_two());
+    (value + alignment - 1) & !(alignment - 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CommandPool;
+
+    fn limits() -> vk::PhysicalDeviceLimits {
+        vk::PhysicalDeviceLimits {
+            min_uniform_buffer_offset_alignment: 256,
+            min_storage_buffer_offset_alignment: 64,
+            max_uniform_buffer_range: 16384,
+            max_storage_buffer_range: 1 << 27,
+            ..Default::default()
+        }
+    }
+
+    const UNIFORM: vk::BufferUsageFlags = vk::BufferUsageFlags::UNIFORM_BUFFER;
+    const STORAGE: vk::BufferUsageFlags = vk::BufferUsageFlags::STORAGE_BUFFER;
+
+    #[test]
+    fn test_buffer_range_alignment_depends_on_type() {
+        let ty = vk::DescriptorType::UNIFORM_BUFFER;
+        assert_eq!(
+            check_buffer_range(&limits(), ty, 1024, UNIFORM, 256, Some(256)).unwrap(),
+            256
+        );
+        // The rest of the buffer when no size is given.
+        assert_eq!(
+            check_buffer_range(&limits(), ty, 1024, UNIFORM, 512, None).unwrap(),
+            512
+        );
+        assert!(check_buffer_range(&limits(), ty, 1024, UNIFORM, 64, Some(64)).is_err());
+
+        let ty = vk::DescriptorType::STORAGE_BUFFER;
+        assert!(check_buffer_range(&limits(), ty, 1024, STORAGE, 64, Some(64)).is_ok());
+        assert!(check_buffer_range(&limits(), ty, 1024, STORAGE, 32, Some(64)).is_err());
+        // The buffer must have been created for the descriptor type.
+        assert!(check_buffer_range(&limits(), ty, 1024, UNIFORM, 0, None).is_err());
+        assert!(check_buffer_range(
+            &limits(),
+            vk::DescriptorType::SAMPLER,
+            1024,
+            UNIFORM,
+            0,
+            None
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_buffer_range_size_limits() {
+        let uniform = vk::DescriptorType::UNIFORM_BUFFER;
+        let big = 1 << 20;
+        // Larger than max_uniform_buffer_range, but fine for storage.
+        assert!(check_buffer_range(&limits(), uniform, big, UNIFORM, 0, Some(32768)).is_err());
+        assert!(check_buffer_range(&limits(), uniform, big, UNIFORM, 0, None).is_err());
+        assert!(check_buffer_range(
+            &limits(),
+            vk::DescriptorType::STORAGE_BUFFER,
+            big,
+            STORAGE,
+            0,
+            Some(32768)
+        )
+        .is_ok());
+
+        assert!(check_buffer_range(&limits(), uniform, 1024, UNIFORM, 768, Some(512)).is_err());
+        assert!(check_buffer_range(&limits(), uniform, 1024, UNIFORM, 0, Some(0)).is_err());
+        assert!(check_buffer_range(&limits(), uniform, 1024, UNIFORM, 1024, None).is_err());
+    }
+
+    #[test]
+    fn test_dynamic_offset_stays_aligned_and_in_bounds() {
+        let ty = vk::DescriptorType::UNIFORM_BUFFER_DYNAMIC;
+        let check = |dynamic_offset| {
+            check_dynamic_range(&limits(), ty, 1024, UNIFORM, 0, Some(256), dynamic_offset)
+        };
+        assert!(check(0).is_ok());
+        assert!(check(768).is_ok());
+        assert!(check(128).is_err());
+        assert!(check(1024).is_err());
+    }
+
+    #[test]
+    fn test_align_up_rounds_to_alignment() {
+        assert_eq!(align_up(0, 256), 0);
+        assert_eq!(align_up(1, 256), 256);
+        assert_eq!(align_up(256, 256), 256);
+        assert_eq!(align_up(257, 64), 320);
+    }
+
+    /// Needs a Vulkan device; skipped when no driver is available.
+    #[test]
+    fn test_uniform_buffer_set_binds() {
+        let Some((instance, device, _allocator)) = crate::test_util::headless_gpu() else {
+            return;
+        };
+
+        let layout = DescriptorSetLayout::uniform_buffer(&device, 0, vk::ShaderStageFlags::VERTEX)
+            .expect("set layout");
+        let pool = DescriptorPool::new(
+            &device,
+            1,
+            &[vk::DescriptorPoolSize {
+                ty: vk::DescriptorType::UNIFORM_BUFFER,
+                descriptor_count: 1,
+            }],
+        )
+        .expect("descriptor pool");
+        let set = pool.allocate(&layout).expect("descriptor set");
+
+        let mvp = [0.0f32; 16];
+        let uniforms = Buffer::new_with_data(
+            &instance,
+            &device,
+            &mvp,
+            vk::BufferUsageFlags::UNIFORM_BUFFER,
+        )
+        .expect("uniform buffer");
+        write_uniform_buffer(&device, set, 0, &uniforms, 0, uniforms.size()).expect("write");
+        assert!(write_uniform_buffer(&device, set, 0, &uniforms, 0, uniforms.size() + 1).is_err());
+        write_buffer(
+            &device,
+            set,
+            0,
+            vk::DescriptorType::UNIFORM_BUFFER,
+            &BufferBinding::whole(&uniforms),
+        )
+        .expect("whole-buffer write");
+
+        let pipeline_layout_info = vk::PipelineLayoutCreateInfo::default()
+            .set_layouts(std::slice::from_ref(&layout.layout));
+        let pipeline_layout = unsafe {
+            device
+                .raw()
+                .create_pipeline_layout(&pipeline_layout_info, None)
+                .expect("pipeline layout")
+        };
+
+        let command_pool =
+            CommandPool::new(&device, device.queue_family_indices().graphics).expect("pool");
+        let mut command_buffer = command_pool
+            .allocate_command_buffer()
+            .expect("command buffer");
+        command_buffer
+            .begin(vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT)
+            .expect("begin");
+        // The set's descriptor is not dynamic, so it takes no offset.
+        let offset = DynamicOffset {
+            descriptor_type: vk::DescriptorType::UNIFORM_BUFFER,
+            binding: BufferBinding::whole(&uniforms),
+            offset: 0,
+        };
+        assert!(command_buffer
+            .bind_descriptor_sets(pipeline_layout, 0, &[set], &[offset])
+            .is_err());
+        command_buffer
+            .bind_descriptor_sets(pipeline_layout, 0, &[set], &[])
+            .expect("bind descriptor set");
+        command_buffer.end().expect("end");
+
+        unsafe {
+            device.raw().destroy_pipeline_layout(pipeline_layout, None);
+        }
+    }
+}
@@ -1,9 +1,12 @@
 //! Vulkan logical device abstraction.
 
+use crate::adapter::AdapterInfo;
 use crate::error::{Error, Result};
+use crate::features::Features;
+use crate::format;
 use crate::instance::Instance;
 use ash::vk;
-use std::ffi::{c_char, CStr};
+use std::ffi::{c_char, CStr, CString};
 
 const DEVICE_EXTENSIONS: &[&CStr] = &[ash::khr::swapchain::NAME];
 
@@ -64,10 +67,14 @@ impl QueueFamilyIndices {
 /// Vulkan logical device and its primary queues.
 pub struct Device {
     physical_device: vk::PhysicalDevice,
+    properties: vk::PhysicalDeviceProperties,
+    enabled_features: vk::PhysicalDeviceFeatures,
     device: ash::Device,
     graphics_queue: vk::Queue,
     present_queue: vk::Queue,
     queue_family_indices: QueueFamilyIndices,
+    debug_utils: Option<ash::ext::debug_utils::Device>,
+    instance: ash::Instance,
 }
 
 impl Device {
@@ -75,6 +82,20 @@ impl Device {
     ///
     /// If `surface` is provided, presentation support is required.
     pub fn new(instance: &Instance, surface: Option<vk::SurfaceKHR>) -> Result<Self> {
+        Self::new_with_features(instance, surface, Features::empty())
+    }
+
+    /// Create a logical device with the optional `features` enabled on the
+    /// preferred physical device that supports all of them.
+    ///
+    /// Fails with a validation error naming the requested features the
+    /// preferred physical device does not support when no device supports
+    /// all of them.
+    pub fn new_with_features(
+        instance: &Instance,
+        surface: Option<vk::SurfaceKHR>,
+        features: Features,
+    ) -> Result<Self> {
         let physical_devices = instance.enumerate_physical_devices()?;
         if physical_devices.is_empty() {
             return Err(Error::Backend(
@@ -82,20 +103,19 @@ impl Device {
             ));
         }
 
-        // Prefer discrete GPU, then integrated, then any.
         let physical_device = physical_devices
             .iter()
             .copied()
-            .min_by_key(
-                |pd| match instance.physical_device_properties(*pd).device_type {
-                    vk::PhysicalDeviceType::DISCRETE_GPU => 0,
-                    vk::PhysicalDeviceType::INTEGRATED_GPU => 1,
-                    _ => 2,
-                },
-            )
+            .min_by_key(|pd| {
+                selection_key(
+                    instance.physical_device_properties(*pd).device_type,
+                    Self::supported_features(instance, *pd),
+                    features,
+                )
+            })
             .ok_or(Error::Unsupported)?;
 
-        Self::from_physical_device(instance, physical_device, surface)
+        Self::from_physical_device_with_features(instance, physical_device, surface, features)
     }
 
     /// Create a logical device from a specific physical device.
@@ -104,6 +124,30 @@ impl Device {
         physical_device: vk::PhysicalDevice,
         surface: Option<vk::SurfaceKHR>,
     ) -> Result<Self> {
+        Self::from_physical_device_with_features(
+            instance,
+            physical_device,
+            surface,
+            Features::empty(),
+        )
+    }
+
+    /// Create a logical device from a specific physical device with the
+    /// optional `features` enabled.
+    pub fn from_physical_device_with_features(
+        instance: &Instance,
+        physical_device: vk::PhysicalDevice,
+        surface: Option<vk::SurfaceKHR>,
+        features: Features,
+    ) -> Result<Self> {
+        let unsupported = features.difference(Self::supported_features(instance, physical_device));
+        if !unsupported.is_empty() {
+            return Err(Error::Validation(format!(
+                "requested device features are not supported: {}",
+                unsupported
+            )));
+        }
+
         let queue_family_indices = QueueFamilyIndices::find(instance, physical_device, surface)?;
 
         let unique_indices = queue_family_indices.unique_indices();
@@ -120,12 +164,12 @@ impl Device {
         let device_extension_names: Vec<*const c_char> =
             DEVICE_EXTENSIONS.iter().map(|name| name.as_ptr()).collect();
 
-        let features = vk::PhysicalDeviceFeatures::default();
+        let enabled_features = features.to_vk();
 
         let create_info = vk::DeviceCreateInfo::default()
             .queue_create_infos(&queue_create_infos)
             .enabled_extension_names(&device_extension_names)
-            .enabled_features(&features);
+            .enabled_features(&enabled_features);
 
         let device = unsafe {
             instance
@@ -137,15 +181,32 @@ impl Device {
         let graphics_queue = unsafe { device.get_device_queue(queue_family_indices.graphics, 0) };
         let present_queue = unsafe { device.get_device_queue(queue_family_indices.present, 0) };
 
+        let properties = instance.physical_device_properties(physical_device);
+        let debug_utils = instance
+            .debug_utils_enabled()
+            .then(|| ash::ext::debug_utils::Device::new(instance.raw(), &device));
+
         Ok(Self {
             physical_device,
+            properties,
+            enabled_features,
             device,
             graphics_queue,
             present_queue,
             queue_family_indices,
+            debug_utils,
+            instance: instance.raw().clone(),
         })
     }
 
+    /// The optional features `physical_device` supports.
+    pub fn supported_features(
+        instance: &Instance,
+        physical_device: vk::PhysicalDevice,
+    ) -> Features {
+        Features::from_vk(&instance.physical_device_features(physical_device))
+    }
+
     /// Access the raw `ash::Device`.
     pub fn raw(&self) -> &ash::Device {
         &self.device
@@ -156,6 +217,94 @@ impl Device {
         self.physical_device
     }
 
+    /// Access the properties of the underlying physical device.
+    pub fn properties(&self) -> &vk::PhysicalDeviceProperties {
+        &self.properties
+    }
+
+    /// Describe the physical device, e.g. for logs and bug reports.
+    pub fn adapter_info(&self) -> AdapterInfo {
+        AdapterInfo::from_properties(&self.properties)
+    }
+
+    /// The optional features enabled on this device.
+    pub fn features(&self) -> Features {
+        Features::from_vk(&self.enabled_features)
+    }
+
+    /// The core features enabled on this device.
+    pub fn enabled_features(&self) -> &vk::PhysicalDeviceFeatures {
+        &self.enabled_features
+    }
+
+    /// The features the physical device supports for `format`.
+    pub fn format_properties(&self, format: vk::Format) -> vk::FormatProperties {
+        // SAFETY: the physical device belongs to the instance the device was
+        // created from, which outlives the device.
+        unsafe {
+            self.instance
+                .get_physical_device_format_properties(self.physical_device, format)
+        }
+    }
+
+    /// Check that an optimally tiled 2D image of `format` can be created with
+    /// `usage` and `samples` samples per pixel.
+    ///
+    /// Fails with a validation error naming the usages the format does not
+    /// support, or the sample count the format does not support for `usage`.
+    pub fn validate_image_support(
+        &self,
+        format: vk::Format,
+        usage: vk::ImageUsageFlags,
+        samples: vk::SampleCountFlags,
+    ) -> Result<()> {
+        let supported = self.format_properties(format).optimal_tiling_features;
+        let unsupported = format::unsupported_usage(usage, supported);
+        if !unsupported.is_empty() {
+            return Err(Error::Validation(format!(
+                "image usage {:?} is not supported for format {:?}",
+                unsupported, format
+            )));
+        }
+
+        if samples.as_raw().count_ones() != 1 {
+            return Err(Error::Validation(format!(
+                "image sample count must be a single value, got {:?}",
+                samples
+            )));
+        }
+        if samples == vk::SampleCountFlags::TYPE_1 {
+            return Ok(());
+        }
+        // SAFETY: as in `format_properties`.
+        let properties = unsafe {
+            self.instance.get_physical_device_image_format_properties(
+                self.physical_device,
+                format,
+                vk::ImageType::TYPE_2D,
+                vk::ImageTiling::OPTIMAL,
+                usage,
+                vk::ImageCreateFlags::empty(),
+            )
+        }
+        .map_err(|e| Error::from_vk("failed to query image format properties", e))?;
+        if !properties.sample_counts.contains(samples) {
+            return Err(Error::Validation(format!(
+                "{:?} samples are not supported for {:?} images with usage {:?}",
+                samples, format, usage
+            )));
+        }
+        Ok(())
+    }
+
+    /// Nanoseconds per timestamp query tick.
+    ///
+    /// Multiply the difference of two timestamp query results by this value
+    /// to get a duration in nanoseconds.
+    pub fn timestamp_period(&self) -> f32 {
+        self.properties.limits.timestamp_period
+    }
+
     /// Access the graphics queue.
     pub fn graphics_queue(&self) -> vk::Queue {
         self.graphics_queue
@@ -170,6 +319,60 @@ impl Device {
     pub fn queue_family_indices(&self) -> QueueFamilyIndices {
         self.queue_family_indices
     }
+
+    /// Attach a debug name to a Vulkan object, shown by tools such as
+    /// RenderDoc and in validation messages.
+    ///
+    /// Does nothing when the instance was created without debug utils (see
+    /// [`InstanceOptions`](crate::InstanceOptions)); names containing a nul
+    /// byte are rejected either way.
+    pub fn set_object_name<H: vk::Handle>(&self, handle: H, name: &str) -> Result<()> {
+        let name = CString::new(name)
+            .map_err(|_| Error::Validation(format!("debug name {:?} contains a nul byte", name)))?;
+        let Some(debug_utils) = &self.debug_utils else {
+            return Ok(());
+        };
+        let name_info = vk::DebugUtilsObjectNameInfoEXT::default()
+            .object_handle(handle)
+            .object_name(&name);
+        unsafe {
+            debug_utils
+                .set_debug_utils_object_name(&name_info)
+                .map_err(|e| Error::Backend(format!("failed to set object name: {:?}", e)))
+        }
+    }
+
+    /// The `VK_EXT_debug_utils` device functions, if the instance enabled
+    /// the extension.
+    pub(crate) fn debug_utils(&self) -> Option<&ash::ext::debug_utils::Device> {
+        self.debug_utils.as_ref()
+    }
+
+    /// Block until all queues on this device have finished their submitted
+    /// work.
+    pub fn wait_idle(&self) -> Result<()> {
+        // SAFETY: the device is valid for the lifetime of `self`.
+        unsafe {
+            self.device
+                .device_wait_idle()
+                .map_err(|e| Error::Backend(format!("failed to wait for device idle: {:?}", e)))
+        }
+    }
+}
+
+/// Ordering key for physical device selection: devices supporting every
+/// requested feature first, then discrete GPUs, integrated GPUs and the rest.
+fn selection_key(
+    device_type: vk::PhysicalDeviceType,
+    supported: Features,
+    requested: Features,
+) -> (bool, u8) {
+    let rank = match device_type {
+        vk::PhysicalDeviceType::DISCRETE_GPU => 0,
+        vk::PhysicalDeviceType::INTEGRATED_GPU => 1,
+        _ => 2,
+    };
+    (!supported.contains(requested), rank)
 }
 
 impl Drop for Device {
@@ -179,3 +382,118 @@ impl Drop for Device {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Buffer, InstanceOptions};
+
+    /// Needs a Vulkan device; skipped when no driver is available.
+    #[test]
+    fn test_set_object_name_with_and_without_debug_utils() {
+        for debug_utils in [true, false] {
+            let options = InstanceOptions {
+                validation: false,
+                debug_utils,
+            };
+            let Some((instance, device, _allocator)) = crate::test_util::headless_gpu_with(options)
+            else {
+                return;
+            };
+
+            let buffer = Buffer::new(&instance, &device, 64, vk::BufferUsageFlags::UNIFORM_BUFFER)
+                .expect("buffer");
+            device
+                .set_object_name(buffer.raw(), "camera uniforms")
+                .expect("set name");
+            assert!(device.set_object_name(buffer.raw(), "bad\0name").is_err());
+        }
+    }
+
+    /// Needs a Vulkan device; skipped when no driver is available.
+    #[test]
+    fn test_validate_image_support_rejects_incompatible_usage() {
+        let Some((_instance, device, _allocator)) = crate::test_util::headless_gpu() else {
+            return;
+        };
+
+        let err = device
+            .validate_image_support(
+                vk::Format::BC1_RGBA_UNORM_BLOCK,
+                vk::ImageUsageFlags::SAMPLED | vk::ImageUsageFlags::STORAGE,
+                vk::SampleCountFlags::TYPE_1,
+            )
+            .expect_err("storage BC1 images must be rejected");
+        assert!(err.to_string().contains("STORAGE"), "{err}");
+
+        device
+            .validate_image_support(
+                vk::Format::R8G8B8A8_UNORM,
+                vk::ImageUsageFlags::COLOR_ATTACHMENT | vk::ImageUsageFlags::SAMPLED,
+                vk::SampleCountFlags::TYPE_1,
+            )
+            .expect("RGBA8 render attachment");
+        assert!(device
+            .validate_image_support(
+                vk::Format::R8G8B8A8_UNORM,
+                vk::ImageUsageFlags::COLOR_ATTACHMENT,
+                vk::SampleCountFlags::TYPE_1 | vk::SampleCountFlags::TYPE_4,
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn test_selection_prefers_devices_with_requested_features() {
+        let requested = Features::GEOMETRY_SHADER;
+        let discrete = selection_key(
+            vk::PhysicalDeviceType::DISCRETE_GPU,
+            Features::empty(),
+            requested,
+        );
+        let integrated =
+            selection_key(vk::PhysicalDeviceType::INTEGRATED_GPU, requested, requested);
+        let cpu = selection_key(vk::PhysicalDeviceType::CPU, requested, requested);
+        assert!(integrated < discrete);
+        assert!(integrated < cpu);
+        assert!(
+            selection_key(
+                vk::PhysicalDeviceType::DISCRETE_GPU,
+                Features::empty(),
+                Features::empty()
+            ) < integrated
+        );
+    }
+
+    /// Needs a Vulkan device; skipped when no driver is available.
+    #[test]
+    fn test_unsupported_feature_request_names_the_feature() {
+        let Some((instance, device, _allocator)) = crate::test_util::headless_gpu() else {
+            return;
+        };
+        let physical_device = device.physical_device();
+
+        let supported = Device::supported_features(&instance, physical_device);
+        let Some(missing) = [
+            Features::SHADER_FLOAT64,
+            Features::GEOMETRY_SHADER,
+            Features::TEXTURE_COMPRESSION_BC,
+            Features::WIDE_LINES,
+        ]
+        .into_iter()
+        .find(|feature| !supported.contains(*feature)) else {
+            eprintln!("skipping: device supports every probed feature");
+            return;
+        };
+
+        let err =
+            Device::from_physical_device_with_features(&instance, physical_device, None, missing)
+                .err()
+                .expect("unsupported feature must be rejected");
+        assert!(err.to_string().contains(&missing.to_string()), "{err}");
+
+        let device =
+            Device::from_physical_device_with_features(&instance, physical_device, None, supported)
+                .expect("device with every supported feature");
+        assert_eq!(device.features(), supported);
+    }
+}
@@ -18,6 +18,30 @@ pub enum Error {
     ShaderCompilation(String),
     /// Validation failed.
     Validation(String),
+    /// The surface changed (e.g. was resized) and the swapchain must be
+    /// recreated before presenting again.
+    SurfaceOutdated,
+    /// The surface is no longer usable, e.g. its window was destroyed.
+    SurfaceLost,
+    /// A wait or acquire did not complete within its timeout.
+    ///
+    /// Also covers `NOT_READY`, which Vulkan returns instead of `TIMEOUT`
+    /// when a zero timeout is used to poll, e.g. for an acquire or for query
+    /// results without `WAIT`.
+    Timeout,
+    /// The device was lost; none of its resources can be used anymore.
+    DeviceLost,
+}
+
+impl Error {
+    /// Convert a failed `vk::Result`, keeping the codes callers react to as
+    /// their own variants and describing any other code with `context`.
+    pub(crate) fn from_vk(context: &str, result: ash::vk::Result) -> Self {
+        match Error::from(result) {
+            Error::Backend(_) => Error::Backend(format!("{}: {:?}", context, result)),
+            err => err,
+        }
+    }
 }
 
 impl fmt::Display for Error {
@@ -28,6 +52,10 @@ impl fmt::Display for Error {
             Error::InvalidHandle => write!(f, "invalid handle"),
             Error::ShaderCompilation(msg) => write!(f, "shader compilation failed: {}", msg),
             Error::Validation(msg) => write!(f, "validation failed: {}", msg),
+            Error::SurfaceOutdated => write!(f, "surface is out of date"),
+            Error::SurfaceLost => write!(f, "surface was lost"),
+            Error::Timeout => write!(f, "operation timed out"),
+            Error::DeviceLost => write!(f, "device was lost"),
         }
     }
 }
@@ -36,7 +64,15 @@ impl std::error::Error for Error {}
 
 impl From<ash::vk::Result> for Error {
     fn from(result: ash::vk::Result) -> Self {
-        Error::Backend(format!("{:?}", result))
+        use ash::vk::Result as VkResult;
+        match result {
+            VkResult::ERROR_OUT_OF_DATE_KHR => Error::SurfaceOutdated,
+            VkResult::ERROR_SURFACE_LOST_KHR => Error::SurfaceLost,
+            // NOT_READY is the zero-timeout form of TIMEOUT.
+            VkResult::TIMEOUT | VkResult::NOT_READY => Error::Timeout,
+            VkResult::ERROR_DEVICE_LOST => Error::DeviceLost,
+            other => Error::Backend(format!("{:?}", other)),
+        }
     }
 }
 
@@ -45,3 +81,47 @@ impl From<ash::LoadingError> for Error {
         Error::Backend(format!("failed to load Vulkan: {}", err))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ash::vk;
+
+    #[test]
+    fn test_vk_results_convert_to_matching_variants() {
+        let cases = [
+            (
+                vk::Result::ERROR_OUT_OF_DATE_KHR,
+                Error::SurfaceOutdated,
+                "out of date",
+            ),
+            (
+                vk::Result::ERROR_SURFACE_LOST_KHR,
+                Error::SurfaceLost,
+                "surface was lost",
+            ),
+            (vk::Result::TIMEOUT, Error::Timeout, "timed out"),
+            (vk::Result::NOT_READY, Error::Timeout, "timed out"),
+            (
+                vk::Result::ERROR_DEVICE_LOST,
+                Error::DeviceLost,
+                "device was lost",
+            ),
+        ];
+        for (result, expected, message) in cases {
+            let err = Error::from(result);
+            assert_eq!(err, expected);
+            assert!(err.to_string().contains(message), "{err}");
+            assert_eq!(Error::from_vk("failed to present", result), expected);
+        }
+
+        let err = Error::from_vk(
+            "failed to submit frame",
+            vk::Result::ERROR_OUT_OF_DEVICE_MEMORY,
+        );
+        assert_eq!(
+            err.to_string(),
+            "backend error: failed to submit frame: ERROR_OUT_OF_DEVICE_MEMORY"
+        );
+    }
+}
@@ -0,0 +1,193 @@
+//! Optional device features that must be requested at device creation.
+
+use crate::error::{Error, Result};
+use ash::vk;
+use std::fmt;
+use std::ops::{BitOr, BitOrAssign};
+use std::str::FromStr;
+
+/// A set of optional Vulkan core features.
+///
+/// Sets are written as `|`-separated feature names, e.g.
+/// `sampler_anisotropy | texture_compression_bc`, matching the
+/// `vk::PhysicalDeviceFeatures` field names.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Features(u32);
+
+type FeatureField = fn(&mut vk::PhysicalDeviceFeatures) -> &mut vk::Bool32;
+
+const FEATURES: &[(Features, &str, FeatureField)] = &[
+    (Features::SAMPLER_ANISOTROPY, "sampler_anisotropy", |f| {
+        &mut f.sampler_anisotropy
+    }),
+    (
+        Features::TEXTURE_COMPRESSION_BC,
+        "texture_compression_bc",
+        |f| &mut f.texture_compression_bc,
+    ),
+    (Features::FILL_MODE_NON_SOLID, "fill_mode_non_solid", |f| {
+        &mut f.fill_mode_non_solid
+    }),
+    (Features::WIDE_LINES, "wide_lines", |f| &mut f.wide_lines),
+    (Features::DEPTH_CLAMP, "depth_clamp", |f| &mut f.depth_clamp),
+    (Features::INDEPENDENT_BLEND, "independent_blend", |f| {
+        &mut f.independent_blend
+    }),
+    (Features::GEOMETRY_SHADER, "geometry_shader", |f| {
+        &mut f.geometry_shader
+    }),
+    (Features::TESSELLATION_SHADER, "tessellation_shader", |f| {
+        &mut f.tessellation_shader
+    }),
+    (Features::MULTI_DRAW_INDIRECT, "multi_draw_indirect", |f| {
+        &mut f.multi_draw_indirect
+    }),
+    (
+        Features::PIPELINE_STATISTICS_QUERY,
+        "pipeline_statistics_query",
+        |f| &mut f.pipeline_statistics_query,
+    ),
+    (Features::SHADER_FLOAT64, "shader_float64", |f| {
+        &mut f.shader_float64
+    }),
+];
+
+impl Features {
+    pub const SAMPLER_ANISOTROPY: Self = Self(1 << 0);
+    pub const TEXTURE_COMPRESSION_BC: Self = Self(1 << 1);
+    pub const FILL_MODE_NON_SOLID: Self = Self(1 << 2);
+    pub const WIDE_LINES: Self = Self(1 << 3);
+    pub const DEPTH_CLAMP: Self = Self(1 << 4);
+    pub const INDEPENDENT_BLEND: Self = Self(1 << 5);
+    pub const GEOMETRY_SHADER: Self = Self(1 << 6);
+    pub const TESSELLATION_SHADER: Self = Self(1 << 7);
+    pub const MULTI_DRAW_INDIRECT: Self = Self(1 << 8);
+    pub const PIPELINE_STATISTICS_QUERY: Self = Self(1 << 9);
+    pub const SHADER_FLOAT64: Self = Self(1 << 10);
+
+    /// The empty set.
+    pub const fn empty() -> Self {
+        Self(0)
+    }
+
+    /// Whether no feature is set.
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Whether every feature in `other` is also in `self`.
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// The features in `self` that are not in `other`.
+    pub const fn difference(self, other: Self) -> Self {
+        Self(self.0 & !other.0)
+    }
+
+    /// The features enabled in a Vulkan feature struct.
+    pub fn from_vk(features: &vk::PhysicalDeviceFeatures) -> Self {
+        let mut features = *features;
+        FEATURES
+            .iter()
+            .filter(|(_, _, field)| *field(&mut features) == vk::TRUE)
+            .fold(Self::empty(), |set, (feature, _, _)| set | *feature)
+    }
+
+    /// A Vulkan feature struct enabling exactly this set.
+    pub fn to_vk(self) -> vk::PhysicalDeviceFeatures {
+        let mut features = vk::PhysicalDeviceFeatures::default();
+        for (feature, _, field) in FEATURES {
+            if self.contains(*feature) {
+                *field(&mut features) = vk::TRUE;
+            }
+        }
+        features
+    }
+}
+
+impl BitOr for Features {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for Features {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
+impl fmt::Display for Features {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut names = FEATURES
+            .iter()
+            .filter(|(feature, _, _)| self.contains(*feature))
+            .map(|(_, name, _)| name);
+        if let Some(first) = names.next() {
+            write!(f, "{}", first)?;
+        }
+        for name in names {
+            write!(f, " | {}", name)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Features {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        s.split('|')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .try_fold(Self::empty(), |set, name| {
+                FEATURES
+                    .iter()
+                    .find(|(_, known, _)| *known == name)
+                    .map(|(feature, _, _)| set | *feature)
+                    .ok_or_else(|| Error::Validation(format!("unknown device feature {:?}", name)))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_features_round_trip_through_strings_and_vk() {
+        let features = Features::SAMPLER_ANISOTROPY | Features::TEXTURE_COMPRESSION_BC;
+        assert_eq!(
+            features.to_string(),
+            "sampler_anisotropy | texture_compression_bc"
+        );
+        assert_eq!(features.to_string().parse::<Features>(), Ok(features));
+        assert_eq!(
+            " depth_clamp|wide_lines ".parse::<Features>().unwrap(),
+            Features::DEPTH_CLAMP | Features::WIDE_LINES
+        );
+        assert_eq!("".parse::<Features>(), Ok(Features::empty()));
+        assert!("ray_query".parse::<Features>().is_err());
+
+        let vk_features = features.to_vk();
+        assert_eq!(vk_features.sampler_anisotropy, vk::TRUE);
+        assert_eq!(vk_features.geometry_shader, vk::FALSE);
+        assert_eq!(Features::from_vk(&vk_features), features);
+
+        let all = FEATURES
+            .iter()
+            .fold(Features::empty(), |set, (feature, _, _)| set | *feature);
+        assert_eq!(Features::from_vk(&all.to_vk()), all);
+        let without_float64 = all.difference(Features::SHADER_FLOAT64);
+        assert!(!without_float64.contains(Features::SHADER_FLOAT64));
+        assert!(without_float64.contains(Features::GEOMETRY_SHADER | Features::WIDE_LINES));
+        assert_eq!(
+            (Features::SHADER_FLOAT64 | Features::WIDE_LINES).difference(Features::SHADER_FLOAT64),
+            Features::WIDE_LINES
+        );
+        assert!(Features::WIDE_LINES.difference(all).is_empty());
+    }
+}
@@ -0,0 +1,655 @@
+//! Texel format, image extent and index type helpers.
+//!
+//! Size and component queries over `vk::Format` and `vk::Extent3D`, used to
+//! size staging and readback buffers and to describe vertex attributes. Only
+//! uncompressed color and depth formats are covered; block-compressed formats
+//! return `None`.
+
+use ash::vk;
+
+/// Size in bytes of a single texel of `format`, or `None` for formats this
+/// module does not know about (including block-compressed formats).
+///
+/// Combined depth/stencil formats report the packed size of both aspects;
+/// `D32_SFLOAT_S8_UINT` includes the 24 bits of padding implementations may
+/// store after the stencil byte.
+pub fn texel_size(format: vk::Format) -> Option<u32> {
+    let size = match format {
+        vk::Format::R8_UNORM
+        | vk::Format::R8_SNORM
+        | vk::Format::R8_UINT
+        | vk::Format::R8_SINT
+        | vk::Format::R8_SRGB
+        | vk::Format::S8_UINT => 1,
+        vk::Format::R8G8_UNORM
+        | vk::Format::R8G8_SNORM
+        | vk::Format::R8G8_UINT
+        | vk::Format::R8G8_SINT
+        | vk::Format::R16_UNORM
+        | vk::Format::R16_SNORM
+        | vk::Format::R16_UINT
+        | vk::Format::R16_SINT
+        | vk::Format::R16_SFLOAT
+        | vk::Format::D16_UNORM => 2,
+        vk::Format::D16_UNORM_S8_UINT => 3,
+        vk::Format::R8G8B8A8_UNORM
+        | vk::Format::R8G8B8A8_SNORM
+        | vk::Format::R8G8B8A8_UINT
+        | vk::Format::R8G8B8A8_SINT
+        | vk::Format::R8G8B8A8_SRGB
+        | vk::Format::B8G8R8A8_UNORM
+        | vk::Format::B8G8R8A8_SRGB
+        | vk::Format::A2B10G10R10_UNORM_PACK32
+        | vk::Format::B10G11R11_UFLOAT_PACK32
+        | vk::Format::R16G16_UNORM
+        | vk::Format::R16G16_SNORM
+        | vk::Format::R16G16_UINT
+        | vk::Format::R16G16_SINT
+        | vk::Format::R16G16_SFLOAT
+        | vk::Format::R32_UINT
+        | vk::Format::R32_SINT
+        | vk::Format::R32_SFLOAT
+        | vk::Format::D32_SFLOAT
+        | vk::Format::D24_UNORM_S8_UINT
+        | vk::Format::X8_D24_UNORM_PACK32 => 4,
+        vk::Format::R16G16B16A16_UNORM
+        | vk::Format::R16G16B16A16_SNORM
+        | vk::Format::R16G16B16A16_UINT
+        | vk::Format::R16G16B16A16_SINT
+        | vk::Format::R16G16B16A16_SFLOAT
+        | vk::Format::R32G32_UINT
+        | vk::Format::R32G32_SINT
+        | vk::Format::R32G32_SFLOAT
+        | vk::Format::D32_SFLOAT_S8_UINT => 8,
+        vk::Format::R16G16B16_SFLOAT => 6,
+        vk::Format::R32G32B32_UINT | vk::Format::R32G32B32_SINT | vk::Format::R32G32B32_SFLOAT => {
+            12
+        }
+        vk::Format::R32G32B32A32_UINT
+        | vk::Format::R32G32B32A32_SINT
+        | vk::Format::R32G32B32A32_SFLOAT => 16,
+        _ => return None,
+    };
+    Some(size)
+}
+
+/// How shaders read the components of a format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SampleKind {
+    /// Floating point, including normalized integer formats.
+    Float,
+    /// Signed integer.
+    Sint,
+    /// Unsigned integer.
+    Uint,
+}
+
+/// Number of components of `format` (e.g. 3 for `R32G32B32_SFLOAT`), or
+/// `None` for formats [`texel_size`] does not know about.
+///
+/// Depth/stencil formats count the depth and stencil aspects separately;
+/// padding such as the `X8` in `X8_D24_UNORM_PACK32` is not counted.
+pub fn component_count(format: vk::Format) -> Option<u32> {
+    let count = match format {
+        vk::Format::R8_UNORM
+        | vk::Format::R8_SNORM
+        | vk::Format::R8_UINT
+        | vk::Format::R8_SINT
+        | vk::Format::R8_SRGB
+        | vk::Format::R16_UNORM
+        | vk::Format::R16_SNORM
+        | vk::Format::R16_UINT
+        | vk::Format::R16_SINT
+        | vk::Format::R16_SFLOAT
+        | vk::Format::R32_UINT
+        | vk::Format::R32_SINT
+        | vk::Format::R32_SFLOAT
+        | vk::Format::S8_UINT
+        | vk::Format::D16_UNORM
+        | vk::Format::X8_D24_UNORM_PACK32
+        | vk::Format::D32_SFLOAT => 1,
+        vk::Format::R8G8_UNORM
+        | vk::Format::R8G8_SNORM
+        | vk::Format::R8G8_UINT
+        | vk::Format::R8G8_SINT
+        | vk::Format::R16G16_UNORM
+        | vk::Format::R16G16_SNORM
+        | vk::Format::R16G16_UINT
+        | vk::Format::R16G16_SINT
+        | vk::Format::R16G16_SFLOAT
+        | vk::Format::R32G32_UINT
+        | vk::Format::R32G32_SINT
+        | vk::Format::R32G32_SFLOAT
+        | vk::Format::D16_UNORM_S8_UINT
+        | vk::Format::D24_UNORM_S8_UINT
+        | vk::Format::D32_SFLOAT_S8_UINT => 2,
+        vk::Format::B10G11R11_UFLOAT_PACK32
+        | vk::Format::R16G16B16_SFLOAT
+        | vk::Format::R32G32B32_UINT
+        | vk::Format::R32G32B32_SINT
+        | vk::Format::R32G32B32_SFLOAT => 3,
+        vk::Format::R8G8B8A8_UNORM
+        | vk::Format::R8G8B8A8_SNORM
+        | vk::Format::R8G8B8A8_UINT
+        | vk::Format::R8G8B8A8_SINT
+        | vk::Format::R8G8B8A8_SRGB
+        | vk::Format::B8G8R8A8_UNORM
+        | vk::Format::B8G8R8A8_SRGB
+        | vk::Format::A2B10G10R10_UNORM_PACK32
+        | vk::Format::R16G16B16A16_UNORM
+        | vk::Format::R16G16B16A16_SNORM
+        | vk::Format::R16G16B16A16_UINT
+        | vk::Format::R16G16B16A16_SINT
+        | vk::Format::R16G16B16A16_SFLOAT
+        | vk::Format::R32G32B32A32_UINT
+        | vk::Format::R32G32B32A32_SINT
+        | vk::Format::R32G32B32A32_SFLOAT => 4,
+        _ => return None,
+    };
+    Some(count)
+}
+
+/// Whether `format` stores normalized integers that shaders read as floats
+/// in `[0, 1]` (`UNORM`, `SRGB`) or `[-1, 1]` (`SNORM`).
+///
+/// For combined depth/stencil formats this describes the depth aspect.
+pub fn is_normalized(format: vk::Format) -> bool {
+    matches!(
+        format,
+        vk::Format::R8_UNORM
+            | vk::Format::R8_SNORM
+            | vk::Format::R8_SRGB
+            | vk::Format::R8G8_UNORM
+            | vk::Format::R8G8_SNORM
+            | vk::Format::R8G8B8A8_UNORM
+            | vk::Format::R8G8B8A8_SNORM
+            | vk::Format::R8G8B8A8_SRGB
+            | vk::Format::B8G8R8A8_UNORM
+            | vk::Format::B8G8R8A8_SRGB
+            | vk::Format::A2B10G10R10_UNORM_PACK32
+            | vk::Format::R16_UNORM
+            | vk::Format::R16_SNORM
+            | vk::Format::R16G16_UNORM
+            | vk::Format::R16G16_SNORM
+            | vk::Format::R16G16B16A16_UNORM
+            | vk::Format::R16G16B16A16_SNORM
+            | vk::Format::D16_UNORM
+            | vk::Format::D16_UNORM_S8_UINT
+            | vk::Format::X8_D24_UNORM_PACK32
+            | vk::Format::D24_UNORM_S8_UINT
+    )
+}
+
+/// How shaders read `format`, or `None` for formats [`texel_size`] does not
+/// know about.
+///
+/// For combined depth/stencil formats this describes the depth aspect.
+pub fn sample_kind(format: vk::Format) -> Option<SampleKind> {
+    let kind = match format {
+        vk::Format::R8_UINT
+        | vk::Format::R8G8_UINT
+        | vk::Format::R8G8B8A8_UINT
+        | vk::Format::R16_UINT
+        | vk::Format::R16G16_UINT
+        | vk::Format::R16G16B16A16_UINT
+        | vk::Format::R32_UINT
+        | vk::Format::R32G32_UINT
+        | vk::Format::R32G32B32_UINT
+        | vk::Format::R32G32B32A32_UINT
+        | vk::Format::S8_UINT => SampleKind::Uint,
+        vk::Format::R8_SINT
+        | vk::Format::R8G8_SINT
+        | vk::Format::R8G8B8A8_SINT
+        | vk::Format::R16_SINT
+        | vk::Format::R16G16_SINT
+        | vk::Format::R16G16B16A16_SINT
+        | vk::Format::R32_SINT
+        | vk::Format::R32G32_SINT
+        | vk::Format::R32G32B32_SINT
+        | vk::Format::R32G32B32A32_SINT => SampleKind::Sint,
+        _ if texel_size(format).is_some() => SampleKind::Float,
+        _ => return None,
+    };
+    Some(kind)
+}
+
+/// `(sRGB, linear)` pairs of formats sharing a texel layout.
+const SRGB_PAIRS: &[(vk::Format, vk::Format)] = &[
+    (vk::Format::R8_SRGB, vk::Format::R8_UNORM),
+    (vk::Format::R8G8_SRGB, vk::Format::R8G8_UNORM),
+    (vk::Format::R8G8B8A8_SRGB, vk::Format::R8G8B8A8_UNORM),
+    (vk::Format::B8G8R8A8_SRGB, vk::Format::B8G8R8A8_UNORM),
+    (
+        vk::Format::A8B8G8R8_SRGB_PACK32,
+        vk::Format::A8B8G8R8_UNORM_PACK32,
+    ),
+    (
+        vk::Format::BC1_RGBA_SRGB_BLOCK,
+        vk::Format::BC1_RGBA_UNORM_BLOCK,
+    ),
+    (vk::Format::BC2_SRGB_BLOCK, vk::Format::BC2_UNORM_BLOCK),
+    (vk::Format::BC3_SRGB_BLOCK, vk::Format::BC3_UNORM_BLOCK),
+    (vk::Format::BC7_SRGB_BLOCK, vk::Format::BC7_UNORM_BLOCK),
+];
+
+/// The linear counterpart of an sRGB format (e.g. `R8G8B8A8_SRGB` to
+/// `R8G8B8A8_UNORM`); other formats are returned unchanged.
+pub fn remove_srgb_suffix(format: vk::Format) -> vk::Format {
+    SRGB_PAIRS
+        .iter()
+        .find(|(srgb, _)| *srgb == format)
+        .map_or(format, |(_, linear)| *linear)
+}
+
+/// The sRGB counterpart of a linear format (e.g. `B8G8R8A8_UNORM` to
+/// `B8G8R8A8_SRGB`); formats without one are returned unchanged.
+pub fn add_srgb_suffix(format: vk::Format) -> vk::Format {
+    SRGB_PAIRS
+        .iter()
+        .find(|(_, linear)| *linear == format)
+        .map_or(format, |(srgb, _)| *srgb)
+}
+
+/// Whether `format` stores sRGB-encoded color.
+pub fn is_srgb(format: vk::Format) -> bool {
+    remove_srgb_suffix(format) != format
+}
+
+/// Whether an image of `image_format` may be viewed as `view_format`.
+///
+/// Only the sRGB-ness may differ, so the same texels can be read either
+/// encoded or linear. Views with a different format additionally require
+/// the image to be created with `MUTABLE_FORMAT`.
+pub fn is_view_compatible(image_format: vk::Format, view_format: vk::Format) -> bool {
+    remove_srgb_suffix(image_format) == remove_srgb_suffix(view_format)
+}
+
+/// Format feature an optimally tiled image needs for each image usage.
+const USAGE_FORMAT_FEATURES: &[(vk::ImageUsageFlags, vk::FormatFeatureFlags)] = &[
+    (
+        vk::ImageUsageFlags::TRANSFER_SRC,
+        vk::FormatFeatureFlags::TRANSFER_SRC,
+    ),
+    (
+        vk::ImageUsageFlags::TRANSFER_DST,
+        vk::FormatFeatureFlags::TRANSFER_DST,
+    ),
+    (
+        vk::ImageUsageFlags::SAMPLED,
+        vk::FormatFeatureFlags::SAMPLED_IMAGE,
+    ),
+    (
+        vk::ImageUsageFlags::STORAGE,
+        vk::FormatFeatureFlags::STORAGE_IMAGE,
+    ),
+    (
+        vk::ImageUsageFlags::COLOR_ATTACHMENT,
+        vk::FormatFeatureFlags::COLOR_ATTACHMENT,
+    ),
+    (
+        vk::ImageUsageFlags::DEPTH_STENCIL_ATTACHMENT,
+        vk::FormatFeatureFlags::DEPTH_STENCIL_ATTACHMENT,
+    ),
+    (
+        vk::ImageUsageFlags::INPUT_ATTACHMENT,
+        vk::FormatFeatureFlags::from_raw(
+            vk::FormatFeatureFlags::COLOR_ATTACHMENT.as_raw()
+                | vk::FormatFeatureFlags::DEPTH_STENCIL_ATTACHMENT.as_raw(),
+        ),
+    ),
+];
+
+/// The usages in `usage` that an image with the format features `supported`
+/// cannot have; empty when every usage is allowed.
+///
+/// Input attachments need either color or depth/stencil attachment support.
+/// Usages without a format feature requirement (e.g. `TRANSIENT_ATTACHMENT`)
+/// are always allowed.
+pub fn unsupported_usage(
+    usage: vk::ImageUsageFlags,
+    supported: vk::FormatFeatureFlags,
+) -> vk::ImageUsageFlags {
+    USAGE_FORMAT_FEATURES
+        .iter()
+        .filter(|(flag, required)| usage.contains(*flag) && !supported.intersects(*required))
+        .fold(vk::ImageUsageFlags::empty(), |unsupported, (flag, _)| {
+            unsupported | *flag
+        })
+}
+
+/// Whether `format` has a depth component.
+pub fn is_depth_format(format: vk::Format) -> bool {
+    matches!(
+        format,
+        vk::Format::D16_UNORM
+            | vk::Format::X8_D24_UNORM_PACK32
+            | vk::Format::D32_SFLOAT
+            | vk::Format::D16_UNORM_S8_UINT
+            | vk::Format::D24_UNORM_S8_UINT
+            | vk::Format::D32_SFLOAT_S8_UINT
+    )
+}
+
+/// Whether `format` has a stencil component.
+pub fn has_stencil(format: vk::Format) -> bool {
+    matches!(
+        format,
+        vk::Format::S8_UINT
+            | vk::Format::D16_UNORM_S8_UINT
+            | vk::Format::D24_UNORM_S8_UINT
+            | vk::Format::D32_SFLOAT_S8_UINT
+    )
+}
+
+/// Number of texels covered by `extent` (`width * height * depth`).
+pub fn texel_count(extent: vk::Extent3D) -> u64 {
+    extent.width as u64 * extent.height as u64 * extent.depth as u64
+}
+
+/// Maximum number of mip levels for `extent`, down to a 1×1×1 level.
+pub fn max_mip_levels(extent: vk::Extent3D) -> u32 {
+    let largest = extent.width.max(extent.height).max(extent.depth).max(1);
+    32 - largest.leading_zeros()
+}
+
+/// Extent of mip `level` of an image with base extent `extent`.
+///
+/// Each dimension is halved per level and clamped to 1.
+pub fn mip_level_extent(extent: vk::Extent3D, level: u32) -> vk::Extent3D {
+    let shrink = |dim: u32| dim.checked_shr(level).unwrap_or(0).max(1);
+    vk::Extent3D {
+        width: shrink(extent.width),
+        height: shrink(extent.height),
+        depth: shrink(extent.depth),
+    }
+}
+
+/// Tightly packed size in bytes of the first `mip_levels` levels of an image
+/// with base extent `extent`. Pass `1` for a single level, or
+/// [`max_mip_levels`] for the full chain.
+///
+/// Returns `None` when the texel size of `format` is unknown.
+pub fn mip_chain_size(extent: vk::Extent3D, format: vk::Format, mip_levels: u32) -> Option<u64> {
+    let texel = texel_size(format)? as u64;
+    Some(
+        (0..mip_levels)
+            .map(|level| texel_count(mip_level_extent(extent, level)) * texel)
+            .sum(),
+    )
+}
+
+/// Size in bytes of one index of `index_type`, or `None` for index types
+/// this crate does not enable (`UINT8` needs an extension).
+pub fn index_size(index_type: vk::IndexType) -> Option<u32> {
+    match index_type {
+        vk::IndexType::UINT16 => Some(2),
+        vk::IndexType::UINT32 => Some(4),
+        _ => None,
+    }
+}
+
+/// The index value that restarts a strip or fan when primitive restart is
+/// enabled: the maximum value representable by `index_type`.
+pub fn primitive_restart_index(index_type: vk::IndexType) -> Option<u32> {
+    match index_type {
+        vk::IndexType::UINT16 => Some(u16::MAX as u32),
+        vk::IndexType::UINT32 => Some(u32::MAX),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXTENT_256: vk::Extent3D = vk::Extent3D {
+        width: 256,
+        height: 256,
+        depth: 1,
+    };
+
+    #[test]
+    fn test_texel_count_multiplies_dimensions() {
+        assert_eq!(texel_count(EXTENT_256), 65_536);
+        let volume = vk::Extent3D {
+            width: 4,
+            height: 3,
+            depth: 2,
+        };
+        assert_eq!(texel_count(volume), 24);
+    }
+
+    #[test]
+    fn test_mip_chain_size_rgba8_without_mips() {
+        assert_eq!(
+            mip_chain_size(EXTENT_256, vk::Format::R8G8B8A8_UNORM, 1),
+            Some(262_144)
+        );
+    }
+
+    #[test]
+    fn test_mip_chain_size_rgba8_full_chain() {
+        let levels = max_mip_levels(EXTENT_256);
+        assert_eq!(levels, 9);
+        // 4 * (256² + 128² + ... + 1²)
+        assert_eq!(
+            mip_chain_size(EXTENT_256, vk::Format::R8G8B8A8_UNORM, levels),
+            Some(349_524)
+        );
+    }
+
+    #[test]
+    fn test_mip_level_extent_clamps_to_one() {
+        let extent = vk::Extent3D {
+            width: 8,
+            height: 2,
+            depth: 1,
+        };
+        let level = mip_level_extent(extent, 2);
+        assert_eq!((level.width, level.height, level.depth), (2, 1, 1));
+        let past_end = mip_level_extent(extent, 40);
+        assert_eq!((past_end.width, past_end.height, past_end.depth), (1, 1, 1));
+    }
+
+    #[test]
+    fn test_unknown_format_has_no_size() {
+        assert_eq!(texel_size(vk::Format::BC1_RGBA_UNORM_BLOCK), None);
+        assert_eq!(
+            mip_chain_size(EXTENT_256, vk::Format::BC1_RGBA_UNORM_BLOCK, 1),
+            None
+        );
+    }
+
+    #[test]
+    fn test_index_size_and_restart_index() {
+        assert_eq!(index_size(vk::IndexType::UINT16), Some(2));
+        assert_eq!(index_size(vk::IndexType::UINT32), Some(4));
+        assert_eq!(index_size(vk::IndexType::NONE_KHR), None);
+        assert_eq!(primitive_restart_index(vk::IndexType::UINT16), Some(0xFFFF));
+        assert_eq!(
+            primitive_restart_index(vk::IndexType::UINT32),
+            Some(0xFFFF_FFFF)
+        );
+    }
+
+    #[test]
+    fn test_depth_and_stencil_formats() {
+        assert!(is_depth_format(vk::Format::D32_SFLOAT));
+        assert!(is_depth_format(vk::Format::D24_UNORM_S8_UINT));
+        assert!(!is_depth_format(vk::Format::S8_UINT));
+        assert!(!is_depth_format(vk::Format::R8G8B8A8_UNORM));
+        assert!(has_stencil(vk::Format::D24_UNORM_S8_UINT));
+        assert!(!has_stencil(vk::Format::D32_SFLOAT));
+    }
+
+    use SampleKind::{Float, Sint, Uint};
+
+    /// Every format [`texel_size`] knows about, with its texel size in bytes,
+    /// component count, whether it is normalized and how shaders sample it.
+    const KNOWN_FORMATS: &[(vk::Format, u32, u32, bool, SampleKind)] = &[
+        (vk::Format::R8_UNORM, 1, 1, true, Float),
+        (vk::Format::R8_SNORM, 1, 1, true, Float),
+        (vk::Format::R8_UINT, 1, 1, false, Uint),
+        (vk::Format::R8_SINT, 1, 1, false, Sint),
+        (vk::Format::R8_SRGB, 1, 1, true, Float),
+        (vk::Format::S8_UINT, 1, 1, false, Uint),
+        (vk::Format::R8G8_UNORM, 2, 2, true, Float),
+        (vk::Format::R8G8_SNORM, 2, 2, true, Float),
+        (vk::Format::R8G8_UINT, 2, 2, false, Uint),
+        (vk::Format::R8G8_SINT, 2, 2, false, Sint),
+        (vk::Format::R16_UNORM, 2, 1, true, Float),
+        (vk::Format::R16_SNORM, 2, 1, true, Float),
+        (vk::Format::R16_UINT, 2, 1, false, Uint),
+        (vk::Format::R16_SINT, 2, 1, false, Sint),
+        (vk::Format::R16_SFLOAT, 2, 1, false, Float),
+        (vk::Format::D16_UNORM, 2, 1, true, Float),
+        (vk::Format::D16_UNORM_S8_UINT, 3, 2, true, Float),
+        (vk::Format::R8G8B8A8_UNORM, 4, 4, true, Float),
+        (vk::Format::R8G8B8A8_SNORM, 4, 4, true, Float),
+        (vk::Format::R8G8B8A8_UINT, 4, 4, false, Uint),
+        (vk::Format::R8G8B8A8_SINT, 4, 4, false, Sint),
+        (vk::Format::R8G8B8A8_SRGB, 4, 4, true, Float),
+        (vk::Format::B8G8R8A8_UNORM, 4, 4, true, Float),
+        (vk::Format::B8G8R8A8_SRGB, 4, 4, true, Float),
+        (vk::Format::A2B10G10R10_UNORM_PACK32, 4, 4, true, Float),
+        (vk::Format::B10G11R11_UFLOAT_PACK32, 4, 3, false, Float),
+        (vk::Format::R16G16_UNORM, 4, 2, true, Float),
+        (vk::Format::R16G16_SNORM, 4, 2, true, Float),
+        (vk::Format::R16G16_UINT, 4, 2, false, Uint),
+        (vk::Format::R16G16_SINT, 4, 2, false, Sint),
+        (vk::Format::R16G16_SFLOAT, 4, 2, false, Float),
+        (vk::Format::R32_UINT, 4, 1, false, Uint),
+        (vk::Format::R32_SINT, 4, 1, false, Sint),
+        (vk::Format::R32_SFLOAT, 4, 1, false, Float),
+        (vk::Format::D32_SFLOAT, 4, 1, false, Float),
+        (vk::Format::D24_UNORM_S8_UINT, 4, 2, true, Float),
+        (vk::Format::X8_D24_UNORM_PACK32, 4, 1, true, Float),
+        (vk::Format::R16G16B16A16_UNORM, 8, 4, true, Float),
+        (vk::Format::R16G16B16A16_SNORM, 8, 4, true, Float),
+        (vk::Format::R16G16B16A16_UINT, 8, 4, false, Uint),
+        (vk::Format::R16G16B16A16_SINT, 8, 4, false, Sint),
+        (vk::Format::R16G16B16A16_SFLOAT, 8, 4, false, Float),
+        (vk::Format::R32G32_UINT, 8, 2, false, Uint),
+        (vk::Format::R32G32_SINT, 8, 2, false, Sint),
+        (vk::Format::R32G32_SFLOAT, 8, 2, false, Float),
+        (vk::Format::D32_SFLOAT_S8_UINT, 8, 2, false, Float),
+        (vk::Format::R16G16B16_SFLOAT, 6, 3, false, Float),
+        (vk::Format::R32G32B32_UINT, 12, 3, false, Uint),
+        (vk::Format::R32G32B32_SINT, 12, 3, false, Sint),
+        (vk::Format::R32G32B32_SFLOAT, 12, 3, false, Float),
+        (vk::Format::R32G32B32A32_UINT, 16, 4, false, Uint),
+        (vk::Format::R32G32B32A32_SINT, 16, 4, false, Sint),
+        (vk::Format::R32G32B32A32_SFLOAT, 16, 4, false, Float),
+    ];
+
+    #[test]
+    fn test_known_formats_are_fully_described() {
+        for &(format, size, count, normalized, kind) in KNOWN_FORMATS {
+            assert_eq!(texel_size(format), Some(size), "{format:?}");
+            assert_eq!(component_count(format), Some(count), "{format:?}");
+            assert_eq!(is_normalized(format), normalized, "{format:?}");
+            assert_eq!(sample_kind(format), Some(kind), "{format:?}");
+        }
+    }
+
+    #[test]
+    fn test_vertex_format_components() {
+        assert_eq!(component_count(vk::Format::R32G32B32_SFLOAT), Some(3));
+        assert_eq!(component_count(vk::Format::R8G8B8A8_UNORM), Some(4));
+        assert_eq!(component_count(vk::Format::R32_UINT), Some(1));
+        assert_eq!(component_count(vk::Format::BC1_RGBA_UNORM_BLOCK), None);
+
+        assert!(is_normalized(vk::Format::R8G8B8A8_UNORM));
+        assert!(is_normalized(vk::Format::R16G16_SNORM));
+        assert!(!is_normalized(vk::Format::R8G8B8A8_UINT));
+        assert!(!is_normalized(vk::Format::R32G32_SFLOAT));
+
+        assert_eq!(
+            sample_kind(vk::Format::R8G8B8A8_SNORM),
+            Some(SampleKind::Float)
+        );
+        assert_eq!(sample_kind(vk::Format::R16G16_SINT), Some(SampleKind::Sint));
+        assert_eq!(sample_kind(vk::Format::R32_UINT), Some(SampleKind::Uint));
+        assert_eq!(sample_kind(vk::Format::BC1_RGBA_UNORM_BLOCK), None);
+    }
+
+    #[test]
+    fn test_srgb_view_compatibility() {
+        assert_eq!(
+            remove_srgb_suffix(vk::Format::R8G8B8A8_SRGB),
+            vk::Format::R8G8B8A8_UNORM
+        );
+        assert_eq!(
+            remove_srgb_suffix(vk::Format::R32_SFLOAT),
+            vk::Format::R32_SFLOAT
+        );
+        assert_eq!(
+            add_srgb_suffix(vk::Format::B8G8R8A8_UNORM),
+            vk::Format::B8G8R8A8_SRGB
+        );
+        assert_eq!(
+            add_srgb_suffix(vk::Format::R16G16B16A16_SFLOAT),
+            vk::Format::R16G16B16A16_SFLOAT
+        );
+        for (srgb, linear) in SRGB_PAIRS {
+            assert_eq!(add_srgb_suffix(remove_srgb_suffix(*srgb)), *srgb);
+            assert_eq!(remove_srgb_suffix(add_srgb_suffix(*linear)), *linear);
+        }
+        assert!(is_srgb(vk::Format::B8G8R8A8_SRGB));
+        assert!(!is_srgb(vk::Format::B8G8R8A8_UNORM));
+
+        assert!(is_view_compatible(
+            vk::Format::R8G8B8A8_UNORM,
+            vk::Format::R8G8B8A8_SRGB
+        ));
+        assert!(is_view_compatible(
+            vk::Format::R8G8B8A8_SRGB,
+            vk::Format::R8G8B8A8_UNORM
+        ));
+        assert!(!is_view_compatible(
+            vk::Format::R8G8B8A8_UNORM,
+            vk::Format::B8G8R8A8_SRGB
+        ));
+        assert!(!is_view_compatible(
+            vk::Format::R8G8B8A8_UNORM,
+            vk::Format::R32_UINT
+        ));
+    }
+
+    #[test]
+    fn test_unsupported_usage_names_missing_features() {
+        // Block-compressed formats are typically only sampleable.
+        let compressed = vk::FormatFeatureFlags::SAMPLED_IMAGE
+            | vk::FormatFeatureFlags::SAMPLED_IMAGE_FILTER_LINEAR
+            | vk::FormatFeatureFlags::TRANSFER_SRC
+            | vk::FormatFeatureFlags::TRANSFER_DST;
+        assert_eq!(
+            unsupported_usage(
+                vk::ImageUsageFlags::SAMPLED | vk::ImageUsageFlags::STORAGE,
+                compressed
+            ),
+            vk::ImageUsageFlags::STORAGE
+        );
+        assert_eq!(
+            unsupported_usage(
+                vk::ImageUsageFlags::SAMPLED | vk::ImageUsageFlags::TRANSFER_DST,
+                compressed
+            ),
+            vk::ImageUsageFlags::empty()
+        );
+
+        let color =
+            vk::FormatFeatureFlags::COLOR_ATTACHMENT | vk::FormatFeatureFlags::SAMPLED_IMAGE;
+        assert!(unsupported_usage(
+            vk::ImageUsageFlags::COLOR_ATTACHMENT
+                | vk::ImageUsageFlags::INPUT_ATTACHMENT
+                | vk::ImageUsageFlags::TRANSIENT_ATTACHMENT,
+            color
+        )
+        .is_empty());
+        assert_eq!(
+            unsupported_usage(vk::ImageUsageFlags::DEPTH_STENCIL_ATTACHMENT, color),
+            vk::ImageUsageFlags::DEPTH_STENCIL_ATTACHMENT
+        );
+    }
+}
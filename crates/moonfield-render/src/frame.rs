@@ -0,0 +1,161 @@
+//! Per-frame resources for keeping several frames in flight.
+
+use crate::command::{CommandBuffer, CommandPool};
+use crate::device::Device;
+use crate::error::{Error, Result};
+use crate::sync::Fence;
+
+/// The command buffer and fence owned by one frame in flight.
+pub struct Frame {
+    command_buffer: CommandBuffer,
+    fence: Fence,
+}
+
+impl Frame {
+    /// The command buffer recording this frame.
+    pub fn command_buffer(&mut self) -> &mut CommandBuffer {
+        &mut self.command_buffer
+    }
+
+    /// The fence to signal when this frame's submission completes.
+    pub fn fence(&self) -> &Fence {
+        &self.fence
+    }
+}
+
+/// A ring of [`Frame`]s letting the CPU record ahead of the GPU.
+///
+/// Up to [`len`](Self::len) frames may be submitted before the CPU blocks:
+/// reusing a slot first waits on the fence of the submission that last used
+/// it, which is always the oldest one still in flight. Every fence must be
+/// handed to the submission of its frame, or waiting on it never returns.
+///
+/// The ring must be dropped before the [`CommandPool`] it allocated from.
+pub struct FrameRing {
+    frames: Vec<Frame>,
+    current: usize,
+}
+
+impl FrameRing {
+    /// Allocate `frames_in_flight` frames from `command_pool`.
+    pub fn new(
+        device: &Device,
+        command_pool: &CommandPool,
+        frames_in_flight: usize,
+    ) -> Result<Self> {
+        if frames_in_flight == 0 {
+            return Err(Error::Validation(
+                "a frame ring needs at least one frame in flight".to_string(),
+            ));
+        }
+        let frames = (0..frames_in_flight)
+            .map(|_| {
+                Ok(Frame {
+                    command_buffer: command_pool.allocate_command_buffer()?,
+                    // Signaled so the first wait on each slot returns at once.
+                    fence: Fence::new(device, true)?,
+                })
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { frames, current: 0 })
+    }
+
+    /// The number of frames that may be in flight.
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Always `false`; a ring holds at least one frame.
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// The slot index of the current frame.
+    pub fn current_index(&self) -> usize {
+        self.current
+    }
+
+    /// Block until the GPU has finished the last submission that used the
+    /// current slot.
+    pub fn wait_current(&self) -> Result<()> {
+        self.frames[self.current].fence.wait(u64::MAX)
+    }
+
+    /// Wait for the current slot to be free, reset its fence and return it
+    /// for recording.
+    ///
+    /// Callers that may still bail out before submitting (e.g. when
+    /// swapchain acquisition fails) should use
+    /// [`wait_current`](Self::wait_current) and reset the fence themselves
+    /// once submission is certain.
+    pub fn begin_frame(&mut self) -> Result<&mut Frame> {
+        self.wait_current()?;
+        let frame = &mut self.frames[self.current];
+        frame.fence.reset()?;
+        Ok(frame)
+    }
+
+    /// The current frame.
+    pub fn current(&mut self) -> &mut Frame {
+        &mut self.frames[self.current]
+    }
+
+    /// Move on to the next slot once the current frame is submitted.
+    pub fn advance(&mut self) {
+        self.current = (self.current + 1) % self.frames.len();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ash::vk;
+
+    /// Needs a Vulkan device; skipped when no driver is available.
+    #[test]
+    fn test_frame_ring_reuses_slots_after_their_fences() {
+        let Some((_instance, device, _allocator)) = crate::test_util::headless_gpu() else {
+            return;
+        };
+
+        let command_pool = CommandPool::new(&device, device.queue_family_indices().graphics)
+            .expect("command pool");
+        let mut ring = FrameRing::new(&device, &command_pool, 3).expect("frame ring");
+        assert_eq!(ring.len(), 3);
+
+        let mut slots = Vec::new();
+        for _ in 0..7 {
+            slots.push(ring.current_index());
+            let frame = ring.begin_frame().expect("begin frame");
+            assert!(!frame.fence().is_signaled().expect("fence status"));
+
+            let command_buffer = frame.command_buffer();
+            command_buffer
+                .begin(vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT)
+                .expect("begin");
+            command_buffer.end().expect("end");
+            let command_buffers = [command_buffer.raw()];
+            let submit_info = vk::SubmitInfo::default().command_buffers(&command_buffers);
+            unsafe {
+                device
+                    .raw()
+                    .queue_submit(
+                        device.graphics_queue(),
+                        std::slice::from_ref(&submit_info),
+                        frame.fence().raw(),
+                    )
+                    .expect("submit");
+            }
+            ring.advance();
+
+            // The slot about to be reused belongs to the oldest submission;
+            // once waited on, its fence must be signaled.
+            ring.wait_current().expect("wait for oldest frame");
+            assert!(ring.current().fence().is_signaled().expect("fence status"));
+        }
+        assert_eq!(slots, [0, 1, 2, 0, 1, 2, 0]);
+
+        device.wait_idle().expect("wait idle");
+        assert!(FrameRing::new(&device, &command_pool, 0).is_err());
+    }
+}
@@ -7,7 +7,7 @@
 use crate::error::{Error, Result};
 use crate::{
     Buffer, CommandBuffer, CommandPool, Compiler, Device, GraphicsPipeline, Instance, RenderPass,
-    ShaderModule,
+    ShaderModule, VertexLayout,
 };
 use ash::vk;
 
@@ -72,30 +72,22 @@ impl HeadlessContext {
 
         let render_pass = RenderPass::new(&device, vk::Format::B8G8R8A8_UNORM)?;
 
-        let binding = vk::VertexInputBindingDescription::default()
-            .binding(0)
-            .stride(std::mem::size_of::<Vertex>() as u32)
-            .input_rate(vk::VertexInputRate::VERTEX);
-
-        let position_attribute = vk::VertexInputAttributeDescription::default()
-            .binding(0)
-            .location(0)
-            .format(vk::Format::R32G32B32_SFLOAT)
-            .offset(0);
-
-        let color_attribute = vk::VertexInputAttributeDescription::default()
-            .binding(0)
-            .location(1)
-            .format(vk::Format::R32G32B32_SFLOAT)
-            .offset(std::mem::size_of::<[f32; 3]>() as u32);
+        let vertex_layout = VertexLayout::new(
+            0,
+            &[vk::Format::R32G32B32_SFLOAT, vk::Format::R32G32B32_SFLOAT],
+        )?;
+        debug_assert_eq!(
+            vertex_layout.stride() as usize,
+            std::mem::size_of::<Vertex>()
+        );
 
         let pipeline = GraphicsPipeline::new(
             &device,
             &render_pass,
             &vertex_shader,
             &fragment_shader,
-            &[binding],
-            &[position_attribute, color_attribute],
+            &[vertex_layout.binding_description()],
+            vertex_layout.attributes(),
             extent,
         )?;
 
@@ -114,13 +106,12 @@ impl HeadlessContext {
             },
         ];
 
-        let vertex_buffer = Buffer::new(
+        let vertex_buffer = Buffer::new_with_data(
             &instance,
             &device,
-            std::mem::size_of_val(&vertices) as vk::DeviceSize,
+            &vertices,
             vk::BufferUsageFlags::VERTEX_BUFFER,
         )?;
-        vertex_buffer.upload(&vertices)?;
 
         let queue_family_index = device.queue_family_indices().graphics;
         let command_pool = CommandPool::new(&device, queue_family_index)?;
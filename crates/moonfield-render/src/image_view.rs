@@ -0,0 +1,153 @@
+//! Vulkan image view abstraction.
+
+use crate::device::Device;
+use crate::error::{Error, Result};
+use crate::format;
+use ash::vk;
+
+/// A Vulkan image view.
+///
+/// The view does not keep its image alive; the image must outlive it.
+pub struct ImageView {
+    view: vk::ImageView,
+    format: vk::Format,
+    device: ash::Device,
+}
+
+impl ImageView {
+    /// Create a view over `subresource_range` of `image`.
+    ///
+    /// `view_format` defaults to `image_format`. A different format may only
+    /// change the sRGB-ness (see [`format::is_view_compatible`]), e.g. to
+    /// sample an `R8G8B8A8_UNORM` image as `R8G8B8A8_SRGB`; the image must
+    /// then have been created with `MUTABLE_FORMAT`.
+    pub fn new(
+        device: &Device,
+        image: vk::Image,
+        image_format: vk::Format,
+        view_format: Option<vk::Format>,
+        view_type: vk::ImageViewType,
+        subresource_range: vk::ImageSubresourceRange,
+    ) -> Result<Self> {
+        let view_format = view_format.unwrap_or(image_format);
+        if !format::is_view_compatible(image_format, view_format) {
+            return Err(Error::Validation(format!(
+                "cannot view a {:?} image as {:?}: only sRGB-ness may differ",
+                image_format, view_format
+            )));
+        }
+
+        let create_info = vk::ImageViewCreateInfo::default()
+            .image(image)
+            .view_type(view_type)
+            .format(view_format)
+            .subresource_range(subresource_range);
+        let view = unsafe {
+            device
+                .raw()
+                .create_image_view(&create_info, None)
+                .map_err(|e| Error::Backend(format!("failed to create image view: {:?}", e)))?
+        };
+
+        Ok(Self {
+            view,
+            format: view_format,
+            device: device.raw().clone(),
+        })
+    }
+
+    /// Access the raw `vk::ImageView` handle.
+    pub fn raw(&self) -> vk::ImageView {
+        self.view
+    }
+
+    /// The format texels are read and written as through this view.
+    pub fn format(&self) -> vk::Format {
+        self.format
+    }
+}
+
+impl Drop for ImageView {
+    fn drop(&mut self) {
+        unsafe {
+            self.device.destroy_image_view(self.view, None);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Needs a Vulkan device; skipped when no driver is available.
+    #[test]
+    fn test_incompatible_view_format_is_rejected() {
+        let Some((_instance, device, _allocator)) = crate::test_util::headless_gpu() else {
+            return;
+        };
+
+        // Rejected before the (null) image is ever touched.
+        let result = ImageView::new(
+            &device,
+            vk::Image::null(),
+            vk::Format::R8G8B8A8_UNORM,
+            Some(vk::Format::B8G8R8A8_SRGB),
+            vk::ImageViewType::TYPE_2D,
+            vk::ImageSubresourceRange::default()
+                .aspect_mask(vk::ImageAspectFlags::COLOR)
+                .level_count(1)
+                .layer_count(1),
+        );
+        assert!(matches!(result, Err(Error::Validation(_))));
+    }
+
+    /// Needs a Vulkan device; skipped when no driver is available.
+    #[test]
+    fn test_srgb_view_of_mutable_unorm_image() {
+        let Some((_instance, device, allocator)) = crate::test_util::headless_gpu() else {
+            return;
+        };
+
+        let image_info = vk::ImageCreateInfo::default()
+            .flags(vk::ImageCreateFlags::MUTABLE_FORMAT)
+            .image_type(vk::ImageType::TYPE_2D)
+            .format(vk::Format::R8G8B8A8_UNORM)
+            .extent(vk::Extent3D {
+                width: 4,
+                height: 4,
+                depth: 1,
+            })
+            .mip_levels(1)
+            .array_layers(1)
+            .samples(vk::SampleCountFlags::TYPE_1)
+            .tiling(vk::ImageTiling::OPTIMAL)
+            .usage(vk::ImageUsageFlags::SAMPLED)
+            .sharing_mode(vk::SharingMode::EXCLUSIVE)
+            .initial_layout(vk::ImageLayout::UNDEFINED);
+        let (image, allocation) =
+            crate::offscreen::allocate_image(&device, &allocator, &image_info, "mutable image")
+                .expect("mutable-format image");
+
+        let view = ImageView::new(
+            &device,
+            image,
+            vk::Format::R8G8B8A8_UNORM,
+            Some(vk::Format::R8G8B8A8_SRGB),
+            vk::ImageViewType::TYPE_2D,
+            vk::ImageSubresourceRange::default()
+                .aspect_mask(vk::ImageAspectFlags::COLOR)
+                .level_count(1)
+                .layer_count(1),
+        )
+        .expect("sRGB view");
+        assert_eq!(view.format(), vk::Format::R8G8B8A8_SRGB);
+
+        drop(view);
+        unsafe { device.raw().destroy_image(image, None) };
+        allocator
+            .lock()
+            .unwrap()
+            .free(allocation)
+            .expect("free image memory");
+    }
+}
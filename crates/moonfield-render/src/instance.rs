@@ -4,19 +4,61 @@ use crate::error::{Error, Result};
 use ash::vk;
 use std::ffi::{c_char, CStr};
 
+const VALIDATION_LAYER: &CStr = c"VK_LAYER_KHRONOS_validation";
+
+/// Debugging options for instance creation.
+///
+/// Both options default to on in debug builds and off in release builds.
+/// Requested layers or extensions that are not installed are skipped with a
+/// warning rather than failing instance creation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InstanceOptions {
+    /// Enable `VK_LAYER_KHRONOS_validation`.
+    pub validation: bool,
+    /// Enable `VK_EXT_debug_utils`: validation messages are routed to
+    /// `moonfield_log` and objects can be given debug names.
+    pub debug_utils: bool,
+}
+
+impl Default for InstanceOptions {
+    fn default() -> Self {
+        Self {
+            validation: cfg!(debug_assertions),
+            debug_utils: cfg!(debug_assertions),
+        }
+    }
+}
+
 /// Vulkan instance and entry point.
 pub struct Instance {
     entry: ash::Entry,
     instance: ash::Instance,
     surface_instance: ash::khr::surface::Instance,
+    debug_utils: Option<DebugUtils>,
+    validation_enabled: bool,
+}
+
+struct DebugUtils {
+    loader: ash::ext::debug_utils::Instance,
+    messenger: vk::DebugUtilsMessengerEXT,
 }
 
 impl Instance {
-    /// Create a Vulkan instance with the requested extensions.
+    /// Create a Vulkan instance with the requested extensions and default
+    /// [`InstanceOptions`].
     ///
     /// `required_extensions` should contain platform surface extensions such as
     /// `VK_KHR_surface` and the platform-specific `VK_KHR_win32_surface`, etc.
     pub fn new(required_extensions: &[&CStr]) -> Result<Self> {
+        Self::new_with_options(required_extensions, InstanceOptions::default())
+    }
+
+    /// Create a Vulkan instance with the requested extensions and debugging
+    /// options.
+    pub fn new_with_options(
+        required_extensions: &[&CStr],
+        options: InstanceOptions,
+    ) -> Result<Self> {
         let entry = unsafe { ash::Entry::load() }?;
 
         let app_name = std::ffi::CString::new("moonfield").unwrap();
@@ -29,22 +71,71 @@ impl Instance {
             .engine_version(vk::make_api_version(0, 0, 1, 0))
             .api_version(vk::API_VERSION_1_3);
 
-        let extensions: Vec<*const c_char> =
+        let validation_enabled = requested_layer(&entry, options.validation, VALIDATION_LAYER);
+        let debug_utils_enabled =
+            options.debug_utils && extension_available(&entry, ash::ext::debug_utils::NAME);
+        if options.debug_utils && !debug_utils_enabled {
+            moonfield_log::warn!(
+                "Vulkan debug utils requested but VK_EXT_debug_utils is not available"
+            );
+        }
+
+        let mut extensions: Vec<*const c_char> =
             required_extensions.iter().map(|ext| ext.as_ptr()).collect();
+        if debug_utils_enabled {
+            extensions.push(ash::ext::debug_utils::NAME.as_ptr());
+        }
+        let layers: Vec<*const c_char> = if validation_enabled {
+            vec![VALIDATION_LAYER.as_ptr()]
+        } else {
+            Vec::new()
+        };
 
         let create_info = vk::InstanceCreateInfo::default()
             .application_info(&app_info)
-            .enabled_extension_names(&extensions);
+            .enabled_extension_names(&extensions)
+            .enabled_layer_names(&layers);
 
         let instance = unsafe { entry.create_instance(&create_info, None) }
             .map_err(|e| Error::Backend(format!("failed to create Vulkan instance: {:?}", e)))?;
 
         let surface_instance = ash::khr::surface::Instance::new(&entry, &instance);
 
+        let debug_utils = if debug_utils_enabled {
+            let loader = ash::ext::debug_utils::Instance::new(&entry, &instance);
+            let messenger_info = vk::DebugUtilsMessengerCreateInfoEXT::default()
+                .message_severity(
+                    vk::DebugUtilsMessageSeverityFlagsEXT::ERROR
+                        | vk::DebugUtilsMessageSeverityFlagsEXT::WARNING
+                        | vk::DebugUtilsMessageSeverityFlagsEXT::INFO
+                        | vk::DebugUtilsMessageSeverityFlagsEXT::VERBOSE,
+                )
+                .message_type(
+                    vk::DebugUtilsMessageTypeFlagsEXT::GENERAL
+                        | vk::DebugUtilsMessageTypeFlagsEXT::VALIDATION
+                        | vk::DebugUtilsMessageTypeFlagsEXT::PERFORMANCE,
+                )
+                .pfn_user_callback(Some(debug_messenger_callback));
+            match unsafe { loader.create_debug_utils_messenger(&messenger_info, None) } {
+                Ok(messenger) => Some(DebugUtils { loader, messenger }),
+                Err(e) => {
+                    unsafe { instance.destroy_instance(None) };
+                    return Err(Error::Backend(format!(
+                        "failed to create debug messenger: {:?}",
+                        e
+                    )));
+                }
+            }
+        } else {
+            None
+        };
+
         Ok(Self {
             entry,
             instance,
             surface_instance,
+            debug_utils,
+            validation_enabled,
         })
     }
 
@@ -58,6 +149,16 @@ impl Instance {
         &self.entry
     }
 
+    /// Whether the validation layer is active.
+    pub fn validation_enabled(&self) -> bool {
+        self.validation_enabled
+    }
+
+    /// Whether `VK_EXT_debug_utils` is enabled on this instance.
+    pub fn debug_utils_enabled(&self) -> bool {
+        self.debug_utils.is_some()
+    }
+
     /// Access the raw `ash::Instance`.
     pub fn raw(&self) -> &ash::Instance {
         &self.instance
@@ -85,6 +186,14 @@ impl Instance {
         unsafe { self.instance.get_physical_device_properties(device) }
     }
 
+    /// Get the core features supported by a physical device.
+    pub fn physical_device_features(
+        &self,
+        device: vk::PhysicalDevice,
+    ) -> vk::PhysicalDeviceFeatures {
+        unsafe { self.instance.get_physical_device_features(device) }
+    }
+
     /// Get queue family properties for a physical device.
     pub fn queue_family_properties(
         &self,
@@ -114,7 +223,133 @@ impl Instance {
 impl Drop for Instance {
     fn drop(&mut self) {
         unsafe {
+            if let Some(debug_utils) = self.debug_utils.take() {
+                debug_utils
+                    .loader
+                    .destroy_debug_utils_messenger(debug_utils.messenger, None);
+            }
             self.instance.destroy_instance(None);
         }
     }
 }
+
+fn layer_available(entry: &ash::Entry, name: &CStr) -> bool {
+    unsafe { entry.enumerate_instance_layer_properties() }
+        .map(|layers| {
+            layers
+                .iter()
+                .any(|layer| layer.layer_name_as_c_str() == Ok(name))
+        })
+        .unwrap_or(false)
+}
+
+/// Whether a `requested` layer can be enabled, warning when it is requested but
+/// not installed.
+fn requested_layer(entry: &ash::Entry, requested: bool, name: &CStr) -> bool {
+    let enabled = requested && layer_available(entry, name);
+    if requested && !enabled {
+        moonfield_log::warn!(
+            "Vulkan layer {} requested but not installed",
+            name.to_string_lossy()
+        );
+    }
+    enabled
+}
+
+fn extension_available(entry: &ash::Entry, name: &CStr) -> bool {
+    unsafe { entry.enumerate_instance_extension_properties(None) }
+        .map(|extensions| {
+            extensions
+                .iter()
+                .any(|extension| extension.extension_name_as_c_str() == Ok(name))
+        })
+        .unwrap_or(false)
+}
+
+/// Forward `VK_EXT_debug_utils` messages to `moonfield_log` at the matching
+/// level.
+unsafe extern "system" fn debug_messenger_callback(
+    severity: vk::DebugUtilsMessageSeverityFlagsEXT,
+    message_type: vk::DebugUtilsMessageTypeFlagsEXT,
+    callback_data: *const vk::DebugUtilsMessengerCallbackDataEXT<'_>,
+    _user_data: *mut std::ffi::c_void,
+) -> vk::Bool32 {
+    // SAFETY: the loader passes valid callback data for the duration of the
+    // call; the message pointer may be null.
+    let message = unsafe {
+        callback_data
+            .as_ref()
+            .and_then(|data| data.message_as_c_str())
+            .map(|message| message.to_string_lossy())
+            .unwrap_or_default()
+    };
+
+    if severity.contains(vk::DebugUtilsMessageSeverityFlagsEXT::ERROR) {
+        moonfield_log::error!(?message_type, "{message}");
+    } else if severity.contains(vk::DebugUtilsMessageSeverityFlagsEXT::WARNING) {
+        moonfield_log::warn!(?message_type, "{message}");
+    } else if severity.contains(vk::DebugUtilsMessageSeverityFlagsEXT::INFO) {
+        moonfield_log::debug!(?message_type, "{message}");
+    } else {
+        moonfield_log::trace!(?message_type, "{message}");
+    }
+    vk::FALSE
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_options_follow_build_profile() {
+        let options = InstanceOptions::default();
+        assert_eq!(options.validation, cfg!(debug_assertions));
+        assert_eq!(options.debug_utils, cfg!(debug_assertions));
+    }
+
+    /// Needs a Vulkan loader; the validation layer itself is optional and
+    /// silently skipped when not installed.
+    #[test]
+    fn test_instance_with_validation_requested() {
+        let options = InstanceOptions {
+            validation: true,
+            debug_utils: true,
+        };
+        let instance = match Instance::new_with_options(&[], options) {
+            Ok(instance) => instance,
+            Err(err) => {
+                eprintln!("skipping: no Vulkan instance available ({err})");
+                return;
+            }
+        };
+        assert_ne!(instance.raw().handle(), vk::Instance::null());
+        assert_eq!(
+            instance.validation_enabled(),
+            layer_available(instance.entry(), VALIDATION_LAYER)
+        );
+        assert_eq!(
+            instance.debug_utils_enabled(),
+            extension_available(instance.entry(), ash::ext::debug_utils::NAME)
+        );
+        instance
+            .enumerate_physical_devices()
+            .expect("instance is usable");
+    }
+
+    /// Needs a Vulkan loader.
+    #[test]
+    fn test_missing_layer_is_skipped() {
+        let Ok(entry) = (unsafe { ash::Entry::load() }) else {
+            eprintln!("skipping: no Vulkan loader available");
+            return;
+        };
+        let missing = c"VK_LAYER_MOONFIELD_does_not_exist";
+        assert!(!layer_available(&entry, missing));
+        assert!(!requested_layer(&entry, true, missing));
+        assert!(!requested_layer(&entry, false, VALIDATION_LAYER));
+        assert_eq!(
+            requested_layer(&entry, true, VALIDATION_LAYER),
+            layer_available(&entry, VALIDATION_LAYER)
+        );
+    }
+}
@@ -4,38 +4,78 @@
 //! surface over instance, physical device, logical device, and swapchain
 //! creation.
 
+pub mod adapter;
 pub mod buffer;
+pub mod color;
 pub mod command;
+pub mod descriptor;
 pub mod device;
 pub mod error;
+pub mod features;
+pub mod format;
+pub mod frame;
 pub mod framebuffer;
 pub mod headless;
+pub mod image_view;
 pub mod instance;
 pub mod offscreen;
 pub mod pipeline;
+pub mod pipeline_cache;
 pub mod plugin;
+pub mod query;
+#[cfg(feature = "reflect")]
+pub mod reflect;
 pub mod render_pass;
+pub mod sampler;
 pub mod shader;
+pub mod shader_cache;
 pub mod shader_module;
+pub mod shadow_map;
+pub mod stage;
 pub mod swapchain;
 pub mod sync;
+#[cfg(test)]
+mod test_util;
+pub mod texture;
+pub mod vertex;
+pub mod viewport;
 pub mod window_target;
 
+pub use adapter::{AdapterInfo, DeviceType};
 pub use buffer::Buffer;
+pub use color::{Color, Gradient};
 pub use command::{CommandBuffer, CommandPool};
+pub use descriptor::{BufferBinding, DescriptorPool, DescriptorSetLayout, DynamicOffset};
 pub use device::{Device, QueueFamilyIndices};
 pub use error::{Error, Result};
+pub use features::Features;
+pub use frame::{Frame, FrameRing};
 pub use framebuffer::Framebuffer;
 pub use headless::HeadlessContext;
-pub use instance::Instance;
+pub use image_view::ImageView;
+pub use instance::{Instance, InstanceOptions};
 pub use offscreen::OffscreenTarget;
-pub use pipeline::GraphicsPipeline;
+pub use pipeline::{
+    BlendState, DepthState, GraphicsPipeline, GraphicsPipelineDesc, PipelineState,
+    StencilFaceState, StencilState,
+};
+pub use pipeline_cache::PipelineCache;
 pub use plugin::RenderPlugin;
-pub use render_pass::RenderPass;
+pub use query::QueryPool;
+#[cfg(feature = "reflect")]
+pub use reflect::ShaderReflection;
+pub use render_pass::{Attachment, RenderPass};
+pub use sampler::{Sampler, SamplerDesc};
 pub use shader::Compiler;
+pub use shader_cache::ShaderCache;
 pub use shader_module::ShaderModule;
-pub use swapchain::{Surface, Swapchain};
+pub use shadow_map::ShadowMap;
+pub use stage::ShaderStage;
+pub use swapchain::{PresentMode, Surface, Swapchain, SwapchainConfig};
 pub use sync::{Fence, Semaphore};
+pub use texture::{Texture, TextureDesc};
+pub use vertex::VertexLayout;
+pub use viewport::{Scissor, Viewport};
 pub use window_target::WindowRenderer;
 
 use std::ffi::CStr;
@@ -5,11 +5,20 @@
 //! a UI toolkit (e.g. egui) samples it afterwards. The render pass finishes in
 //! `SHADER_READ_ONLY_OPTIMAL`, so the image is ready for sampling at the end
 //! of every render pass without explicit transitions.
+//!
+//! The target needs no window, so it also serves headless rendering: record a
+//! pass into it and read the result back with [`OffscreenTarget::read_pixels`].
+//!
+//! A multisampled target renders into a transient multisampled image that the
+//! render pass resolves into the sampleable image when it ends.
 
+use crate::buffer::Buffer;
 use crate::device::Device;
 use crate::error::{Error, Result};
+use crate::format;
 use crate::framebuffer::Framebuffer;
-use crate::render_pass::RenderPass;
+use crate::instance::Instance;
+use crate::render_pass::{Attachment, RenderPass};
 use crate::{CommandBuffer, CommandPool};
 use ash::vk;
 use gpu_allocator::vulkan::{Allocation, AllocationCreateDesc, AllocationScheme, Allocator};
@@ -28,12 +37,21 @@ pub struct OffscreenTarget {
     sampler: vk::Sampler,
     image: vk::Image,
     allocation: Option<Allocation>,
+    msaa: Option<MsaaImage>,
     allocator: Arc<Mutex<Allocator>>,
     device: ash::Device,
     format: vk::Format,
+    samples: vk::SampleCountFlags,
     extent: vk::Extent2D,
 }
 
+/// The multisampled color image a multisampled target renders into.
+struct MsaaImage {
+    view: vk::ImageView,
+    image: vk::Image,
+    allocation: Allocation,
+}
+
 impl OffscreenTarget {
     /// Create an offscreen target of `width`×`height` with the given color
     /// format. The image is transitioned to `SHADER_READ_ONLY_OPTIMAL` so it
@@ -44,6 +62,30 @@ impl OffscreenTarget {
         width: u32,
         height: u32,
         format: vk::Format,
+    ) -> Result<Self> {
+        Self::new_multisampled(
+            device,
+            allocator,
+            width,
+            height,
+            format,
+            vk::SampleCountFlags::TYPE_1,
+        )
+    }
+
+    /// Create an offscreen target rendered with `samples` samples per pixel.
+    ///
+    /// With more than one sample the render pass draws into a multisampled
+    /// image and resolves it into the sampleable image at the end of the
+    /// pass; pipelines must use the same sample count (see
+    /// [`RenderPass::samples`]). With `TYPE_1` this is [`Self::new`].
+    pub fn new_multisampled(
+        device: &Device,
+        allocator: Arc<Mutex<Allocator>>,
+        width: u32,
+        height: u32,
+        format: vk::Format,
+        samples: vk::SampleCountFlags,
     ) -> Result<Self> {
         if width == 0 || height == 0 {
             return Err(Error::Validation(format!(
@@ -52,16 +94,41 @@ impl OffscreenTarget {
             )));
         }
 
+        let render_pass = if samples == vk::SampleCountFlags::TYPE_1 {
+            RenderPass::new_with_final_layout(
+                device,
+                format,
+                vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+            )?
+        } else {
+            RenderPass::new_with_resolve(
+                device,
+                &[Attachment::multisampled_color(format, samples)],
+                &[Attachment::resolve(
+                    format,
+                    vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+                )],
+                None,
+            )?
+        };
+
         let extent = vk::Extent2D { width, height };
-        let (image, allocation) = create_color_image(device, &allocator, extent, format)?;
-        let image_view = create_image_view(device, image, format)?;
-        let sampler = create_sampler(device)?;
-        let render_pass = RenderPass::new_with_final_layout(
+        let (image, allocation) = create_color_image(
             device,
+            &allocator,
+            extent,
             format,
-            vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+            vk::SampleCountFlags::TYPE_1,
+        )?;
+        let image_view = create_image_view(device, image, format, vk::ImageAspectFlags::COLOR)?;
+        let msaa = create_msaa_image(device, &allocator, extent, format, samples)?;
+        let sampler = create_sampler(device)?;
+        let framebuffer = Framebuffer::new(
+            device,
+            &render_pass,
+            &framebuffer_views(msaa.as_ref(), image_view),
+            extent,
         )?;
-        let framebuffer = Framebuffer::new(device, &render_pass, &[image_view], extent)?;
 
         transition_to_shader_read(device, image)?;
 
@@ -72,9 +139,11 @@ impl OffscreenTarget {
             sampler,
             image,
             allocation: Some(allocation),
+            msaa,
             allocator,
             device: device.raw().clone(),
             format,
+            samples,
             extent,
         })
     }
@@ -101,17 +170,48 @@ impl OffscreenTarget {
         self.destroy_image_resources();
 
         let extent = vk::Extent2D { width, height };
-        let (image, allocation) = create_color_image(device, &self.allocator, extent, self.format)?;
-        self.image_view = create_image_view(device, image, self.format)?;
+        let (image, allocation) = create_color_image(
+            device,
+            &self.allocator,
+            extent,
+            self.format,
+            vk::SampleCountFlags::TYPE_1,
+        )?;
+        self.image_view =
+            create_image_view(device, image, self.format, vk::ImageAspectFlags::COLOR)?;
         self.image = image;
         self.allocation = Some(allocation);
+        self.msaa = create_msaa_image(device, &self.allocator, extent, self.format, self.samples)?;
         self.extent = extent;
-        self.framebuffer = Framebuffer::new(device, &self.render_pass, &[self.image_view], extent)?;
+        self.framebuffer = Framebuffer::new(
+            device,
+            &self.render_pass,
+            &framebuffer_views(self.msaa.as_ref(), self.image_view),
+            extent,
+        )?;
 
         transition_to_shader_read(device, image)?;
         Ok(())
     }
 
+    /// Copy the color image back to the host as tightly packed texels.
+    ///
+    /// For a multisampled target this is the resolved image.
+    ///
+    /// The rendered pass must have completed, e.g. by waiting on its fence;
+    /// this call then blocks until the copy itself has finished. The image is
+    /// left in `SHADER_READ_ONLY_OPTIMAL`.
+    pub fn read_pixels(&self, instance: &Instance, device: &Device) -> Result<Vec<u8>> {
+        read_image(
+            instance,
+            device,
+            self.image,
+            vk::ImageAspectFlags::COLOR,
+            self.format,
+            self.extent,
+        )
+    }
+
     /// Access the color image view (for sampling in a UI renderer).
     pub fn image_view(&self) -> vk::ImageView {
         self.image_view
@@ -132,6 +232,11 @@ impl OffscreenTarget {
         &self.framebuffer
     }
 
+    /// Samples per pixel the target is rendered with.
+    pub fn samples(&self) -> vk::SampleCountFlags {
+        self.samples
+    }
+
     /// The `(width, height)` of the target.
     pub fn extent(&self) -> (u32, u32) {
         (self.extent.width, self.extent.height)
@@ -152,6 +257,17 @@ impl OffscreenTarget {
                 log_free_error(&e);
             }
         }
+        if let Some(msaa) = self.msaa.take() {
+            // SAFETY: as above, the GPU no longer uses the multisampled image.
+            unsafe {
+                self.device.destroy_image_view(msaa.view, None);
+                self.device.destroy_image(msaa.image, None);
+            }
+            let mut allocator = self.allocator.lock().unwrap_or_else(|e| e.into_inner());
+            if let Err(e) = allocator.free(msaa.allocation) {
+                log_free_error(&e);
+            }
+        }
     }
 }
 
@@ -169,12 +285,77 @@ impl Drop for OffscreenTarget {
     }
 }
 
+/// Image views for the target's framebuffer, in render pass attachment
+/// order: the multisampled image (if any) and then the resolved image.
+fn framebuffer_views(msaa: Option<&MsaaImage>, image_view: vk::ImageView) -> Vec<vk::ImageView> {
+    msaa.map(|msaa| msaa.view)
+        .into_iter()
+        .chain([image_view])
+        .collect()
+}
+
+/// Create the multisampled image for a target with `samples` samples, or
+/// `None` for a single-sampled target.
+fn create_msaa_image(
+    device: &Device,
+    allocator: &Arc<Mutex<Allocator>>,
+    extent: vk::Extent2D,
+    format: vk::Format,
+    samples: vk::SampleCountFlags,
+) -> Result<Option<MsaaImage>> {
+    if samples == vk::SampleCountFlags::TYPE_1 {
+        return Ok(None);
+    }
+    let (image, allocation) = create_color_image(device, allocator, extent, format, samples)?;
+    let view = create_image_view(device, image, format, vk::ImageAspectFlags::COLOR)?;
+    Ok(Some(MsaaImage {
+        view,
+        image,
+        allocation,
+    }))
+}
+
+/// Create a color image with `samples` samples. Single-sampled images can
+/// be sampled and copied from; multisampled ones are only rendered into and
+/// resolved, so they are transient.
 fn create_color_image(
     device: &Device,
     allocator: &Arc<Mutex<Allocator>>,
     extent: vk::Extent2D,
     format: vk::Format,
+    samples: vk::SampleCountFlags,
+) -> Result<(vk::Image, Allocation)> {
+    let usage = if samples == vk::SampleCountFlags::TYPE_1 {
+        vk::ImageUsageFlags::COLOR_ATTACHMENT
+            | vk::ImageUsageFlags::SAMPLED
+            | vk::ImageUsageFlags::TRANSFER_SRC
+    } else {
+        vk::ImageUsageFlags::COLOR_ATTACHMENT | vk::ImageUsageFlags::TRANSIENT_ATTACHMENT
+    };
+    create_image(
+        device,
+        allocator,
+        extent,
+        format,
+        samples,
+        usage,
+        "offscreen-color",
+    )
+}
+
+/// Create a 2D image with one mip level and bind newly allocated device
+/// memory to it, after checking the device supports `format` for `usage`.
+/// `name` labels the allocation.
+pub(crate) fn create_image(
+    device: &Device,
+    allocator: &Arc<Mutex<Allocator>>,
+    extent: vk::Extent2D,
+    format: vk::Format,
+    samples: vk::SampleCountFlags,
+    usage: vk::ImageUsageFlags,
+    name: &str,
 ) -> Result<(vk::Image, Allocation)> {
+    device.validate_image_support(format, usage, samples)?;
     let image_info = vk::ImageCreateInfo::default()
         .image_type(vk::ImageType::TYPE_2D)
         .format(format)
@@ -185,18 +366,30 @@ fn create_color_image(
         })
         .mip_levels(1)
         .array_layers(1)
-        .samples(vk::SampleCountFlags::TYPE_1)
+        .samples(samples)
         .tiling(vk::ImageTiling::OPTIMAL)
-        .usage(vk::ImageUsageFlags::COLOR_ATTACHMENT | vk::ImageUsageFlags::SAMPLED)
+        .usage(usage)
         .sharing_mode(vk::SharingMode::EXCLUSIVE)
         .initial_layout(vk::ImageLayout::UNDEFINED);
+    allocate_image(device, allocator, &image_info, name)
+}
 
+/// Create an image from `image_info` and bind freshly allocated GPU-only
+/// memory to it.
+///
+/// The caller validates `image_info` against the device beforehand.
+pub(crate) fn allocate_image(
+    device: &Device,
+    allocator: &Arc<Mutex<Allocator>>,
+    image_info: &vk::ImageCreateInfo<'_>,
+    name: &str,
+) -> Result<(vk::Image, Allocation)> {
     // SAFETY: the device is valid and the create info describes a legal image.
     let image = unsafe {
         device
             .raw()
-            .create_image(&image_info, None)
-            .map_err(|e| Error::Backend(format!("failed to create offscreen image: {:?}", e)))?
+            .create_image(image_info, None)
+            .map_err(|e| Error::Backend(format!("failed to create image: {:?}", e)))?
     };
 
     // SAFETY: the image was just created and has no bound memory yet.
@@ -205,31 +398,31 @@ fn create_color_image(
         .lock()
         .unwrap_or_else(|e| e.into_inner())
         .allocate(&AllocationCreateDesc {
-            name: "offscreen-color",
+            name,
             requirements,
             location: MemoryLocation::GpuOnly,
             linear: false,
             allocation_scheme: AllocationScheme::GpuAllocatorManaged,
         })
-        .map_err(|e| Error::Backend(format!("failed to allocate offscreen image memory: {e}")))?;
+        .map_err(|e| Error::Backend(format!("failed to allocate image memory: {e}")))?;
 
     // SAFETY: the allocation satisfies the image's memory requirements.
     unsafe {
         device
             .raw()
             .bind_image_memory(image, allocation.memory(), allocation.offset())
-            .map_err(|e| {
-                Error::Backend(format!("failed to bind offscreen image memory: {:?}", e))
-            })?;
+            .map_err(|e| Error::Backend(format!("failed to bind image memory: {:?}", e)))?;
     }
 
     Ok((image, allocation))
 }
 
-fn create_image_view(
+/// Create a 2D view of the single mip level of `image`.
+pub(crate) fn create_image_view(
     device: &Device,
     image: vk::Image,
     format: vk::Format,
+    aspect: vk::ImageAspectFlags,
 ) -> Result<vk::ImageView> {
     let create_info = vk::ImageViewCreateInfo::default()
         .image(image)
@@ -237,7 +430,7 @@ fn create_image_view(
         .format(format)
         .subresource_range(
             vk::ImageSubresourceRange::default()
-                .aspect_mask(vk::ImageAspectFlags::COLOR)
+                .aspect_mask(aspect)
                 .base_mip_level(0)
                 .level_count(1)
                 .base_array_layer(0)
@@ -248,7 +441,7 @@ fn create_image_view(
         device
             .raw()
             .create_image_view(&create_info, None)
-            .map_err(|e| Error::Backend(format!("failed to create offscreen image view: {:?}", e)))
+            .map_err(|e| Error::Backend(format!("failed to create image view: {:?}", e)))
     }
 }
 
@@ -273,35 +466,152 @@ fn create_sampler(device: &Device) -> Result<vk::Sampler> {
 /// Transition the image from UNDEFINED to SHADER_READ_ONLY_OPTIMAL via a
 /// one-shot command buffer, so sampling is valid before the first render.
 fn transition_to_shader_read(device: &Device, image: vk::Image) -> Result<()> {
-    let queue_family_index = device.queue_family_indices().graphics;
-    let command_pool = CommandPool::new(device, queue_family_index)?;
-    let mut command_buffer: CommandBuffer = command_pool.allocate_command_buffer()?;
+    submit_one_shot(device, |command_buffer| {
+        command_buffer.pipeline_barrier(
+            vk::PipelineStageFlags::TOP_OF_PIPE,
+            vk::PipelineStageFlags::FRAGMENT_SHADER,
+            vk::DependencyFlags::empty(),
+            &[],
+            &[],
+            &[color_barrier(
+                image,
+                vk::AccessFlags::empty(),
+                vk::AccessFlags::SHADER_READ,
+                vk::ImageLayout::UNDEFINED,
+                vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+            )],
+        );
+    })
+}
 
-    command_buffer.begin(vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT)?;
-    let barrier = vk::ImageMemoryBarrier::default()
-        .src_access_mask(vk::AccessFlags::empty())
-        .dst_access_mask(vk::AccessFlags::SHADER_READ)
-        .old_layout(vk::ImageLayout::UNDEFINED)
-        .new_layout(vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL)
+fn color_barrier(
+    image: vk::Image,
+    src_access: vk::AccessFlags,
+    dst_access: vk::AccessFlags,
+    old_layout: vk::ImageLayout,
+    new_layout: vk::ImageLayout,
+) -> vk::ImageMemoryBarrier<'static> {
+    image_barrier(
+        image,
+        vk::ImageAspectFlags::COLOR,
+        src_access,
+        dst_access,
+        old_layout,
+        new_layout,
+    )
+}
+
+/// Copy the single mip level and layer of `image`'s `aspect` back to the host
+/// as tightly packed texels.
+///
+/// The image must be in `SHADER_READ_ONLY_OPTIMAL` with every earlier write
+/// complete, and is left in that layout; this call blocks until the copy has
+/// finished.
+pub(crate) fn read_image(
+    instance: &Instance,
+    device: &Device,
+    image: vk::Image,
+    aspect: vk::ImageAspectFlags,
+    format: vk::Format,
+    extent: vk::Extent2D,
+) -> Result<Vec<u8>> {
+    let extent = vk::Extent3D {
+        width: extent.width,
+        height: extent.height,
+        depth: 1,
+    };
+    let size = format::mip_chain_size(extent, format, 1).ok_or(Error::Unsupported)?;
+    let readback = Buffer::new(instance, device, size, vk::BufferUsageFlags::TRANSFER_DST)?;
+
+    submit_one_shot(device, |command_buffer| {
+        // The render pass already made its writes visible to fragment
+        // shaders; the copy only has to wait for sampling to finish.
+        command_buffer.pipeline_barrier(
+            vk::PipelineStageFlags::FRAGMENT_SHADER,
+            vk::PipelineStageFlags::TRANSFER,
+            vk::DependencyFlags::empty(),
+            &[],
+            &[],
+            &[image_barrier(
+                image,
+                aspect,
+                vk::AccessFlags::SHADER_READ,
+                vk::AccessFlags::TRANSFER_READ,
+                vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+                vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+            )],
+        );
+        let region = vk::BufferImageCopy::default()
+            .image_subresource(
+                vk::ImageSubresourceLayers::default()
+                    .aspect_mask(aspect)
+                    .mip_level(0)
+                    .base_array_layer(0)
+                    .layer_count(1),
+            )
+            .image_extent(extent);
+        command_buffer.copy_image_to_buffer(
+            image,
+            vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+            readback.raw(),
+            &[region],
+        );
+        command_buffer.pipeline_barrier(
+            vk::PipelineStageFlags::TRANSFER,
+            vk::PipelineStageFlags::FRAGMENT_SHADER | vk::PipelineStageFlags::HOST,
+            vk::DependencyFlags::empty(),
+            &[],
+            &[readback.host_read_barrier()],
+            &[image_barrier(
+                image,
+                aspect,
+                vk::AccessFlags::TRANSFER_READ,
+                vk::AccessFlags::SHADER_READ,
+                vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+                vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+            )],
+        );
+    })?;
+
+    readback.download()
+}
+
+/// A layout transition of the single mip level and layer of `image`.
+pub(crate) fn image_barrier(
+    image: vk::Image,
+    aspect: vk::ImageAspectFlags,
+    src_access: vk::AccessFlags,
+    dst_access: vk::AccessFlags,
+    old_layout: vk::ImageLayout,
+    new_layout: vk::ImageLayout,
+) -> vk::ImageMemoryBarrier<'static> {
+    vk::ImageMemoryBarrier::default()
+        .src_access_mask(src_access)
+        .dst_access_mask(dst_access)
+        .old_layout(old_layout)
+        .new_layout(new_layout)
         .src_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
         .dst_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
         .image(image)
         .subresource_range(
             vk::ImageSubresourceRange::default()
-                .aspect_mask(vk::ImageAspectFlags::COLOR)
+                .aspect_mask(aspect)
                 .base_mip_level(0)
                 .level_count(1)
                 .base_array_layer(0)
                 .layer_count(1),
-        );
-    command_buffer.pipeline_barrier(
-        vk::PipelineStageFlags::TOP_OF_PIPE,
-        vk::PipelineStageFlags::FRAGMENT_SHADER,
-        vk::DependencyFlags::empty(),
-        &[],
-        &[],
-        &[barrier],
-    );
+        )
+}
+
+/// Record commands with `record` into a one-shot command buffer, submit it
+/// to the graphics queue and block until it has executed.
+pub(crate) fn submit_one_shot(device: &Device, record: impl FnOnce(&CommandBuffer)) -> Result<()> {
+    let queue_family_index = device.queue_family_indices().graphics;
+    let command_pool = CommandPool::new(device, queue_family_index)?;
+    let mut command_buffer: CommandBuffer = command_pool.allocate_command_buffer()?;
+
+    command_buffer.begin(vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT)?;
+    record(&command_buffer);
     command_buffer.end()?;
 
     let command_buffers = [command_buffer.raw()];
@@ -315,17 +625,17 @@ fn transition_to_shader_read(device: &Device, image: vk::Image) -> Result<()> {
                 std::slice::from_ref(&submit_info),
                 vk::Fence::null(),
             )
-            .map_err(|e| Error::Backend(format!("failed to submit layout transition: {:?}", e)))?;
+            .map_err(|e| Error::from_vk("failed to submit one-shot commands", e))?;
         device
             .raw()
             .queue_wait_idle(device.graphics_queue())
-            .map_err(|e| Error::Backend(format!("failed to wait for transition: {:?}", e)))?;
+            .map_err(|e| Error::from_vk("failed to wait for one-shot commands", e))?;
     }
     Ok(())
 }
 
-fn log_free_error(err: &gpu_allocator::AllocationError) {
+pub(crate) fn log_free_error(err: &gpu_allocator::AllocationError) {
     // gpu-allocator reports double-frees and leaks here; destruction must not
     // panic, so surface the error through the log crate instead.
-    moonfield_log::error!("failed to free offscreen image allocation: {err}");
+    moonfield_log::error!("failed to free image allocation: {err}");
 }
@@ -1,15 +1,66 @@
 //! Vulkan graphics pipeline abstraction.
 
+use crate::descriptor::DescriptorSetLayout;
 use crate::device::Device;
 use crate::error::{Error, Result};
+use crate::pipeline_cache::PipelineCache;
 use crate::render_pass::RenderPass;
 use crate::shader_module::ShaderModule;
+use crate::viewport::{Scissor, Viewport};
 use ash::vk;
 
+/// Everything needed to build a [`GraphicsPipeline`] besides the device and
+/// render pass.
+///
+/// [`new`](Self::new) fills in the shaders and viewport extent; the
+/// remaining fields default to no vertex input, no descriptor sets or push
+/// constants, no pipeline cache and [`PipelineState::default`].
+#[derive(Clone, Copy)]
+pub struct GraphicsPipelineDesc<'a> {
+    pub vertex_shader: &'a ShaderModule,
+    pub fragment_shader: &'a ShaderModule,
+    pub vertex_input_bindings: &'a [vk::VertexInputBindingDescription],
+    pub vertex_input_attributes: &'a [vk::VertexInputAttributeDescription],
+    /// Size of the static viewport and scissor, unless
+    /// [`PipelineState::dynamic_viewport`] is set.
+    pub extent: vk::Extent2D,
+    pub set_layouts: &'a [&'a DescriptorSetLayout],
+    /// Push constant ranges of the pipeline layout, set with
+    /// [`CommandBuffer::set_push_constants`](crate::CommandBuffer::set_push_constants).
+    pub push_constant_ranges: &'a [vk::PushConstantRange],
+    pub state: PipelineState,
+    /// Cache the driver reuses compiled state from and adds it to.
+    pub cache: Option<&'a PipelineCache>,
+}
+
+impl<'a> GraphicsPipelineDesc<'a> {
+    /// Describe a pipeline running `vertex_shader` and `fragment_shader`
+    /// over a viewport of `extent`.
+    pub fn new(
+        vertex_shader: &'a ShaderModule,
+        fragment_shader: &'a ShaderModule,
+        extent: vk::Extent2D,
+    ) -> Self {
+        Self {
+            vertex_shader,
+            fragment_shader,
+            vertex_input_bindings: &[],
+            vertex_input_attributes: &[],
+            extent,
+            set_layouts: &[],
+            push_constant_ranges: &[],
+            state: PipelineState::default(),
+            cache: None,
+        }
+    }
+}
+
 /// A Vulkan graphics pipeline and its layout.
 pub struct GraphicsPipeline {
     pipeline: vk::Pipeline,
     layout: vk::PipelineLayout,
+    push_constant_ranges: Vec<vk::PushConstantRange>,
+    uses_blend_constant: bool,
     device: ash::Device,
 }
 
@@ -18,6 +69,7 @@ impl GraphicsPipeline {
     ///
     /// The pipeline uses the provided vertex/fragment shaders, a single
     /// subpass render pass, and static viewport/scissor covering `extent`.
+    /// Back faces with clockwise winding are culled.
     pub fn new(
         device: &Device,
         render_pass: &RenderPass,
@@ -27,42 +79,95 @@ impl GraphicsPipeline {
         vertex_input_attributes: &[vk::VertexInputAttributeDescription],
         extent: vk::Extent2D,
     ) -> Result<Self> {
+        Self::new_with_set_layouts(
+            device,
+            render_pass,
+            vertex_shader,
+            fragment_shader,
+            vertex_input_bindings,
+            vertex_input_attributes,
+            extent,
+            &[],
+        )
+    }
+
+    /// Create a basic graphics pipeline whose layout uses `set_layouts`, so
+    /// descriptor sets (e.g. uniform buffers) can be bound against
+    /// [`layout`](Self::layout).
+    #[allow(clippy::too_many_arguments)]
+    pub fn new_with_set_layouts(
+        device: &Device,
+        render_pass: &RenderPass,
+        vertex_shader: &ShaderModule,
+        fragment_shader: &ShaderModule,
+        vertex_input_bindings: &[vk::VertexInputBindingDescription],
+        vertex_input_attributes: &[vk::VertexInputAttributeDescription],
+        extent: vk::Extent2D,
+        set_layouts: &[&DescriptorSetLayout],
+    ) -> Result<Self> {
+        let desc = GraphicsPipelineDesc {
+            vertex_input_bindings,
+            vertex_input_attributes,
+            set_layouts,
+            state: PipelineState {
+                cull_mode: vk::CullModeFlags::BACK,
+                front_face: vk::FrontFace::CLOCKWISE,
+                ..PipelineState::default()
+            },
+            ..GraphicsPipelineDesc::new(vertex_shader, fragment_shader, extent)
+        };
+        Self::from_desc(device, render_pass, &desc)
+    }
+
+    /// Create a graphics pipeline from a full description.
+    ///
+    /// The pipeline state is validated against `render_pass`: depth testing
+    /// requires a depth attachment. Push constant ranges are validated
+    /// against the device's `max_push_constants_size`.
+    pub fn from_desc(
+        device: &Device,
+        render_pass: &RenderPass,
+        desc: &GraphicsPipelineDesc,
+    ) -> Result<Self> {
+        desc.state
+            .validate(render_pass.has_depth(), render_pass.has_stencil())?;
+        if desc.state.samples != render_pass.samples() {
+            return Err(Error::Validation(format!(
+                "pipeline rasterizes {:?} samples but the render pass has {:?}",
+                desc.state.samples,
+                render_pass.samples()
+            )));
+        }
+        validate_push_constant_ranges(
+            desc.push_constant_ranges,
+            device.properties().limits.max_push_constants_size,
+        )?;
+        let state = &desc.state;
+
         let vertex_entry = std::ffi::CString::new("main").unwrap();
         let fragment_entry = std::ffi::CString::new("main").unwrap();
 
         let shader_stages = [
             vk::PipelineShaderStageCreateInfo::default()
                 .stage(vk::ShaderStageFlags::VERTEX)
-                .module(vertex_shader.raw())
+                .module(desc.vertex_shader.raw())
                 .name(&vertex_entry),
             vk::PipelineShaderStageCreateInfo::default()
                 .stage(vk::ShaderStageFlags::FRAGMENT)
-                .module(fragment_shader.raw())
+                .module(desc.fragment_shader.raw())
                 .name(&fragment_entry),
         ];
 
         let vertex_input_info = vk::PipelineVertexInputStateCreateInfo::default()
-            .vertex_binding_descriptions(vertex_input_bindings)
-            .vertex_attribute_descriptions(vertex_input_attributes);
+            .vertex_binding_descriptions(desc.vertex_input_bindings)
+            .vertex_attribute_descriptions(desc.vertex_input_attributes);
 
         let input_assembly = vk::PipelineInputAssemblyStateCreateInfo::default()
-            .topology(vk::PrimitiveTopology::TRIANGLE_LIST)
-            .primitive_restart_enable(false);
-
-        let viewport = vk::Viewport::default()
-            .x(0.0)
-            .y(0.0)
-            .width(extent.width as f32)
-            .height(extent.height as f32)
-            .min_depth(0.0)
-            .max_depth(1.0);
-
-        let scissor = vk::Rect2D::default()
-            .offset(vk::Offset2D { x: 0, y: 0 })
-            .extent(extent);
-
-        let viewports = [viewport];
-        let scissors = [scissor];
+            .topology(state.topology)
+            .primitive_restart_enable(state.primitive_restart);
+
+        let viewports = [Viewport::from_extent(desc.extent).to_vk()];
+        let scissors = [Scissor::from_extent(desc.extent).to_vk()];
         let viewport_state = vk::PipelineViewportStateCreateInfo::default()
             .viewports(&viewports)
             .scissors(&scissors);
@@ -72,24 +177,51 @@ impl GraphicsPipeline {
             .rasterizer_discard_enable(false)
             .polygon_mode(vk::PolygonMode::FILL)
             .line_width(1.0)
-            .cull_mode(vk::CullModeFlags::BACK)
-            .front_face(vk::FrontFace::CLOCKWISE)
+            .cull_mode(state.cull_mode)
+            .front_face(state.front_face)
             .depth_bias_enable(false);
 
         let multisampling = vk::PipelineMultisampleStateCreateInfo::default()
             .sample_shading_enable(false)
-            .rasterization_samples(vk::SampleCountFlags::TYPE_1);
+            .rasterization_samples(state.samples);
 
-        let color_blend_attachment = vk::PipelineColorBlendAttachmentState::default()
-            .color_write_mask(vk::ColorComponentFlags::RGBA)
-            .blend_enable(false);
+        let mut depth_stencil = match state.depth {
+            Some(depth) => vk::PipelineDepthStencilStateCreateInfo::default()
+                .depth_test_enable(true)
+                .depth_write_enable(depth.write_enabled)
+                .depth_compare_op(depth.compare_op),
+            None => vk::PipelineDepthStencilStateCreateInfo::default(),
+        };
+        // The stencil reference and blend constant are dynamic, set with
+        // `CommandBuffer::set_stencil_reference` and `set_blend_constant`.
+        let mut dynamic_states = Vec::new();
+        if state.dynamic_viewport {
+            dynamic_states.extend([vk::DynamicState::VIEWPORT, vk::DynamicState::SCISSOR]);
+        }
+        if state.blend.uses_constant() {
+            dynamic_states.push(vk::DynamicState::BLEND_CONSTANTS);
+        }
+        if let Some(stencil) = state.stencil {
+            depth_stencil = depth_stencil
+                .stencil_test_enable(true)
+                .front(stencil.op_state(&stencil.front))
+                .back(stencil.op_state(&stencil.back));
+            dynamic_states.push(vk::DynamicState::STENCIL_REFERENCE);
+        }
+        let dynamic_state =
+            vk::PipelineDynamicStateCreateInfo::default().dynamic_states(&dynamic_states);
 
-        let color_blend_attachments = [color_blend_attachment];
+        let color_blend_attachments =
+            vec![state.blend.attachment_state(); render_pass.color_attachment_count() as usize];
         let color_blending = vk::PipelineColorBlendStateCreateInfo::default()
             .logic_op_enable(false)
             .attachments(&color_blend_attachments);
 
-        let pipeline_layout_info = vk::PipelineLayoutCreateInfo::default();
+        let set_layouts: Vec<vk::DescriptorSetLayout> =
+            desc.set_layouts.iter().map(|layout| layout.raw()).collect();
+        let pipeline_layout_info = vk::PipelineLayoutCreateInfo::default()
+            .set_layouts(&set_layouts)
+            .push_constant_ranges(desc.push_constant_ranges);
         let layout = unsafe {
             device
                 .raw()
@@ -97,7 +229,7 @@ impl GraphicsPipeline {
                 .map_err(|e| Error::Backend(format!("failed to create pipeline layout: {:?}", e)))?
         };
 
-        let pipeline_info = vk::GraphicsPipelineCreateInfo::default()
+        let mut pipeline_info = vk::GraphicsPipelineCreateInfo::default()
             .stages(&shader_stages)
             .vertex_input_state(&vertex_input_info)
             .input_assembly_state(&input_assembly)
@@ -105,26 +237,40 @@ impl GraphicsPipeline {
             .rasterization_state(&rasterizer)
             .multisample_state(&multisampling)
             .color_blend_state(&color_blending)
+            .dynamic_state(&dynamic_state)
             .layout(layout)
             .render_pass(render_pass.raw())
             .subpass(0);
+        if render_pass.has_depth() {
+            pipeline_info = pipeline_info.depth_stencil_state(&depth_stencil);
+        }
 
         let pipelines = unsafe {
-            device
-                .raw()
-                .create_graphics_pipelines(
-                    vk::PipelineCache::null(),
-                    std::slice::from_ref(&pipeline_info),
-                    None,
-                )
-                .map_err(|e| {
-                    Error::Backend(format!("failed to create graphics pipeline: {:?}", e))
-                })?
+            device.raw().create_graphics_pipelines(
+                desc.cache
+                    .map_or(vk::PipelineCache::null(), PipelineCache::raw),
+                std::slice::from_ref(&pipeline_info),
+                None,
+            )
+        };
+        let pipelines = match pipelines {
+            Ok(pipelines) => pipelines,
+            Err((_, e)) => {
+                // SAFETY: the layout was created above and is not referenced
+                // by any pipeline.
+                unsafe { device.raw().destroy_pipeline_layout(layout, None) };
+                return Err(Error::Backend(format!(
+                    "failed to create graphics pipeline: {:?}",
+                    e
+                )));
+            }
         };
 
         Ok(Self {
             pipeline: pipelines[0],
             layout,
+            push_constant_ranges: desc.push_constant_ranges.to_vec(),
+            uses_blend_constant: state.blend.uses_constant(),
             device: device.raw().clone(),
         })
     }
@@ -138,6 +284,19 @@ impl GraphicsPipeline {
     pub fn layout(&self) -> vk::PipelineLayout {
         self.layout
     }
+
+    /// The push constant ranges of the pipeline layout.
+    pub fn push_constant_ranges(&self) -> &[vk::PushConstantRange] {
+        &self.push_constant_ranges
+    }
+
+    /// Whether the blend state reads the blend constant, which must then be
+    /// set with
+    /// [`CommandBuffer::set_blend_constant`](crate::CommandBuffer::set_blend_constant)
+    /// before drawing.
+    pub fn uses_blend_constant(&self) -> bool {
+        self.uses_blend_constant
+    }
 }
 
 impl Drop for GraphicsPipeline {
@@ -148,3 +307,610 @@ impl Drop for GraphicsPipeline {
         }
     }
 }
+
+/// Check push constant ranges for a pipeline layout: offsets and sizes are
+/// multiples of 4, every range is non-empty and fits in `max_size` bytes,
+/// and no stage appears in two ranges.
+fn validate_push_constant_ranges(ranges: &[vk::PushConstantRange], max_size: u32) -> Result<()> {
+    let mut seen_stages = vk::ShaderStageFlags::empty();
+    for range in ranges {
+        if range.stage_flags.is_empty() {
+            return Err(Error::Validation(
+                "push constant range has no shader stages".to_string(),
+            ));
+        }
+        if range.size == 0 || !range.offset.is_multiple_of(4) || !range.size.is_multiple_of(4) {
+            return Err(Error::Validation(format!(
+                "push constant range {}+{} must be non-empty with offset and size multiples of 4",
+                range.offset, range.size
+            )));
+        }
+        if range
+            .offset
+            .checked_add(range.size)
+            .is_none_or(|end| end > max_size)
+        {
+            return Err(Error::Validation(format!(
+                "push constant range {}+{} exceeds the device limit of {} bytes",
+                range.offset, range.size, max_size
+            )));
+        }
+        if seen_stages.intersects(range.stage_flags) {
+            return Err(Error::Validation(format!(
+                "shader stages {:?} appear in more than one push constant range",
+                seen_stages & range.stage_flags
+            )));
+        }
+        seen_stages |= range.stage_flags;
+    }
+    Ok(())
+}
+
+/// Check that `size` bytes of push constants at `offset` for `stages` are
+/// 4-byte aligned, that every byte lies in a range for each of `stages`, and
+/// that `stages` includes every stage of each range overlapping the bytes.
+pub(crate) fn validate_push_constants(
+    ranges: &[vk::PushConstantRange],
+    stages: vk::ShaderStageFlags,
+    offset: u32,
+    size: u32,
+) -> Result<()> {
+    if size == 0 || !offset.is_multiple_of(4) || !size.is_multiple_of(4) {
+        return Err(Error::Validation(format!(
+            "push constants {}+{} must be non-empty with offset and size multiples of 4",
+            offset, size
+        )));
+    }
+    let Some(end) = offset.checked_add(size) else {
+        return Err(Error::Validation(format!(
+            "push constants {}+{} overflow",
+            offset, size
+        )));
+    };
+    // Range bounds are multiples of 4, so checking each word is enough.
+    let covered = (0..32)
+        .map(|bit| vk::ShaderStageFlags::from_raw(1 << bit))
+        .all(|stage| {
+            !stages.contains(stage)
+                || (offset..end).step_by(4).all(|word| {
+                    ranges.iter().any(|range| {
+                        range.stage_flags.contains(stage)
+                            && (range.offset..range.offset + range.size).contains(&word)
+                    })
+                })
+        });
+    if stages.is_empty() || !covered {
+        return Err(Error::Validation(format!(
+            "push constants {}+{} for {:?} are outside the pipeline layout's ranges",
+            offset, size, stages
+        )));
+    }
+    let overlapping = ranges
+        .iter()
+        .filter(|range| range.offset < end && offset < range.offset + range.size);
+    for range in overlapping {
+        if !stages.contains(range.stage_flags) {
+            return Err(Error::Validation(format!(
+                "push constants {}+{} for {:?} overlap the range {}+{} for {:?}, \
+                 so they must be pushed for all of its stages",
+                offset, size, stages, range.offset, range.size, range.stage_flags
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Fixed-function state of a graphics pipeline.
+///
+/// The default draws triangle lists without culling, depth or stencil
+/// testing or blending, with one sample per pixel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PipelineState {
+    pub topology: vk::PrimitiveTopology,
+    /// Restart strips and fans at the index type's maximum value. Only
+    /// valid for strip and fan topologies.
+    pub primitive_restart: bool,
+    pub cull_mode: vk::CullModeFlags,
+    pub front_face: vk::FrontFace,
+    /// Depth test configuration, or `None` to disable depth testing.
+    pub depth: Option<DepthState>,
+    /// Stencil test configuration, or `None` to disable stencil testing.
+    pub stencil: Option<StencilState>,
+    pub samples: vk::SampleCountFlags,
+    pub blend: BlendState,
+    /// Leave the viewport and scissor dynamic, to be set with
+    /// [`CommandBuffer::set_viewport`](crate::CommandBuffer::set_viewport)
+    /// and [`set_scissor`](crate::CommandBuffer::set_scissor) before drawing.
+    pub dynamic_viewport: bool,
+}
+
+impl PipelineState {
+    fn validate(&self, render_pass_has_depth: bool, render_pass_has_stencil: bool) -> Result<()> {
+        if self.depth.is_some() && !render_pass_has_depth {
+            return Err(Error::Validation(
+                "depth testing requires a render pass with a depth attachment".to_string(),
+            ));
+        }
+        if self.stencil.is_some() && !render_pass_has_stencil {
+            return Err(Error::Validation(
+                "stencil testing requires a render pass with a stencil attachment".to_string(),
+            ));
+        }
+        if self.primitive_restart && !is_strip_or_fan(self.topology) {
+            return Err(Error::Validation(format!(
+                "primitive restart is not supported for {:?}",
+                self.topology
+            )));
+        }
+        if self.samples.as_raw().count_ones() != 1 {
+            return Err(Error::Validation(format!(
+                "pipeline sample count must be a single value, got {:?}",
+                self.samples
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl Default for PipelineState {
+    fn default() -> Self {
+        Self {
+            topology: vk::PrimitiveTopology::TRIANGLE_LIST,
+            primitive_restart: false,
+            cull_mode: vk::CullModeFlags::NONE,
+            front_face: vk::FrontFace::COUNTER_CLOCKWISE,
+            depth: None,
+            stencil: None,
+            samples: vk::SampleCountFlags::TYPE_1,
+            blend: BlendState::REPLACE,
+            dynamic_viewport: false,
+        }
+    }
+}
+
+/// Depth test configuration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DepthState {
+    /// Comparison between the fragment depth and the stored depth; the
+    /// fragment passes when it evaluates to true.
+    pub compare_op: vk::CompareOp,
+    /// Whether passing fragments write their depth.
+    pub write_enabled: bool,
+}
+
+impl DepthState {
+    /// Standard depth testing: nearer fragments pass and write their depth.
+    pub const LESS: Self = Self {
+        compare_op: vk::CompareOp::LESS,
+        write_enabled: true,
+    };
+
+    /// Test against existing depth without writing, e.g. for transparent
+    /// geometry drawn after opaque geometry.
+    pub const READ_ONLY: Self = Self {
+        compare_op: vk::CompareOp::LESS_OR_EQUAL,
+        write_enabled: false,
+    };
+}
+
+/// Stencil test configuration.
+///
+/// Each face compares `reference & read_mask` against
+/// `stored & read_mask`, then applies one of its operations to the stored
+/// value, writing only the bits in `write_mask`. The reference is dynamic
+/// state set with
+/// [`CommandBuffer::set_stencil_reference`](crate::CommandBuffer::set_stencil_reference).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StencilState {
+    /// Test and operations for front-facing primitives.
+    pub front: StencilFaceState,
+    /// Test and operations for back-facing primitives.
+    pub back: StencilFaceState,
+    pub read_mask: u32,
+    pub write_mask: u32,
+}
+
+impl StencilState {
+    /// Write the reference value wherever a primitive is drawn, e.g. to
+    /// mark a region for later draws.
+    pub const WRITE_REFERENCE: Self = Self::both_faces(StencilFaceState {
+        compare_op: vk::CompareOp::ALWAYS,
+        fail_op: vk::StencilOp::KEEP,
+        depth_fail_op: vk::StencilOp::KEEP,
+        pass_op: vk::StencilOp::REPLACE,
+    });
+
+    /// Draw only where the stored value equals the reference, leaving the
+    /// stencil contents unchanged.
+    pub const EQUAL_REFERENCE: Self = Self::both_faces(StencilFaceState {
+        compare_op: vk::CompareOp::EQUAL,
+        fail_op: vk::StencilOp::KEEP,
+        depth_fail_op: vk::StencilOp::KEEP,
+        pass_op: vk::StencilOp::KEEP,
+    });
+
+    /// Apply `face` to front and back faces with all mask bits set.
+    pub const fn both_faces(face: StencilFaceState) -> Self {
+        Self {
+            front: face,
+            back: face,
+            read_mask: !0,
+            write_mask: !0,
+        }
+    }
+
+    /// Whether either face reads the stencil reference, which must then be
+    /// set before drawing.
+    pub fn needs_reference(&self) -> bool {
+        self.front.needs_reference() || self.back.needs_reference()
+    }
+
+    fn op_state(&self, face: &StencilFaceState) -> vk::StencilOpState {
+        vk::StencilOpState::default()
+            .fail_op(face.fail_op)
+            .pass_op(face.pass_op)
+            .depth_fail_op(face.depth_fail_op)
+            .compare_op(face.compare_op)
+            .compare_mask(self.read_mask)
+            .write_mask(self.write_mask)
+    }
+}
+
+/// Stencil test and operations for one face.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StencilFaceState {
+    /// Comparison between the reference and the stored value; the fragment
+    /// passes when it evaluates to true.
+    pub compare_op: vk::CompareOp,
+    /// Applied when the stencil test fails.
+    pub fail_op: vk::StencilOp,
+    /// Applied when the stencil test passes but the depth test fails.
+    pub depth_fail_op: vk::StencilOp,
+    /// Applied when both tests pass.
+    pub pass_op: vk::StencilOp,
+}
+
+impl StencilFaceState {
+    /// Whether the comparison or an operation reads the reference value.
+    pub fn needs_reference(&self) -> bool {
+        !matches!(
+            self.compare_op,
+            vk::CompareOp::ALWAYS | vk::CompareOp::NEVER
+        ) || [self.fail_op, self.depth_fail_op, self.pass_op].contains(&vk::StencilOp::REPLACE)
+    }
+}
+
+fn is_strip_or_fan(topology: vk::PrimitiveTopology) -> bool {
+    matches!(
+        topology,
+        vk::PrimitiveTopology::LINE_STRIP
+            | vk::PrimitiveTopology::TRIANGLE_STRIP
+            | vk::PrimitiveTopology::TRIANGLE_FAN
+            | vk::PrimitiveTopology::LINE_STRIP_WITH_ADJACENCY
+            | vk::PrimitiveTopology::TRIANGLE_STRIP_WITH_ADJACENCY
+    )
+}
+
+/// Color blend equation for a single color attachment.
+///
+/// The color and alpha channels are blended independently as
+/// `result = src * src_factor <op> dst * dst_factor`. When `enabled` is
+/// `false` the fragment output replaces the attachment contents and the
+/// factors are ignored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlendState {
+    pub enabled: bool,
+    pub src_color_factor: vk::BlendFactor,
+    pub dst_color_factor: vk::BlendFactor,
+    pub color_op: vk::BlendOp,
+    pub src_alpha_factor: vk::BlendFactor,
+    pub dst_alpha_factor: vk::BlendFactor,
+    pub alpha_op: vk::BlendOp,
+}
+
+impl BlendState {
+    /// No blending: the source overwrites the destination.
+    pub const REPLACE: Self = Self {
+        enabled: false,
+        src_color_factor: vk::BlendFactor::ONE,
+        dst_color_factor: vk::BlendFactor::ZERO,
+        color_op: vk::BlendOp::ADD,
+        src_alpha_factor: vk::BlendFactor::ONE,
+        dst_alpha_factor: vk::BlendFactor::ZERO,
+        alpha_op: vk::BlendOp::ADD,
+    };
+
+    /// Straight alpha blending.
+    ///
+    /// Color: `src * src.a + dst * (1 - src.a)`.
+    /// Alpha: `src.a + dst.a * (1 - src.a)`.
+    pub const ALPHA_BLENDING: Self = Self {
+        enabled: true,
+        src_color_factor: vk::BlendFactor::SRC_ALPHA,
+        dst_color_factor: vk::BlendFactor::ONE_MINUS_SRC_ALPHA,
+        color_op: vk::BlendOp::ADD,
+        src_alpha_factor: vk::BlendFactor::ONE,
+        dst_alpha_factor: vk::BlendFactor::ONE_MINUS_SRC_ALPHA,
+        alpha_op: vk::BlendOp::ADD,
+    };
+
+    /// Premultiplied alpha blending.
+    ///
+    /// Color: `src + dst * (1 - src.a)`.
+    /// Alpha: `src.a + dst.a * (1 - src.a)`.
+    pub const PREMULTIPLIED_ALPHA_BLENDING: Self = Self {
+        enabled: true,
+        src_color_factor: vk::BlendFactor::ONE,
+        dst_color_factor: vk::BlendFactor::ONE_MINUS_SRC_ALPHA,
+        color_op: vk::BlendOp::ADD,
+        src_alpha_factor: vk::BlendFactor::ONE,
+        dst_alpha_factor: vk::BlendFactor::ONE_MINUS_SRC_ALPHA,
+        alpha_op: vk::BlendOp::ADD,
+    };
+
+    /// Additive blending, for particles and glow.
+    ///
+    /// Color: `src + dst`.
+    /// Alpha: `src.a + dst.a`.
+    pub const ADDITIVE: Self = Self {
+        enabled: true,
+        src_color_factor: vk::BlendFactor::ONE,
+        dst_color_factor: vk::BlendFactor::ONE,
+        color_op: vk::BlendOp::ADD,
+        src_alpha_factor: vk::BlendFactor::ONE,
+        dst_alpha_factor: vk::BlendFactor::ONE,
+        alpha_op: vk::BlendOp::ADD,
+    };
+
+    /// Multiplicative blending, for decals.
+    ///
+    /// Color: `src * dst + dst * 0`.
+    /// Alpha: `src.a * dst.a + dst.a * 0`.
+    pub const MULTIPLY: Self = Self {
+        enabled: true,
+        src_color_factor: vk::BlendFactor::DST_COLOR,
+        dst_color_factor: vk::BlendFactor::ZERO,
+        color_op: vk::BlendOp::ADD,
+        src_alpha_factor: vk::BlendFactor::DST_ALPHA,
+        dst_alpha_factor: vk::BlendFactor::ZERO,
+        alpha_op: vk::BlendOp::ADD,
+    };
+
+    /// Whether any factor reads the pipeline's blend constants, which must
+    /// then be set before drawing.
+    pub fn uses_constant(&self) -> bool {
+        self.enabled
+            && [
+                self.src_color_factor,
+                self.dst_color_factor,
+                self.src_alpha_factor,
+                self.dst_alpha_factor,
+            ]
+            .iter()
+            .any(|factor| is_constant_factor(*factor))
+    }
+
+    /// The Vulkan attachment blend state, writing all color components.
+    pub fn attachment_state(&self) -> vk::PipelineColorBlendAttachmentState {
+        vk::PipelineColorBlendAttachmentState::default()
+            .blend_enable(self.enabled)
+            .src_color_blend_factor(self.src_color_factor)
+            .dst_color_blend_factor(self.dst_color_factor)
+            .color_blend_op(self.color_op)
+            .src_alpha_blend_factor(self.src_alpha_factor)
+            .dst_alpha_blend_factor(self.dst_alpha_factor)
+            .alpha_blend_op(self.alpha_op)
+            .color_write_mask(vk::ColorComponentFlags::RGBA)
+    }
+}
+
+impl Default for BlendState {
+    fn default() -> Self {
+        Self::REPLACE
+    }
+}
+
+fn is_constant_factor(factor: vk::BlendFactor) -> bool {
+    matches!(
+        factor,
+        vk::BlendFactor::CONSTANT_COLOR
+            | vk::BlendFactor::ONE_MINUS_CONSTANT_COLOR
+            | vk::BlendFactor::CONSTANT_ALPHA
+            | vk::BlendFactor::ONE_MINUS_CONSTANT_ALPHA
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_pipeline_state() {
+        let state = PipelineState::default();
+        assert_eq!(state.topology, vk::PrimitiveTopology::TRIANGLE_LIST);
+        assert_eq!(state.cull_mode, vk::CullModeFlags::NONE);
+        assert_eq!(state.depth, None);
+        assert!(state.validate(false, false).is_ok());
+    }
+
+    #[test]
+    fn test_pipeline_state_validation() {
+        let depth_tested = PipelineState {
+            cull_mode: vk::CullModeFlags::BACK,
+            depth: Some(DepthState::LESS),
+            ..PipelineState::default()
+        };
+        assert!(depth_tested.validate(true, false).is_ok());
+        assert!(depth_tested.validate(false, false).is_err());
+
+        let list_restart = PipelineState {
+            primitive_restart: true,
+            ..PipelineState::default()
+        };
+        assert!(list_restart.validate(false, false).is_err());
+        let strip_restart = PipelineState {
+            topology: vk::PrimitiveTopology::TRIANGLE_STRIP,
+            ..list_restart
+        };
+        assert!(strip_restart.validate(false, false).is_ok());
+
+        let multi_sample_mask = PipelineState {
+            samples: vk::SampleCountFlags::TYPE_1 | vk::SampleCountFlags::TYPE_4,
+            ..PipelineState::default()
+        };
+        assert!(multi_sample_mask.validate(false, false).is_err());
+
+        let stencil_tested = PipelineState {
+            stencil: Some(StencilState::EQUAL_REFERENCE),
+            ..PipelineState::default()
+        };
+        assert!(stencil_tested.validate(true, true).is_ok());
+        assert!(stencil_tested.validate(true, false).is_err());
+    }
+
+    #[test]
+    fn test_push_constant_range_validation() {
+        let range = |stage_flags, offset, size| vk::PushConstantRange {
+            stage_flags,
+            offset,
+            size,
+        };
+        let vertex = vk::ShaderStageFlags::VERTEX;
+        let fragment = vk::ShaderStageFlags::FRAGMENT;
+
+        assert!(validate_push_constant_ranges(&[], 128).is_ok());
+        assert!(validate_push_constant_ranges(&[range(vertex, 0, 64)], 128).is_ok());
+        assert!(validate_push_constant_ranges(
+            &[range(vertex, 0, 64), range(fragment, 64, 16)],
+            128
+        )
+        .is_ok());
+
+        // Misaligned, empty, oversized, stageless and overlapping stages.
+        assert!(validate_push_constant_ranges(&[range(vertex, 2, 64)], 128).is_err());
+        assert!(validate_push_constant_ranges(&[range(vertex, 0, 62)], 128).is_err());
+        assert!(validate_push_constant_ranges(&[range(vertex, 0, 0)], 128).is_err());
+        assert!(validate_push_constant_ranges(&[range(vertex, 64, 128)], 128).is_err());
+        assert!(
+            validate_push_constant_ranges(&[range(vk::ShaderStageFlags::empty(), 0, 4)], 128)
+                .is_err()
+        );
+        assert!(validate_push_constant_ranges(
+            &[range(vertex, 0, 16), range(vertex | fragment, 16, 16)],
+            128
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_push_constants_must_lie_in_a_range() {
+        let ranges = [vk::PushConstantRange {
+            stage_flags: vk::ShaderStageFlags::VERTEX | vk::ShaderStageFlags::FRAGMENT,
+            offset: 16,
+            size: 64,
+        }];
+        let both = vk::ShaderStageFlags::VERTEX | vk::ShaderStageFlags::FRAGMENT;
+        assert!(validate_push_constants(&ranges, both, 16, 64).is_ok());
+        assert!(validate_push_constants(&ranges, both, 32, 16).is_ok());
+        assert!(validate_push_constants(&ranges, both, 18, 16).is_err());
+        assert!(validate_push_constants(&ranges, both, 16, 6).is_err());
+        assert!(validate_push_constants(&ranges, both, 0, 16).is_err());
+        assert!(validate_push_constants(&ranges, both, 64, 32).is_err());
+        assert!(validate_push_constants(&ranges, vk::ShaderStageFlags::COMPUTE, 16, 16).is_err());
+        // Pushing for only some of an overlapping range's stages is invalid.
+        let vertex = vk::ShaderStageFlags::VERTEX;
+        assert!(validate_push_constants(&ranges, vertex, 32, 16).is_err());
+
+        // Separate ranges per stage, sharing bytes 16..32.
+        let split = [
+            vk::PushConstantRange {
+                stage_flags: vk::ShaderStageFlags::VERTEX,
+                offset: 0,
+                size: 32,
+            },
+            vk::PushConstantRange {
+                stage_flags: vk::ShaderStageFlags::FRAGMENT,
+                offset: 16,
+                size: 32,
+            },
+        ];
+        assert!(validate_push_constants(&split, vertex, 0, 16).is_ok());
+        assert!(validate_push_constants(&split, vertex, 0, 32).is_err());
+        assert!(validate_push_constants(&split, both, 16, 16).is_ok());
+        assert!(validate_push_constants(&split, both, 0, 32).is_err());
+        let fragment = vk::ShaderStageFlags::FRAGMENT;
+        assert!(validate_push_constants(&split, fragment, 32, 16).is_ok());
+    }
+
+    #[test]
+    fn test_stencil_reference_usage() {
+        assert!(StencilState::WRITE_REFERENCE.needs_reference());
+        assert!(StencilState::EQUAL_REFERENCE.needs_reference());
+
+        let invert = StencilState::both_faces(StencilFaceState {
+            compare_op: vk::CompareOp::ALWAYS,
+            fail_op: vk::StencilOp::KEEP,
+            depth_fail_op: vk::StencilOp::KEEP,
+            pass_op: vk::StencilOp::INVERT,
+        });
+        assert!(!invert.needs_reference());
+        let front_only = StencilState {
+            front: StencilState::EQUAL_REFERENCE.front,
+            ..invert
+        };
+        assert!(front_only.needs_reference());
+
+        let op_state = invert.op_state(&invert.front);
+        assert_eq!(op_state.pass_op, vk::StencilOp::INVERT);
+        assert_eq!(op_state.compare_mask, 0xFFFF_FFFF);
+    }
+
+    #[test]
+    fn test_additive_blend_sums_source_and_destination() {
+        let blend = BlendState::ADDITIVE;
+        assert!(blend.enabled);
+        assert_eq!(blend.src_color_factor, vk::BlendFactor::ONE);
+        assert_eq!(blend.dst_color_factor, vk::BlendFactor::ONE);
+        assert_eq!(blend.color_op, vk::BlendOp::ADD);
+        assert_eq!(blend.src_alpha_factor, vk::BlendFactor::ONE);
+        assert_eq!(blend.dst_alpha_factor, vk::BlendFactor::ONE);
+        assert_eq!(blend.alpha_op, vk::BlendOp::ADD);
+        assert!(!blend.uses_constant());
+    }
+
+    #[test]
+    fn test_multiply_blend_scales_destination_by_source() {
+        let blend = BlendState::MULTIPLY;
+        assert!(blend.enabled);
+        assert_eq!(blend.src_color_factor, vk::BlendFactor::DST_COLOR);
+        assert_eq!(blend.dst_color_factor, vk::BlendFactor::ZERO);
+        assert_eq!(blend.color_op, vk::BlendOp::ADD);
+        assert_eq!(blend.src_alpha_factor, vk::BlendFactor::DST_ALPHA);
+        assert_eq!(blend.dst_alpha_factor, vk::BlendFactor::ZERO);
+        assert_eq!(blend.alpha_op, vk::BlendOp::ADD);
+        assert!(!blend.uses_constant());
+    }
+
+    #[test]
+    fn test_uses_constant_detects_constant_factors() {
+        let blend = BlendState {
+            src_color_factor: vk::BlendFactor::CONSTANT_COLOR,
+            ..BlendState::ALPHA_BLENDING
+        };
+        assert!(blend.uses_constant());
+
+        // Factors are ignored while blending is disabled.
+        let disabled = BlendState {
+            enabled: false,
+            ..blend
+        };
+        assert!(!disabled.uses_constant());
+    }
+
+    #[test]
+    fn test_replace_disables_blending() {
+        let state = BlendState::REPLACE.attachment_state();
+        assert_eq!(state.blend_enable, vk::FALSE);
+        assert_eq!(state.color_write_mask, vk::ColorComponentFlags::RGBA);
+    }
+}
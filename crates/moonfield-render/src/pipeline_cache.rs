@@ -0,0 +1,206 @@
+//! Vulkan pipeline cache that can be persisted between runs.
+//!
+//! The driver fills a [`PipelineCache`] with compiled pipeline state when
+//! pipelines are created through it; saving the cache blob and seeding the
+//! next run's cache with it lets the driver skip that compilation. Blobs
+//! written by a different driver or device are detected from their header
+//! and discarded.
+
+use crate::device::Device;
+use crate::error::{Error, Result};
+use ash::vk;
+use std::path::Path;
+
+/// Size of the `VkPipelineCacheHeaderVersionOne` header.
+const HEADER_SIZE: usize = 32;
+
+/// A Vulkan pipeline cache.
+pub struct PipelineCache {
+    cache: vk::PipelineCache,
+    device: ash::Device,
+}
+
+impl PipelineCache {
+    /// Create an empty pipeline cache.
+    pub fn new(device: &Device) -> Result<Self> {
+        Self::create(device, &[])
+    }
+
+    /// Create a pipeline cache seeded with `data` from [`data`](Self::data).
+    ///
+    /// Data that is malformed or was written for another device or driver
+    /// is ignored with a warning, and an empty cache is created instead.
+    pub fn from_data(device: &Device, data: &[u8]) -> Result<Self> {
+        if is_compatible(data, device.properties()) {
+            return Self::create(device, data);
+        }
+        moonfield_log::warn!(
+            "ignoring incompatible pipeline cache data ({} bytes)",
+            data.len()
+        );
+        Self::new(device)
+    }
+
+    /// Create a pipeline cache seeded from the file at `path`, as written by
+    /// [`save`](Self::save).
+    ///
+    /// A missing file gives an empty cache, as on the first run.
+    pub fn load(device: &Device, path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        match std::fs::read(path) {
+            Ok(data) => Self::from_data(device, &data),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Self::new(device),
+            Err(e) => Err(Error::Backend(format!(
+                "failed to read pipeline cache {}: {}",
+                path.display(),
+                e
+            ))),
+        }
+    }
+
+    /// The cache contents, for seeding a cache in a later run with
+    /// [`from_data`](Self::from_data).
+    pub fn data(&self) -> Result<Vec<u8>> {
+        unsafe {
+            self.device
+                .get_pipeline_cache_data(self.cache)
+                .map_err(|e| Error::Backend(format!("failed to get pipeline cache data: {:?}", e)))
+        }
+    }
+
+    /// Write the cache contents to `path`.
+    ///
+    /// The file is written under a temporary name and renamed into place, so
+    /// a crash never leaves a truncated cache behind.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let data = self.data()?;
+        let temp_path = path.with_extension(format!("{}.tmp", std::process::id()));
+        std::fs::write(&temp_path, data)
+            .and_then(|()| std::fs::rename(&temp_path, path))
+            .map_err(|e| {
+                let _ = std::fs::remove_file(&temp_path);
+                Error::Backend(format!(
+                    "failed to write pipeline cache {}: {}",
+                    path.display(),
+                    e
+                ))
+            })
+    }
+
+    /// Access the raw `vk::PipelineCache` handle.
+    pub fn raw(&self) -> vk::PipelineCache {
+        self.cache
+    }
+
+    fn create(device: &Device, data: &[u8]) -> Result<Self> {
+        let create_info = vk::PipelineCacheCreateInfo::default().initial_data(data);
+        let cache = unsafe {
+            device
+                .raw()
+                .create_pipeline_cache(&create_info, None)
+                .map_err(|e| Error::Backend(format!("failed to create pipeline cache: {:?}", e)))?
+        };
+
+        Ok(Self {
+            cache,
+            device: device.raw().clone(),
+        })
+    }
+}
+
+impl Drop for PipelineCache {
+    fn drop(&mut self) {
+        unsafe {
+            self.device.destroy_pipeline_cache(self.cache, None);
+        }
+    }
+}
+
+/// Whether `data` starts with a version-one pipeline cache header matching
+/// the device described by `properties`.
+///
+/// Header fields are little-endian regardless of the host byte order.
+fn is_compatible(data: &[u8], properties: &vk::PhysicalDeviceProperties) -> bool {
+    let Some(header) = data.get(..HEADER_SIZE) else {
+        return false;
+    };
+    let field = |index: usize| {
+        let bytes = &header[index * 4..index * 4 + 4];
+        u32::from_le_bytes(bytes.try_into().unwrap())
+    };
+    let header_size = field(0) as usize;
+    header_size >= HEADER_SIZE
+        && header_size <= data.len()
+        && field(1) == vk::PipelineCacheHeaderVersion::ONE.as_raw() as u32
+        && field(2) == properties.vendor_id
+        && field(3) == properties.device_id
+        && header[16..32] == properties.pipeline_cache_uuid
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn properties() -> vk::PhysicalDeviceProperties {
+        vk::PhysicalDeviceProperties {
+            vendor_id: 0x10DE,
+            device_id: 0x2504,
+            pipeline_cache_uuid: [7; vk::UUID_SIZE],
+            ..Default::default()
+        }
+    }
+
+    fn header(version: u32, vendor_id: u32, uuid: [u8; vk::UUID_SIZE]) -> Vec<u8> {
+        let mut data = Vec::new();
+        for field in [HEADER_SIZE as u32, version, vendor_id, 0x2504] {
+            data.extend_from_slice(&field.to_le_bytes());
+        }
+        data.extend_from_slice(&uuid);
+        data
+    }
+
+    #[test]
+    fn test_header_must_match_device() {
+        let uuid = properties().pipeline_cache_uuid;
+        let mut valid = header(1, 0x10DE, uuid);
+        assert!(is_compatible(&valid, &properties()));
+        valid.extend_from_slice(&[0xAB; 64]);
+        assert!(is_compatible(&valid, &properties()));
+
+        assert!(!is_compatible(&header(2, 0x10DE, uuid), &properties()));
+        assert!(!is_compatible(&header(1, 0x1002, uuid), &properties()));
+        assert!(!is_compatible(&header(1, 0x10DE, [0; 16]), &properties()));
+        assert!(!is_compatible(&valid[..HEADER_SIZE - 1], &properties()));
+        assert!(!is_compatible(&[], &properties()));
+
+        // A header claiming to be larger than the blob is malformed.
+        let mut oversized = header(1, 0x10DE, uuid);
+        oversized[..4].copy_from_slice(&64u32.to_le_bytes());
+        assert!(!is_compatible(&oversized, &properties()));
+    }
+
+    /// Needs a Vulkan device; skipped when no driver is available.
+    #[test]
+    fn test_cache_round_trips_through_bytes_and_disk() {
+        let Some((_instance, device, _allocator)) = crate::test_util::headless_gpu() else {
+            return;
+        };
+
+        let cache = PipelineCache::new(&device).expect("empty cache");
+        let data = cache.data().expect("cache data");
+        assert!(is_compatible(&data, device.properties()));
+        PipelineCache::from_data(&device, &data).expect("seeded cache");
+        PipelineCache::from_data(&device, b"not a pipeline cache").expect("fallback cache");
+
+        let path = std::env::temp_dir().join(format!(
+            "moonfield-pipeline-cache-{}.bin",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+        PipelineCache::load(&device, &path).expect("missing file");
+        cache.save(&path).expect("save");
+        PipelineCache::load(&device, &path).expect("load");
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -31,17 +31,10 @@ fn init_vulkan() {
     match Instance::new_headless() {
         Ok(instance) => match Device::new(&instance, None) {
             Ok(device) => {
-                let props = device.physical_device();
-                let device_name = unsafe {
-                    std::ffi::CStr::from_ptr(
-                        instance
-                            .physical_device_properties(props)
-                            .device_name
-                            .as_ptr(),
-                    )
-                    .to_string_lossy()
-                };
-                info!("Render initialized Vulkan on device: {}", device_name);
+                info!(
+                    "Render initialized Vulkan on device: {}",
+                    device.adapter_info().summary()
+                );
             }
             Err(e) => {
                 error!("Render could not create Vulkan device: {}", e);
@@ -0,0 +1,238 @@
+//! Vulkan query pool abstraction.
+
+use crate::device::Device;
+use crate::error::{Error, Result};
+use ash::vk;
+
+/// A Vulkan query pool.
+///
+/// Queries are reset and written through [`CommandBuffer`] and read back on
+/// the host with [`results`](Self::results).
+///
+/// [`CommandBuffer`]: crate::CommandBuffer
+pub struct QueryPool {
+    pool: vk::QueryPool,
+    query_type: vk::QueryType,
+    count: u32,
+    device: ash::Device,
+}
+
+impl QueryPool {
+    /// Create a pool of `count` queries of `query_type`.
+    ///
+    /// Pipeline statistics queries are not supported because they need a
+    /// device feature this crate does not enable.
+    pub fn new(device: &Device, query_type: vk::QueryType, count: u32) -> Result<Self> {
+        if count == 0 {
+            return Err(Error::Validation(
+                "query pool must contain at least one query".to_string(),
+            ));
+        }
+        if query_type == vk::QueryType::PIPELINE_STATISTICS {
+            return Err(Error::Unsupported);
+        }
+
+        let create_info = vk::QueryPoolCreateInfo::default()
+            .query_type(query_type)
+            .query_count(count);
+        let pool = unsafe {
+            device
+                .raw()
+                .create_query_pool(&create_info, None)
+                .map_err(|e| Error::Backend(format!("failed to create query pool: {:?}", e)))?
+        };
+
+        Ok(Self {
+            pool,
+            query_type,
+            count,
+            device: device.raw().clone(),
+        })
+    }
+
+    /// Create a pool of `count` timestamp queries.
+    pub fn new_timestamps(device: &Device, count: u32) -> Result<Self> {
+        Self::new(device, vk::QueryType::TIMESTAMP, count)
+    }
+
+    /// Create a pool of `count` occlusion queries, counting the samples
+    /// that pass the depth and stencil tests between a begin and an end.
+    pub fn new_occlusion(device: &Device, count: u32) -> Result<Self> {
+        Self::new(device, vk::QueryType::OCCLUSION, count)
+    }
+
+    /// Access the raw `vk::QueryPool` handle.
+    pub fn raw(&self) -> vk::QueryPool {
+        self.pool
+    }
+
+    /// The type of queries in this pool.
+    pub fn query_type(&self) -> vk::QueryType {
+        self.query_type
+    }
+
+    /// The number of queries in this pool.
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+
+    /// Read back `query_count` 64-bit results starting at `first_query`,
+    /// blocking until they are available.
+    ///
+    /// For timestamp queries, convert tick deltas to nanoseconds with
+    /// [`Device::timestamp_period`]. Occlusion query results are non-zero
+    /// when any samples passed.
+    pub fn results(&self, first_query: u32, query_count: u32) -> Result<Vec<u64>> {
+        self.validate_range(first_query, query_count)?;
+
+        let mut results = vec![0u64; query_count as usize];
+        unsafe {
+            self.device
+                .get_query_pool_results(
+                    self.pool,
+                    first_query,
+                    &mut results,
+                    vk::QueryResultFlags::TYPE_64 | vk::QueryResultFlags::WAIT,
+                )
+                .map_err(|e| Error::from_vk("failed to get query results", e))?;
+        }
+        Ok(results)
+    }
+
+    /// Check that queries `first_query..first_query + query_count` exist.
+    pub(crate) fn validate_range(&self, first_query: u32, query_count: u32) -> Result<()> {
+        let end = first_query.checked_add(query_count);
+        if end.is_none_or(|end| end > self.count) {
+            return Err(Error::Validation(format!(
+                "queries {}..{} are out of range for a pool of {}",
+                first_query,
+                first_query as u64 + query_count as u64,
+                self.count
+            )));
+        }
+        Ok(())
+    }
+
+    /// Check that the pool holds queries of `query_type`.
+    pub(crate) fn validate_type(&self, query_type: vk::QueryType) -> Result<()> {
+        if self.query_type != query_type {
+            return Err(Error::Validation(format!(
+                "expected a {:?} query pool, got {:?}",
+                query_type, self.query_type
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl Drop for QueryPool {
+    fn drop(&mut self) {
+        unsafe {
+            self.device.destroy_query_pool(self.pool, None);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Color, CommandPool, GraphicsPipeline, GraphicsPipelineDesc, OffscreenTarget};
+
+    const VERTEX_SOURCE: &str = r#"
+[shader("vertex")]
+float4 main(uint vertex_id : SV_VertexID) : SV_POSITION
+{
+    float2 positions[3] = { float2(-1.0, -1.0), float2(3.0, -1.0), float2(-1.0, 3.0) };
+    return float4(positions[vertex_id], 0.0, 1.0);
+}
+"#;
+
+    const FRAGMENT_SOURCE: &str = r#"
+[shader("fragment")]
+float4 main() : SV_TARGET
+{
+    return float4(1.0, 1.0, 1.0, 1.0);
+}
+"#;
+
+    /// Needs a Vulkan device; skipped when no driver is available.
+    #[test]
+    fn test_timestamps_are_monotonic() {
+        let Some((instance, device, allocator)) = crate::test_util::headless_gpu() else {
+            return;
+        };
+        let queue_family_index = device.queue_family_indices().graphics;
+        let valid_bits = instance.queue_family_properties(device.physical_device())
+            [queue_family_index as usize]
+            .timestamp_valid_bits;
+        if valid_bits == 0 {
+            eprintln!("skipping: graphics queue does not support timestamps");
+            return;
+        }
+
+        let queries = QueryPool::new_timestamps(&device, 2).expect("query pool");
+        assert!(queries.results(1, 2).is_err());
+
+        let target = OffscreenTarget::new(&device, allocator, 16, 16, vk::Format::R8G8B8A8_UNORM)
+            .expect("offscreen target");
+        let (vertex_shader, fragment_shader) =
+            crate::test_util::compile_shaders(&device, VERTEX_SOURCE, FRAGMENT_SOURCE);
+        let extent = vk::Extent2D {
+            width: 16,
+            height: 16,
+        };
+        let pipeline = GraphicsPipeline::from_desc(
+            &device,
+            target.render_pass(),
+            &GraphicsPipelineDesc::new(&vertex_shader, &fragment_shader, extent),
+        )
+        .expect("pipeline");
+
+        let command_pool = CommandPool::new(&device, queue_family_index).expect("pool");
+        let mut command_buffer = command_pool
+            .allocate_command_buffer()
+            .expect("command buffer");
+        let clear_values = [Color::BLACK.to_clear_value()];
+        let begin_info = vk::RenderPassBeginInfo::default()
+            .render_pass(target.render_pass().raw())
+            .framebuffer(target.framebuffer().raw())
+            .render_area(vk::Rect2D {
+                offset: vk::Offset2D { x: 0, y: 0 },
+                extent,
+            })
+            .clear_values(&clear_values);
+        command_buffer
+            .begin(vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT)
+            .expect("begin");
+        assert!(command_buffer.reset_query_pool(&queries, 1, 2).is_err());
+        command_buffer
+            .reset_query_pool(&queries, 0, 2)
+            .expect("reset queries");
+        assert!(command_buffer
+            .write_timestamp(vk::PipelineStageFlags::TOP_OF_PIPE, &queries, 2)
+            .is_err());
+        let occlusion = QueryPool::new_occlusion(&device, 1).expect("occlusion pool");
+        assert!(command_buffer
+            .write_timestamp(vk::PipelineStageFlags::TOP_OF_PIPE, &occlusion, 0)
+            .is_err());
+        command_buffer
+            .write_timestamp(vk::PipelineStageFlags::TOP_OF_PIPE, &queries, 0)
+            .expect("first timestamp");
+        command_buffer.begin_render_pass(&begin_info, vk::SubpassContents::INLINE);
+        command_buffer.bind_pipeline(&pipeline);
+        command_buffer.draw(3, 1, 0, 0);
+        command_buffer.end_render_pass();
+        command_buffer
+            .write_timestamp(vk::PipelineStageFlags::BOTTOM_OF_PIPE, &queries, 1)
+            .expect("second timestamp");
+        command_buffer.end().expect("end");
+
+        crate::test_util::submit_and_wait(&device, &command_buffer);
+
+        // Bits above `timestamp_valid_bits` are undefined.
+        let mask = u64::MAX >> (64 - valid_bits);
+        let ticks = queries.results(0, 2).expect("results");
+        assert!(ticks[1] & mask >= ticks[0] & mask);
+        assert!(device.timestamp_period() > 0.0);
+    }
+}
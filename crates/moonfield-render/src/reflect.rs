@@ -0,0 +1,581 @@
+//! SPIR-V reflection.
+//!
+//! Recovers entry points, stage input/output locations and descriptor
+//! bindings from a SPIR-V module parsed with `rspirv`, so vertex input and
+//! descriptor set layouts can be derived from the shader instead of being
+//! written by hand. Only module-level declarations are inspected. Requires
+//! the `reflect` feature.
+
+use crate::error::{Error, Result};
+use crate::vertex::VertexLayout;
+use ash::vk;
+use rspirv::dr::{self, Instruction, Operand};
+use rspirv::spirv::{Decoration, Dim, ExecutionModel, Op, StorageClass, Word};
+use std::collections::HashMap;
+
+/// A shader entry point.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntryPoint {
+    pub name: String,
+    pub stage: vk::ShaderStageFlags,
+}
+
+/// A stage input or output variable with an explicit location.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InterfaceVariable {
+    /// Debug name, if the module was compiled with names.
+    pub name: Option<String>,
+    pub location: u32,
+    /// Matching vertex attribute format, or `None` for types without one
+    /// (matrices, structs, booleans).
+    pub format: Option<vk::Format>,
+}
+
+/// A resource bound through a descriptor set.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DescriptorBinding {
+    /// Debug name, if the module was compiled with names.
+    pub name: Option<String>,
+    pub set: u32,
+    pub binding: u32,
+    pub descriptor_type: vk::DescriptorType,
+    /// Array length; runtime-sized arrays report 0.
+    pub count: u32,
+}
+
+/// Interface of a SPIR-V module, as recovered by [`ShaderReflection::from_spirv`].
+///
+/// Variables are collected module-wide; for modules with several entry
+/// points they are not attributed to a particular one.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ShaderReflection {
+    pub entry_points: Vec<EntryPoint>,
+    /// Stage inputs sorted by location; built-ins are omitted.
+    pub inputs: Vec<InterfaceVariable>,
+    /// Stage outputs sorted by location; built-ins are omitted.
+    pub outputs: Vec<InterfaceVariable>,
+    /// Descriptor bindings sorted by set, then binding.
+    pub bindings: Vec<DescriptorBinding>,
+}
+
+/// Decorations of one id that reflection cares about.
+#[derive(Default)]
+struct Decorations {
+    location: Option<u32>,
+    binding: Option<u32>,
+    set: Option<u32>,
+    built_in: bool,
+    block: bool,
+    buffer_block: bool,
+}
+
+/// Lookup tables over a parsed module's declarations.
+struct Declarations<'a> {
+    names: HashMap<Word, &'a str>,
+    decorations: HashMap<Word, Decorations>,
+    /// Type and constant declarations by result id.
+    types: HashMap<Word, &'a Instruction>,
+}
+
+impl ShaderReflection {
+    /// Reflect a SPIR-V binary given as little-endian bytes.
+    pub fn from_spirv(bytecode: &[u8]) -> Result<Self> {
+        if !bytecode.len().is_multiple_of(4) {
+            return Err(Error::Validation(
+                "SPIR-V bytecode length must be a multiple of 4".to_string(),
+            ));
+        }
+        let words: Vec<u32> = bytecode
+            .chunks_exact(4)
+            .map(|chunk| u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+            .collect();
+        Self::from_words(&words)
+    }
+
+    /// Reflect a SPIR-V binary given as 32-bit words.
+    pub fn from_words(words: &[u32]) -> Result<Self> {
+        let module = dr::load_words(words)
+            .map_err(|e| Error::Validation(format!("failed to parse SPIR-V: {}", e)))?;
+        Ok(Self::from_module(&module))
+    }
+
+    fn from_module(module: &dr::Module) -> Self {
+        let declarations = Declarations::new(module);
+        let no_decorations = Decorations::default();
+        let mut reflection = Self {
+            entry_points: module.entry_points.iter().filter_map(entry_point).collect(),
+            ..Self::default()
+        };
+
+        let variables = module
+            .types_global_values
+            .iter()
+            .filter(|inst| inst.class.opcode == Op::Variable);
+        for variable in variables {
+            let (Some(id), Some(pointer_type), Some(Operand::StorageClass(storage_class))) = (
+                variable.result_id,
+                variable.result_type,
+                variable.operands.first(),
+            ) else {
+                continue;
+            };
+            let Some(pointee) = declarations.pointee(pointer_type) else {
+                continue;
+            };
+            let decoration = declarations.decorations.get(&id).unwrap_or(&no_decorations);
+            let name = declarations.names.get(&id).map(|name| name.to_string());
+
+            match storage_class {
+                StorageClass::Input | StorageClass::Output => {
+                    let Some(location) = decoration.location else {
+                        continue;
+                    };
+                    if decoration.built_in {
+                        continue;
+                    }
+                    let variable = InterfaceVariable {
+                        name,
+                        location,
+                        format: declarations.vertex_format(pointee),
+                    };
+                    if *storage_class == StorageClass::Input {
+                        reflection.inputs.push(variable);
+                    } else {
+                        reflection.outputs.push(variable);
+                    }
+                }
+                StorageClass::UniformConstant
+                | StorageClass::Uniform
+                | StorageClass::StorageBuffer => {
+                    let (Some(set), Some(binding)) = (decoration.set, decoration.binding) else {
+                        continue;
+                    };
+                    let (element, count) = declarations.array_element(pointee);
+                    let Some(descriptor_type) =
+                        declarations.descriptor_type(*storage_class, element)
+                    else {
+                        continue;
+                    };
+                    reflection.bindings.push(DescriptorBinding {
+                        name,
+                        set,
+                        binding,
+                        descriptor_type,
+                        count,
+                    });
+                }
+                _ => {}
+            }
+        }
+
+        reflection.inputs.sort_by_key(|variable| variable.location);
+        reflection.outputs.sort_by_key(|variable| variable.location);
+        reflection
+            .bindings
+            .sort_by_key(|binding| (binding.set, binding.binding));
+        reflection
+    }
+
+    /// Derive an interleaved vertex layout for `binding` from the stage
+    /// inputs.
+    ///
+    /// The inputs must occupy contiguous locations starting at 0 and all
+    /// have a vertex attribute format.
+    pub fn vertex_layout(&self, binding: u32) -> Result<VertexLayout> {
+        let formats = self
+            .inputs
+            .iter()
+            .enumerate()
+            .map(|(index, input)| {
+                if input.location != index as u32 {
+                    return Err(Error::Validation(format!(
+                        "vertex inputs are not contiguous: expected location {}, found {}",
+                        index, input.location
+                    )));
+                }
+                input.format.ok_or_else(|| {
+                    Error::Validation(format!(
+                        "vertex input at location {} has no attribute format",
+                        input.location
+                    ))
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        VertexLayout::new(binding, &formats)
+    }
+}
+
+impl<'a> Declarations<'a> {
+    fn new(module: &'a dr::Module) -> Self {
+        let mut names = HashMap::new();
+        for inst in &module.debug_names {
+            if let (Op::Name, [Operand::IdRef(target), Operand::LiteralString(name)]) =
+                (inst.class.opcode, inst.operands.as_slice())
+            {
+                names.insert(*target, name.as_str());
+            }
+        }
+
+        let mut decorations: HashMap<Word, Decorations> = HashMap::new();
+        for inst in &module.annotations {
+            let (
+                Op::Decorate,
+                [Operand::IdRef(target), Operand::Decoration(decoration), rest @ ..],
+            ) = (inst.class.opcode, inst.operands.as_slice())
+            else {
+                continue;
+            };
+            let entry = decorations.entry(*target).or_default();
+            let value = match rest.first() {
+                Some(Operand::LiteralInt32(value)) => Some(*value),
+                _ => None,
+            };
+            match decoration {
+                Decoration::Location => entry.location = value,
+                Decoration::Binding => entry.binding = value,
+                Decoration::DescriptorSet => entry.set = value,
+                Decoration::BuiltIn => entry.built_in = true,
+                Decoration::Block => entry.block = true,
+                Decoration::BufferBlock => entry.buffer_block = true,
+                _ => {}
+            }
+        }
+
+        let types = module
+            .types_global_values
+            .iter()
+            .filter(|inst| inst.class.opcode != Op::Variable)
+            .filter_map(|inst| Some((inst.result_id?, inst)))
+            .collect();
+
+        Self {
+            names,
+            decorations,
+            types,
+        }
+    }
+
+    fn get(&self, id: Word) -> Option<(Op, &'a [Operand])> {
+        self.types
+            .get(&id)
+            .map(|inst| (inst.class.opcode, inst.operands.as_slice()))
+    }
+
+    fn pointee(&self, pointer_type: Word) -> Option<Word> {
+        match self.get(pointer_type)? {
+            (Op::TypePointer, [_, Operand::IdRef(pointee)]) => Some(*pointee),
+            _ => None,
+        }
+    }
+
+    /// The element type and length of an array type, or `(type_id, 1)` for
+    /// other types. Runtime-sized arrays have a length of 0.
+    fn array_element(&self, type_id: Word) -> (Word, u32) {
+        match self.get(type_id) {
+            Some((Op::TypeArray, [Operand::IdRef(element), Operand::IdRef(length)])) => {
+                let length = match self.get(*length) {
+                    Some((Op::Constant, [Operand::LiteralInt32(length)])) => *length,
+                    _ => 1,
+                };
+                (*element, length)
+            }
+            Some((Op::TypeRuntimeArray, [Operand::IdRef(element)])) => (*element, 0),
+            _ => (type_id, 1),
+        }
+    }
+
+    fn vertex_format(&self, type_id: Word) -> Option<vk::Format> {
+        let (scalar, count) = match self.get(type_id)? {
+            (Op::TypeVector, [Operand::IdRef(component), Operand::LiteralInt32(count)]) => {
+                (self.get(*component)?, *count)
+            }
+            scalar => (scalar, 1),
+        };
+        let formats: [vk::Format; 4] = match scalar {
+            (Op::TypeFloat, [Operand::LiteralInt32(32)]) => [
+                vk::Format::R32_SFLOAT,
+                vk::Format::R32G32_SFLOAT,
+                vk::Format::R32G32B32_SFLOAT,
+                vk::Format::R32G32B32A32_SFLOAT,
+            ],
+            (Op::TypeFloat, [Operand::LiteralInt32(16)]) => [
+                vk::Format::R16_SFLOAT,
+                vk::Format::R16G16_SFLOAT,
+                vk::Format::R16G16B16_SFLOAT,
+                vk::Format::R16G16B16A16_SFLOAT,
+            ],
+            (Op::TypeInt, [Operand::LiteralInt32(32), Operand::LiteralInt32(signedness)]) => {
+                if *signedness != 0 {
+                    [
+                        vk::Format::R32_SINT,
+                        vk::Format::R32G32_SINT,
+                        vk::Format::R32G32B32_SINT,
+                        vk::Format::R32G32B32A32_SINT,
+                    ]
+                } else {
+                    [
+                        vk::Format::R32_UINT,
+                        vk::Format::R32G32_UINT,
+                        vk::Format::R32G32B32_UINT,
+                        vk::Format::R32G32B32A32_UINT,
+                    ]
+                }
+            }
+            _ => return None,
+        };
+        formats.get(count.checked_sub(1)? as usize).copied()
+    }
+
+    fn descriptor_type(
+        &self,
+        storage_class: StorageClass,
+        element: Word,
+    ) -> Option<vk::DescriptorType> {
+        let decoration = self.decorations.get(&element);
+        let has = |flag: fn(&Decorations) -> bool| decoration.is_some_and(flag);
+        let descriptor_type = match (storage_class, self.get(element)?) {
+            (StorageClass::StorageBuffer, (Op::TypeStruct, _)) => {
+                vk::DescriptorType::STORAGE_BUFFER
+            }
+            (StorageClass::Uniform, (Op::TypeStruct, _)) if has(|d| d.buffer_block) => {
+                vk::DescriptorType::STORAGE_BUFFER
+            }
+            (StorageClass::Uniform, (Op::TypeStruct, _)) if has(|d| d.block) => {
+                vk::DescriptorType::UNIFORM_BUFFER
+            }
+            (StorageClass::UniformConstant, (Op::TypeSampler, _)) => vk::DescriptorType::SAMPLER,
+            (StorageClass::UniformConstant, (Op::TypeSampledImage, _)) => {
+                vk::DescriptorType::COMBINED_IMAGE_SAMPLER
+            }
+            (StorageClass::UniformConstant, (Op::TypeImage, operands)) => {
+                let (Some(Operand::Dim(dim)), Some(Operand::LiteralInt32(sampled))) =
+                    (operands.get(1), operands.get(5))
+                else {
+                    return None;
+                };
+                match (dim, sampled) {
+                    (Dim::DimSubpassData, _) => vk::DescriptorType::INPUT_ATTACHMENT,
+                    (Dim::DimBuffer, 2) => vk::DescriptorType::STORAGE_TEXEL_BUFFER,
+                    (Dim::DimBuffer, _) => vk::DescriptorType::UNIFORM_TEXEL_BUFFER,
+                    (_, 2) => vk::DescriptorType::STORAGE_IMAGE,
+                    _ => vk::DescriptorType::SAMPLED_IMAGE,
+                }
+            }
+            _ => return None,
+        };
+        Some(descriptor_type)
+    }
+}
+
+fn entry_point(inst: &Instruction) -> Option<EntryPoint> {
+    let [Operand::ExecutionModel(model), _, Operand::LiteralString(name), ..] =
+        inst.operands.as_slice()
+    else {
+        return None;
+    };
+    let stage = match model {
+        ExecutionModel::Vertex => vk::ShaderStageFlags::VERTEX,
+        ExecutionModel::TessellationControl => vk::ShaderStageFlags::TESSELLATION_CONTROL,
+        ExecutionModel::TessellationEvaluation => vk::ShaderStageFlags::TESSELLATION_EVALUATION,
+        ExecutionModel::Geometry => vk::ShaderStageFlags::GEOMETRY,
+        ExecutionModel::Fragment => vk::ShaderStageFlags::FRAGMENT,
+        ExecutionModel::GLCompute => vk::ShaderStageFlags::COMPUTE,
+        _ => return None,
+    };
+    Some(EntryPoint {
+        name: name.clone(),
+        stage,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shader::Compiler;
+    use rspirv::binary::Assemble;
+    use rspirv::spirv::{ExecutionModel, ImageFormat};
+
+    const VERTEX_SOURCE: &str = r#"
+struct Globals
+{
+    float4x4 transform;
+};
+
+[[vk::binding(0, 0)]] ConstantBuffer<Globals> globals;
+[[vk::binding(1, 0)]] Texture2D tint_texture;
+[[vk::binding(2, 0)]] SamplerState tint_sampler;
+
+struct VsOutput
+{
+    float4 position : SV_POSITION;
+    float3 color : COLOR;
+};
+
+[shader("vertex")]
+VsOutput main(float2 position : POSITION, float3 color : COLOR)
+{
+    VsOutput output;
+    output.position = mul(globals.transform, float4(position, 0.0, 1.0));
+    output.color = color * tint_texture.SampleLevel(tint_sampler, position, 0.0).rgb;
+    return output;
+}
+"#;
+
+    /// Needs the Slang compiler; skipped when it is not available.
+    #[test]
+    fn test_reflects_compiled_vertex_shader() {
+        let compiler = match Compiler::new() {
+            Ok(compiler) => compiler,
+            Err(err) => {
+                eprintln!("skipping: Slang compiler not available ({err})");
+                return;
+            }
+        };
+        let spirv = compiler
+            .compile_source_to_spirv("reflect_vs", VERTEX_SOURCE, "main")
+            .expect("vertex shader compilation");
+        let reflection = ShaderReflection::from_spirv(&spirv).unwrap();
+
+        assert_eq!(reflection.entry_points.len(), 1);
+        assert_eq!(
+            reflection.entry_points[0].stage,
+            vk::ShaderStageFlags::VERTEX
+        );
+        let inputs: Vec<_> = reflection
+            .inputs
+            .iter()
+            .map(|input| (input.location, input.format))
+            .collect();
+        assert_eq!(
+            inputs,
+            [
+                (0, Some(vk::Format::R32G32_SFLOAT)),
+                (1, Some(vk::Format::R32G32B32_SFLOAT)),
+            ]
+        );
+        // The built-in position output is omitted.
+        assert_eq!(reflection.outputs.len(), 1);
+        assert_eq!(
+            reflection.outputs[0].format,
+            Some(vk::Format::R32G32B32_SFLOAT)
+        );
+
+        let bindings: Vec<_> = reflection
+            .bindings
+            .iter()
+            .map(|b| (b.set, b.binding, b.descriptor_type, b.count))
+            .collect();
+        assert_eq!(
+            bindings,
+            [
+                (0, 0, vk::DescriptorType::UNIFORM_BUFFER, 1),
+                (0, 1, vk::DescriptorType::SAMPLED_IMAGE, 1),
+                (0, 2, vk::DescriptorType::SAMPLER, 1),
+            ]
+        );
+
+        let layout = reflection.vertex_layout(0).unwrap();
+        let offsets: Vec<u32> = layout.attributes().iter().map(|a| a.offset).collect();
+        assert_eq!(offsets, [0, 8]);
+        assert_eq!(layout.stride(), 20);
+    }
+
+    /// A fragment shader module with a `half3` input at location 0, a
+    /// subpass input at set 0 binding 0 and an array of 4 storage images at
+    /// set 0 binding 1, none of which the Slang test above covers.
+    fn half_and_subpass_module() -> Vec<u32> {
+        let mut b = dr::Builder::new();
+        let half = b.type_float(16);
+        let half3 = b.type_vector(half, 3);
+        let float = b.type_float(32);
+        let uint = b.type_int(32, 0);
+        let four = b.constant_u32(uint, 4);
+        let subpass = b.type_image(
+            float,
+            Dim::DimSubpassData,
+            0,
+            0,
+            0,
+            2,
+            ImageFormat::Unknown,
+            None,
+        );
+        let storage = b.type_image(float, Dim::Dim2D, 0, 0, 0, 2, ImageFormat::Rgba8, None);
+        let storage_array = b.type_array(storage, four);
+
+        let input_pointer = b.type_pointer(None, StorageClass::Input, half3);
+        let subpass_pointer = b.type_pointer(None, StorageClass::UniformConstant, subpass);
+        let storage_pointer = b.type_pointer(None, StorageClass::UniformConstant, storage_array);
+        let color = b.variable(input_pointer, None, StorageClass::Input, None);
+        let previous = b.variable(subpass_pointer, None, StorageClass::UniformConstant, None);
+        let images = b.variable(storage_pointer, None, StorageClass::UniformConstant, None);
+
+        b.name(color, "color");
+        b.decorate(color, Decoration::Location, [Operand::LiteralInt32(0)]);
+        for (variable, binding) in [(previous, 0), (images, 1)] {
+            b.decorate(
+                variable,
+                Decoration::DescriptorSet,
+                [Operand::LiteralInt32(0)],
+            );
+            b.decorate(
+                variable,
+                Decoration::Binding,
+                [Operand::LiteralInt32(binding)],
+            );
+        }
+        let function = b.id();
+        b.entry_point(
+            ExecutionModel::Fragment,
+            function,
+            "main",
+            [color, previous],
+        );
+        b.module().assemble()
+    }
+
+    #[test]
+    fn test_reflects_half_inputs_and_input_attachments() {
+        let reflection = ShaderReflection::from_words(&half_and_subpass_module()).unwrap();
+        assert_eq!(
+            reflection.entry_points,
+            [EntryPoint {
+                name: "main".to_string(),
+                stage: vk::ShaderStageFlags::FRAGMENT,
+            }]
+        );
+        assert_eq!(
+            reflection.inputs,
+            [InterfaceVariable {
+                name: Some("color".to_string()),
+                location: 0,
+                format: Some(vk::Format::R16G16B16_SFLOAT),
+            }]
+        );
+        assert_eq!(reflection.vertex_layout(0).unwrap().stride(), 6);
+
+        let bindings: Vec<_> = reflection
+            .bindings
+            .iter()
+            .map(|b| (b.binding, b.descriptor_type, b.count))
+            .collect();
+        assert_eq!(
+            bindings,
+            [
+                (0, vk::DescriptorType::INPUT_ATTACHMENT, 1),
+                (1, vk::DescriptorType::STORAGE_IMAGE, 4),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_rejects_non_spirv() {
+        assert!(ShaderReflection::from_spirv(&[0, 1, 2]).is_err());
+        assert!(ShaderReflection::from_words(&[0xdead_beef, 0, 0, 0, 0]).is_err());
+        // An instruction claiming more words than remain.
+        assert!(
+            ShaderReflection::from_words(&[0x0723_0203, 0x0001_0000, 0, 1, 0, 0x0005_0005])
+                .is_err()
+        );
+    }
+}
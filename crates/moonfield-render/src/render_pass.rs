@@ -2,11 +2,97 @@
 
 use crate::device::Device;
 use crate::error::{Error, Result};
+use crate::format;
 use ash::vk;
 
-/// A Vulkan render pass with a single color attachment.
+/// Description of one render pass attachment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Attachment {
+    /// Image format of the attachment.
+    pub format: vk::Format,
+    /// What happens to the attachment contents when the pass begins.
+    pub load_op: vk::AttachmentLoadOp,
+    /// Whether the attachment contents are kept when the pass ends.
+    pub store_op: vk::AttachmentStoreOp,
+    /// Layout the attachment is transitioned to when the pass ends.
+    pub final_layout: vk::ImageLayout,
+    /// Samples per pixel; more than one makes a multisampled attachment.
+    pub samples: vk::SampleCountFlags,
+}
+
+impl Attachment {
+    /// A color attachment that is cleared on load and stored, ending in
+    /// `final_layout`.
+    pub fn color(format: vk::Format, final_layout: vk::ImageLayout) -> Self {
+        Self {
+            format,
+            load_op: vk::AttachmentLoadOp::CLEAR,
+            store_op: vk::AttachmentStoreOp::STORE,
+            final_layout,
+            samples: vk::SampleCountFlags::TYPE_1,
+        }
+    }
+
+    /// A depth attachment that is cleared on load and discarded after the
+    /// pass, as is typical for a depth buffer only used for testing.
+    ///
+    /// The stencil aspect of a depth/stencil format uses the same load and
+    /// store operations.
+    pub fn depth(format: vk::Format) -> Self {
+        Self {
+            format,
+            load_op: vk::AttachmentLoadOp::CLEAR,
+            store_op: vk::AttachmentStoreOp::DONT_CARE,
+            final_layout: vk::ImageLayout::DEPTH_STENCIL_ATTACHMENT_OPTIMAL,
+            samples: vk::SampleCountFlags::TYPE_1,
+        }
+    }
+
+    /// A depth attachment that is cleared on load and stored so it can be
+    /// sampled after the pass, e.g. a shadow map, ending in
+    /// `SHADER_READ_ONLY_OPTIMAL`.
+    pub fn sampled_depth(format: vk::Format) -> Self {
+        Self {
+            store_op: vk::AttachmentStoreOp::STORE,
+            final_layout: vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+            ..Self::depth(format)
+        }
+    }
+
+    /// A multisampled color attachment that is cleared on load and whose
+    /// samples are discarded after the pass, as is typical when the pass
+    /// resolves it into a single-sampled image.
+    pub fn multisampled_color(format: vk::Format, samples: vk::SampleCountFlags) -> Self {
+        Self {
+            format,
+            load_op: vk::AttachmentLoadOp::CLEAR,
+            store_op: vk::AttachmentStoreOp::DONT_CARE,
+            final_layout: vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL,
+            samples,
+        }
+    }
+
+    /// A single-sampled attachment receiving the resolved samples of a
+    /// multisampled color attachment, ending in `final_layout`.
+    pub fn resolve(format: vk::Format, final_layout: vk::ImageLayout) -> Self {
+        Self {
+            format,
+            load_op: vk::AttachmentLoadOp::DONT_CARE,
+            store_op: vk::AttachmentStoreOp::STORE,
+            final_layout,
+            samples: vk::SampleCountFlags::TYPE_1,
+        }
+    }
+}
+
+/// A Vulkan render pass with a single subpass writing any number of color
+/// attachments, optionally resolved, and an optional depth attachment.
 pub struct RenderPass {
     render_pass: vk::RenderPass,
+    color_attachment_count: u32,
+    has_depth: bool,
+    has_stencil: bool,
+    samples: vk::SampleCountFlags,
     device: ash::Device,
 }
 
@@ -27,39 +113,138 @@ impl RenderPass {
         color_format: vk::Format,
         final_layout: vk::ImageLayout,
     ) -> Result<Self> {
-        let color_attachment = vk::AttachmentDescription::default()
-            .format(color_format)
-            .samples(vk::SampleCountFlags::TYPE_1)
-            .load_op(vk::AttachmentLoadOp::CLEAR)
-            .store_op(vk::AttachmentStoreOp::STORE)
-            .stencil_load_op(vk::AttachmentLoadOp::DONT_CARE)
-            .stencil_store_op(vk::AttachmentStoreOp::DONT_CARE)
-            .initial_layout(vk::ImageLayout::UNDEFINED)
-            .final_layout(final_layout);
-
-        let color_attachment_ref = vk::AttachmentReference::default()
-            .attachment(0)
-            .layout(vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL);
-
-        let subpass = vk::SubpassDescription::default()
+        Self::new_with_attachments(
+            device,
+            &[Attachment::color(color_format, final_layout)],
+            None,
+        )
+    }
+
+    /// Create a render pass with the given color attachments, bound to
+    /// fragment outputs in order, and an optional depth attachment placed
+    /// after them.
+    ///
+    /// Color formats must not be depth formats and the depth format must
+    /// be one; framebuffers must supply views in the same order.
+    pub fn new_with_attachments(
+        device: &Device,
+        colors: &[Attachment],
+        depth: Option<Attachment>,
+    ) -> Result<Self> {
+        Self::new_with_resolve(device, colors, &[], depth)
+    }
+
+    /// Create a render pass whose multisampled color attachments are
+    /// resolved into `resolves` when the pass ends.
+    ///
+    /// `resolves` is either empty or holds one single-sampled attachment of
+    /// the same format per color attachment. Attachments are ordered colors,
+    /// then resolves, then depth; framebuffers must supply views in the same
+    /// order. All color and depth attachments share one sample count, which
+    /// the device must support.
+    pub fn new_with_resolve(
+        device: &Device,
+        colors: &[Attachment],
+        resolves: &[Attachment],
+        depth: Option<Attachment>,
+    ) -> Result<Self> {
+        validate_attachments(colors, depth.as_ref())?;
+        let samples = validate_samples(colors, resolves, depth.as_ref())?;
+        let limits = &device.properties().limits;
+        let supported = if colors.is_empty() {
+            limits.framebuffer_depth_sample_counts
+        } else if depth.is_some() {
+            limits.framebuffer_color_sample_counts & limits.framebuffer_depth_sample_counts
+        } else {
+            limits.framebuffer_color_sample_counts
+        };
+        if !supported.contains(samples) {
+            return Err(Error::Validation(format!(
+                "device does not support {:?} samples per pixel",
+                samples
+            )));
+        }
+
+        let mut attachments: Vec<vk::AttachmentDescription> = colors
+            .iter()
+            .chain(resolves)
+            .map(|color| {
+                describe_attachment(color)
+                    .stencil_load_op(vk::AttachmentLoadOp::DONT_CARE)
+                    .stencil_store_op(vk::AttachmentStoreOp::DONT_CARE)
+            })
+            .collect();
+        if let Some(depth) = &depth {
+            let (stencil_load_op, stencil_store_op) = if format::has_stencil(depth.format) {
+                (depth.load_op, depth.store_op)
+            } else {
+                (
+                    vk::AttachmentLoadOp::DONT_CARE,
+                    vk::AttachmentStoreOp::DONT_CARE,
+                )
+            };
+            attachments.push(
+                describe_attachment(depth)
+                    .stencil_load_op(stencil_load_op)
+                    .stencil_store_op(stencil_store_op),
+            );
+        }
+
+        let color_reference = |index: usize| {
+            vk::AttachmentReference::default()
+                .attachment(index as u32)
+                .layout(vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL)
+        };
+        let color_attachment_refs: Vec<vk::AttachmentReference> =
+            (0..colors.len()).map(color_reference).collect();
+        let resolve_attachment_refs: Vec<vk::AttachmentReference> = (colors.len()
+            ..colors.len() + resolves.len())
+            .map(color_reference)
+            .collect();
+        let depth_attachment_ref = vk::AttachmentReference::default()
+            .attachment((colors.len() + resolves.len()) as u32)
+            .layout(vk::ImageLayout::DEPTH_STENCIL_ATTACHMENT_OPTIMAL);
+
+        let mut subpass = vk::SubpassDescription::default()
             .pipeline_bind_point(vk::PipelineBindPoint::GRAPHICS)
-            .color_attachments(std::slice::from_ref(&color_attachment_ref));
+            .color_attachments(&color_attachment_refs);
+        if !resolves.is_empty() {
+            subpass = subpass.resolve_attachments(&resolve_attachment_refs);
+        }
+        if depth.is_some() {
+            subpass = subpass.depth_stencil_attachment(&depth_attachment_ref);
+        }
 
+        // Order this pass's attachment writes after earlier ones. Depth is
+        // written in both fragment test stages, so a previous pass's depth
+        // writes must be made available before this pass clears or writes.
+        let mut stage_mask = vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT;
+        let mut src_access_mask = vk::AccessFlags::empty();
+        let mut dst_access_mask = vk::AccessFlags::COLOR_ATTACHMENT_WRITE;
+        if depth.is_some() {
+            stage_mask |= vk::PipelineStageFlags::EARLY_FRAGMENT_TESTS
+                | vk::PipelineStageFlags::LATE_FRAGMENT_TESTS;
+            src_access_mask |= vk::AccessFlags::DEPTH_STENCIL_ATTACHMENT_WRITE;
+            dst_access_mask |= vk::AccessFlags::DEPTH_STENCIL_ATTACHMENT_WRITE;
+        }
         let dependency = vk::SubpassDependency::default()
             .src_subpass(vk::SUBPASS_EXTERNAL)
             .dst_subpass(0)
-            .src_stage_mask(vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT)
-            .dst_stage_mask(vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT)
-            .src_access_mask(vk::AccessFlags::empty())
-            .dst_access_mask(vk::AccessFlags::COLOR_ATTACHMENT_WRITE);
+            .src_stage_mask(stage_mask)
+            .dst_stage_mask(stage_mask)
+            .src_access_mask(src_access_mask)
+            .dst_access_mask(dst_access_mask);
 
-        let attachments = [color_attachment];
         let subpasses = [subpass];
-        // When the attachment is sampled after the pass (offscreen targets),
-        // add an external dependency so the layout transition to
+        // When a color attachment is sampled after the pass (offscreen
+        // targets), add an external dependency so the layout transition to
         // SHADER_READ_ONLY_OPTIMAL is synchronized with fragment shader reads.
         let mut dependencies = vec![dependency];
-        if final_layout == vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL {
+        if colors
+            .iter()
+            .chain(resolves)
+            .any(|color| color.final_layout == vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL)
+        {
             dependencies.push(
                 vk::SubpassDependency::default()
                     .src_subpass(0)
@@ -70,6 +255,21 @@ impl RenderPass {
                     .dst_access_mask(vk::AccessFlags::SHADER_READ),
             );
         }
+        // Likewise for a depth attachment sampled afterwards (shadow maps).
+        if depth
+            .as_ref()
+            .is_some_and(|depth| depth.final_layout == vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL)
+        {
+            dependencies.push(
+                vk::SubpassDependency::default()
+                    .src_subpass(0)
+                    .dst_subpass(vk::SUBPASS_EXTERNAL)
+                    .src_stage_mask(vk::PipelineStageFlags::LATE_FRAGMENT_TESTS)
+                    .dst_stage_mask(vk::PipelineStageFlags::FRAGMENT_SHADER)
+                    .src_access_mask(vk::AccessFlags::DEPTH_STENCIL_ATTACHMENT_WRITE)
+                    .dst_access_mask(vk::AccessFlags::SHADER_READ),
+            );
+        }
 
         let create_info = vk::RenderPassCreateInfo::default()
             .attachments(&attachments)
@@ -85,10 +285,35 @@ impl RenderPass {
 
         Ok(Self {
             render_pass,
+            color_attachment_count: colors.len() as u32,
+            has_depth: depth.is_some(),
+            has_stencil: depth.is_some_and(|depth| format::has_stencil(depth.format)),
+            samples,
             device: device.raw().clone(),
         })
     }
 
+    /// Number of color attachments written by the subpass.
+    pub fn color_attachment_count(&self) -> u32 {
+        self.color_attachment_count
+    }
+
+    /// Whether the render pass has a depth attachment.
+    pub fn has_depth(&self) -> bool {
+        self.has_depth
+    }
+
+    /// Whether the render pass's depth attachment has a stencil aspect.
+    pub fn has_stencil(&self) -> bool {
+        self.has_stencil
+    }
+
+    /// Samples per pixel of the color and depth attachments; pipelines used
+    /// with this pass must rasterize with the same count.
+    pub fn samples(&self) -> vk::SampleCountFlags {
+        self.samples
+    }
+
     /// Access the raw `vk::RenderPass` handle.
     pub fn raw(&self) -> vk::RenderPass {
         self.render_pass
@@ -102,3 +327,165 @@ impl Drop for RenderPass {
         }
     }
 }
+
+fn describe_attachment(attachment: &Attachment) -> vk::AttachmentDescription {
+    vk::AttachmentDescription::default()
+        .format(attachment.format)
+        .samples(attachment.samples)
+        .load_op(attachment.load_op)
+        .store_op(attachment.store_op)
+        .initial_layout(vk::ImageLayout::UNDEFINED)
+        .final_layout(attachment.final_layout)
+}
+
+fn validate_attachments(colors: &[Attachment], depth: Option<&Attachment>) -> Result<()> {
+    if colors.is_empty() && depth.is_none() {
+        return Err(Error::Validation(
+            "render pass must have at least one attachment".to_string(),
+        ));
+    }
+    if let Some(color) = colors
+        .iter()
+        .find(|color| format::is_depth_format(color.format))
+    {
+        return Err(Error::Validation(format!(
+            "color attachment uses depth format {:?}",
+            color.format
+        )));
+    }
+    if let Some(depth) = depth {
+        if !format::is_depth_format(depth.format) {
+            return Err(Error::Validation(format!(
+                "depth attachment uses non-depth format {:?}",
+                depth.format
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Check the sample counts of a pass and return the shared count of its
+/// color and depth attachments.
+fn validate_samples(
+    colors: &[Attachment],
+    resolves: &[Attachment],
+    depth: Option<&Attachment>,
+) -> Result<vk::SampleCountFlags> {
+    let mut rendered = colors.iter().chain(depth);
+    let samples = rendered
+        .next()
+        .map_or(vk::SampleCountFlags::TYPE_1, |attachment| {
+            attachment.samples
+        });
+    if samples.as_raw().count_ones() != 1 {
+        return Err(Error::Validation(format!(
+            "attachment sample count must be a single value, got {:?}",
+            samples
+        )));
+    }
+    if let Some(other) = rendered.find(|attachment| attachment.samples != samples) {
+        return Err(Error::Validation(format!(
+            "attachments mix {:?} and {:?} samples",
+            samples, other.samples
+        )));
+    }
+
+    if resolves.is_empty() {
+        return Ok(samples);
+    }
+    if resolves.len() != colors.len() {
+        return Err(Error::Validation(format!(
+            "{} resolve attachments for {} color attachments",
+            resolves.len(),
+            colors.len()
+        )));
+    }
+    if samples == vk::SampleCountFlags::TYPE_1 {
+        return Err(Error::Validation(
+            "resolving requires multisampled color attachments".to_string(),
+        ));
+    }
+    for (color, resolve) in colors.iter().zip(resolves) {
+        if resolve.samples != vk::SampleCountFlags::TYPE_1 {
+            return Err(Error::Validation(
+                "resolve attachments must be single-sampled".to_string(),
+            ));
+        }
+        if resolve.format != color.format {
+            return Err(Error::Validation(format!(
+                "cannot resolve a {:?} attachment into {:?}",
+                color.format, resolve.format
+            )));
+        }
+    }
+    Ok(samples)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_attachments_checks_formats() {
+        let color = Attachment::color(
+            vk::Format::R8G8B8A8_UNORM,
+            vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+        );
+        let depth = Attachment::depth(vk::Format::D32_SFLOAT);
+
+        assert!(validate_attachments(&[color, color], Some(&depth)).is_ok());
+        assert!(validate_attachments(&[], None).is_err());
+        assert!(validate_attachments(&[depth], None).is_err());
+        assert!(validate_attachments(&[color], Some(&color)).is_err());
+    }
+
+    #[test]
+    fn test_validate_samples_checks_resolve_targets() {
+        let format = vk::Format::R8G8B8A8_UNORM;
+        let msaa = Attachment::multisampled_color(format, vk::SampleCountFlags::TYPE_4);
+        let resolve = Attachment::resolve(format, vk::ImageLayout::TRANSFER_SRC_OPTIMAL);
+        let depth = Attachment {
+            samples: vk::SampleCountFlags::TYPE_4,
+            ..Attachment::depth(vk::Format::D32_SFLOAT)
+        };
+
+        assert_eq!(
+            validate_samples(&[msaa], &[resolve], Some(&depth)),
+            Ok(vk::SampleCountFlags::TYPE_4)
+        );
+        assert_eq!(
+            validate_samples(&[resolve], &[], None),
+            Ok(vk::SampleCountFlags::TYPE_1)
+        );
+        // Mixed sample counts.
+        assert!(
+            validate_samples(&[msaa], &[resolve], Some(&Attachment::depth(depth.format))).is_err()
+        );
+        // Resolving needs multisampled sources and matching targets.
+        assert!(validate_samples(&[resolve], &[resolve], None).is_err());
+        assert!(validate_samples(&[msaa, msaa], &[resolve], None).is_err());
+        assert!(validate_samples(&[msaa], &[msaa], None).is_err());
+        let wrong_format = Attachment::resolve(vk::Format::B8G8R8A8_UNORM, resolve.final_layout);
+        assert!(validate_samples(&[msaa], &[wrong_format], None).is_err());
+    }
+
+    /// Needs a Vulkan device; skipped when no driver is available.
+    #[test]
+    fn test_color_and_depth_render_pass() {
+        let Some((_instance, device, _allocator)) = crate::test_util::headless_gpu() else {
+            return;
+        };
+
+        let render_pass = RenderPass::new_with_attachments(
+            &device,
+            &[Attachment::color(
+                vk::Format::R8G8B8A8_UNORM,
+                vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+            )],
+            Some(Attachment::depth(vk::Format::D32_SFLOAT)),
+        )
+        .expect("render pass");
+        assert_eq!(render_pass.color_attachment_count(), 1);
+        assert!(render_pass.has_depth());
+    }
+}
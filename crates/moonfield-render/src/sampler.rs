@@ -0,0 +1,222 @@
+//! Vulkan sampler abstraction.
+
+use crate::device::Device;
+use crate::error::{Error, Result};
+use ash::vk;
+
+/// Description of a [`Sampler`].
+///
+/// The default is a trilinear sampler repeating on every axis with no LOD
+/// clamp, anisotropy or depth comparison.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SamplerDesc {
+    /// Address modes for the U, V and W coordinates.
+    pub address_modes: [vk::SamplerAddressMode; 3],
+    pub mag_filter: vk::Filter,
+    pub min_filter: vk::Filter,
+    pub mipmap_mode: vk::SamplerMipmapMode,
+    pub lod_min_clamp: f32,
+    pub lod_max_clamp: f32,
+    /// Maximum anisotropy; `None` disables anisotropic filtering. Requires
+    /// the `sampler_anisotropy` device feature.
+    pub max_anisotropy: Option<f32>,
+    /// Depth comparison for shadow sampling; `None` samples raw values.
+    pub compare_op: Option<vk::CompareOp>,
+    /// Color returned outside the image for `CLAMP_TO_BORDER` addressing.
+    /// The `*_CUSTOM_EXT` colors are unsupported.
+    pub border_color: vk::BorderColor,
+}
+
+impl Default for SamplerDesc {
+    fn default() -> Self {
+        Self {
+            address_modes: [vk::SamplerAddressMode::REPEAT; 3],
+            mag_filter: vk::Filter::LINEAR,
+            min_filter: vk::Filter::LINEAR,
+            mipmap_mode: vk::SamplerMipmapMode::LINEAR,
+            lod_min_clamp: 0.0,
+            lod_max_clamp: vk::LOD_CLAMP_NONE,
+            max_anisotropy: None,
+            compare_op: None,
+            border_color: vk::BorderColor::FLOAT_TRANSPARENT_BLACK,
+        }
+    }
+}
+
+impl SamplerDesc {
+    fn validate(
+        &self,
+        features: &vk::PhysicalDeviceFeatures,
+        limits: &vk::PhysicalDeviceLimits,
+    ) -> Result<()> {
+        if self
+            .lod_min_clamp
+            .partial_cmp(&self.lod_max_clamp)
+            .is_none_or(|order| order.is_gt())
+        {
+            return Err(Error::Validation(format!(
+                "sampler LOD clamp {}..{} is empty",
+                self.lod_min_clamp, self.lod_max_clamp
+            )));
+        }
+        if let Some(anisotropy) = self.max_anisotropy {
+            if features.sampler_anisotropy == vk::FALSE {
+                return Err(Error::Validation(
+                    "anisotropic filtering requires the sampler_anisotropy feature".to_string(),
+                ));
+            }
+            if !(1.0..=limits.max_sampler_anisotropy).contains(&anisotropy) {
+                return Err(Error::Validation(format!(
+                    "max anisotropy {} is outside 1..={}",
+                    anisotropy, limits.max_sampler_anisotropy
+                )));
+            }
+        }
+        if matches!(
+            self.border_color,
+            vk::BorderColor::FLOAT_CUSTOM_EXT | vk::BorderColor::INT_CUSTOM_EXT
+        ) {
+            // VK_EXT_custom_border_color is never enabled on the device, and
+            // a custom border color is invalid without its create info
+            // whatever the address modes.
+            return Err(Error::Unsupported);
+        }
+        Ok(())
+    }
+}
+
+/// A Vulkan sampler.
+pub struct Sampler {
+    sampler: vk::Sampler,
+    device: ash::Device,
+}
+
+impl Sampler {
+    /// Create a sampler from `desc`, validating it against the device's
+    /// enabled features and limits.
+    pub fn new(device: &Device, desc: &SamplerDesc) -> Result<Self> {
+        desc.validate(device.enabled_features(), &device.properties().limits)?;
+
+        let create_info = vk::SamplerCreateInfo::default()
+            .mag_filter(desc.mag_filter)
+            .min_filter(desc.min_filter)
+            .mipmap_mode(desc.mipmap_mode)
+            .address_mode_u(desc.address_modes[0])
+            .address_mode_v(desc.address_modes[1])
+            .address_mode_w(desc.address_modes[2])
+            .min_lod(desc.lod_min_clamp)
+            .max_lod(desc.lod_max_clamp)
+            .anisotropy_enable(desc.max_anisotropy.is_some())
+            .max_anisotropy(desc.max_anisotropy.unwrap_or(1.0))
+            .compare_enable(desc.compare_op.is_some())
+            .compare_op(desc.compare_op.unwrap_or(vk::CompareOp::ALWAYS))
+            .border_color(desc.border_color);
+        let sampler = unsafe {
+            device
+                .raw()
+                .create_sampler(&create_info, None)
+                .map_err(|e| Error::Backend(format!("failed to create sampler: {:?}", e)))?
+        };
+
+        Ok(Self {
+            sampler,
+            device: device.raw().clone(),
+        })
+    }
+
+    /// Access the raw `vk::Sampler` handle.
+    pub fn raw(&self) -> vk::Sampler {
+        self.sampler
+    }
+}
+
+impl Drop for Sampler {
+    fn drop(&mut self) {
+        unsafe {
+            self.device.destroy_sampler(self.sampler, None);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limits() -> vk::PhysicalDeviceLimits {
+        vk::PhysicalDeviceLimits {
+            max_sampler_anisotropy: 16.0,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_sampler_desc_validation() {
+        let no_features = vk::PhysicalDeviceFeatures::default();
+        let anisotropy = vk::PhysicalDeviceFeatures {
+            sampler_anisotropy: vk::TRUE,
+            ..Default::default()
+        };
+
+        assert!(SamplerDesc::default()
+            .validate(&no_features, &limits())
+            .is_ok());
+
+        let empty_lod = SamplerDesc {
+            lod_min_clamp: 4.0,
+            lod_max_clamp: 1.0,
+            ..SamplerDesc::default()
+        };
+        assert!(empty_lod.validate(&no_features, &limits()).is_err());
+
+        let anisotropic = SamplerDesc {
+            max_anisotropy: Some(8.0),
+            ..SamplerDesc::default()
+        };
+        assert!(anisotropic.validate(&no_features, &limits()).is_err());
+        assert!(anisotropic.validate(&anisotropy, &limits()).is_ok());
+        let too_anisotropic = SamplerDesc {
+            max_anisotropy: Some(32.0),
+            ..SamplerDesc::default()
+        };
+        assert!(too_anisotropic.validate(&anisotropy, &limits()).is_err());
+
+        let custom_border = SamplerDesc {
+            address_modes: [vk::SamplerAddressMode::CLAMP_TO_BORDER; 3],
+            border_color: vk::BorderColor::FLOAT_CUSTOM_EXT,
+            ..SamplerDesc::default()
+        };
+        assert!(matches!(
+            custom_border.validate(&no_features, &limits()),
+            Err(Error::Unsupported)
+        ));
+        let unused_custom_border = SamplerDesc {
+            border_color: vk::BorderColor::INT_CUSTOM_EXT,
+            ..SamplerDesc::default()
+        };
+        assert!(matches!(
+            unused_custom_border.validate(&no_features, &limits()),
+            Err(Error::Unsupported)
+        ));
+        let white_border = SamplerDesc {
+            border_color: vk::BorderColor::FLOAT_OPAQUE_WHITE,
+            ..custom_border
+        };
+        assert!(white_border.validate(&no_features, &limits()).is_ok());
+    }
+
+    /// Needs a Vulkan device; skipped when no driver is available.
+    #[test]
+    fn test_create_linear_and_comparison_samplers() {
+        let Some((_instance, device, _allocator)) = crate::test_util::headless_gpu() else {
+            return;
+        };
+
+        Sampler::new(&device, &SamplerDesc::default()).expect("linear sampler");
+        let shadow = SamplerDesc {
+            address_modes: [vk::SamplerAddressMode::CLAMP_TO_EDGE; 3],
+            compare_op: Some(vk::CompareOp::LESS_OR_EQUAL),
+            ..SamplerDesc::default()
+        };
+        Sampler::new(&device, &shadow).expect("comparison sampler");
+    }
+}
@@ -4,7 +4,9 @@
 //! bytecode. Errors are mapped to the [`Error`](crate::error::Error) type.
 
 use crate::error::{Error as RenderError, Result as RenderResult};
+use crate::shader_cache::ShaderCache;
 use shader_slang::Downcast;
+use std::path::{Path, PathBuf};
 
 /// Slang compiler session wrapper.
 pub struct Compiler {
@@ -30,6 +32,17 @@ impl Compiler {
         source: &str,
         entry_point: &str,
     ) -> RenderResult<Vec<u8>> {
+        self.compile_source(module_name, source, entry_point)
+            .map(|(spirv, _)| spirv)
+    }
+
+    /// Compile `source`, returning the SPIR-V and the files it imported.
+    fn compile_source(
+        &self,
+        module_name: &str,
+        source: &str,
+        entry_point: &str,
+    ) -> RenderResult<(Vec<u8>, Vec<PathBuf>)> {
         // `shader-slang` 0.1 exposes file-based `load_module`. Compile from
         // source by writing to a temporary file.
         let temp_dir = std::env::temp_dir();
@@ -40,7 +53,17 @@ impl Compiler {
             RenderError::Backend(format!("failed to write temp shader file: {}", e))
         })?;
 
-        let result = self.compile_file_to_spirv(temp_path.to_string_lossy().as_ref(), entry_point);
+        let result = self
+            .compile_file(&temp_path, entry_point)
+            .map(|(spirv, dependencies)| {
+                // The module's own file is the temporary copy of `source`.
+                let own = std::fs::canonicalize(&temp_path).ok();
+                let imports = dependencies
+                    .into_iter()
+                    .filter(|path| std::fs::canonicalize(path).ok() != own)
+                    .collect();
+                (spirv, imports)
+            });
 
         // Best-effort cleanup; ignore errors.
         let _ = std::fs::remove_file(&temp_path);
@@ -48,8 +71,34 @@ impl Compiler {
         result
     }
 
+    /// Like [`compile_source_to_spirv`](Self::compile_source_to_spirv), but
+    /// reuses SPIR-V from `cache` when the source, entry point and imported
+    /// files are unchanged, and stores freshly compiled output in it.
+    pub fn compile_source_to_spirv_cached(
+        &self,
+        cache: &ShaderCache,
+        module_name: &str,
+        source: &str,
+        entry_point: &str,
+    ) -> RenderResult<Vec<u8>> {
+        cache.get_or_insert_with(source, entry_point, || {
+            self.compile_source(module_name, source, entry_point)
+        })
+    }
+
     /// Compile a Slang file to SPIR-V for the given entry point.
     pub fn compile_file_to_spirv(&self, path: &str, entry_point: &str) -> RenderResult<Vec<u8>> {
+        self.compile_file(Path::new(path), entry_point)
+            .map(|(spirv, _)| spirv)
+    }
+
+    /// Compile the file at `path`, returning the SPIR-V and every file the
+    /// module was built from.
+    fn compile_file(
+        &self,
+        path: &Path,
+        entry_point: &str,
+    ) -> RenderResult<(Vec<u8>, Vec<PathBuf>)> {
         let options = shader_slang::CompilerOptions::default()
             .optimization(shader_slang::OptimizationLevel::High)
             .matrix_layout_row(true);
@@ -70,7 +119,9 @@ impl Compiler {
             .create_session(&session_desc)
             .ok_or_else(|| RenderError::Backend("failed to create Slang session".to_string()))?;
 
-        let module = session.load_module(path).map_err(map_slang_error)?;
+        let module = session
+            .load_module(path.to_string_lossy().as_ref())
+            .map_err(map_slang_error)?;
 
         let entry = module
             .find_entry_point_by_name(entry_point)
@@ -85,7 +136,11 @@ impl Compiler {
         let linked = program.link().map_err(map_slang_error)?;
         let bytecode = linked.entry_point_code(0, 0).map_err(map_slang_error)?;
 
-        Ok(bytecode.as_slice().to_vec())
+        let dependencies = (0..module.dependency_file_count())
+            .map(|index| PathBuf::from(module.dependency_file_path(index)))
+            .collect();
+
+        Ok((bytecode.as_slice().to_vec(), dependencies))
     }
 }
 
@@ -0,0 +1,318 @@
+//! On-disk cache for compiled SPIR-V.
+//!
+//! Compiled shaders are stored as `<key>.spv` files in a cache directory,
+//! keyed by a hash of the shader source, entry point and Slang build, so
+//! unchanged shaders skip recompilation across runs. Each entry also records
+//! the files the shader imported and a hash of their contents; an entry whose
+//! dependencies have changed since it was written is a miss.
+
+use crate::error::{Error, Result};
+use std::path::{Path, PathBuf};
+
+/// Bump when the compiler configuration changes in a way that makes old
+/// cache entries invalid.
+const CACHE_VERSION: &str = "slang-spirv-glsl_450-v2";
+
+/// First line of every entry, followed by one `<hash> <path>` line per
+/// dependency and an empty line, then the SPIR-V.
+const ENTRY_HEADER: &[u8] = b"moonfield-shader-cache\n";
+
+const SPIRV_MAGIC: [u8; 4] = 0x0723_0203u32.to_le_bytes();
+
+/// 64-bit FNV-1a over `parts`, each terminated by a zero byte. Unlike `std`'s
+/// hasher it is stable across toolchains, so cache entries survive Rust
+/// upgrades.
+fn fnv1a<'a>(parts: impl IntoIterator<Item = &'a [u8]>) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for part in parts {
+        for &byte in part.iter().chain(std::iter::once(&0)) {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+    hash
+}
+
+/// Identifies the Slang build shaders are compiled with. `shader-slang`
+/// links the SDK found through `SLANG_DIR` or `VULKAN_SDK` at build time, and
+/// SDK install paths carry the release version, so a rebuild against another
+/// Slang release changes every key.
+fn slang_build() -> String {
+    format!(
+        "{}|{}",
+        option_env!("SLANG_DIR").unwrap_or_default(),
+        option_env!("VULKAN_SDK").unwrap_or_default()
+    )
+}
+
+/// A directory of cached SPIR-V binaries.
+pub struct ShaderCache {
+    dir: PathBuf,
+}
+
+impl ShaderCache {
+    /// Open a cache in `dir`, creating the directory if needed.
+    pub fn new(dir: impl Into<PathBuf>) -> Result<Self> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir).map_err(|e| {
+            Error::Backend(format!(
+                "failed to create shader cache directory {}: {}",
+                dir.display(),
+                e
+            ))
+        })?;
+        Ok(Self { dir })
+    }
+
+    /// The cache directory.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// The key under which SPIR-V for `source` and `entry_point` is stored.
+    ///
+    /// Covers the cache version and the Slang build as well as the inputs;
+    /// imported files are checked separately when an entry is read.
+    pub fn key(source: &str, entry_point: &str) -> u64 {
+        let build = slang_build();
+        fnv1a([
+            CACHE_VERSION.as_bytes(),
+            build.as_bytes(),
+            entry_point.as_bytes(),
+            source.as_bytes(),
+        ])
+    }
+
+    /// Look up cached SPIR-V. Missing or corrupt entries, and entries whose
+    /// dependencies have changed or disappeared, are misses.
+    pub fn get(&self, source: &str, entry_point: &str) -> Option<Vec<u8>> {
+        let bytes = std::fs::read(self.path(source, entry_point)).ok()?;
+        let mut rest = bytes.strip_prefix(ENTRY_HEADER)?;
+        loop {
+            let end = rest.iter().position(|&b| b == b'\n')?;
+            let line = std::str::from_utf8(&rest[..end]).ok()?;
+            rest = &rest[end + 1..];
+            if line.is_empty() {
+                break;
+            }
+            let (hash, path) = line.split_once(' ')?;
+            let contents = std::fs::read(path).ok()?;
+            if u64::from_str_radix(hash, 16).ok()? != fnv1a([contents.as_slice()]) {
+                return None;
+            }
+        }
+        (rest.len().is_multiple_of(4) && rest.starts_with(&SPIRV_MAGIC)).then(|| rest.to_vec())
+    }
+
+    /// Store SPIR-V for `source` and `entry_point`, along with the current
+    /// contents of the files it was compiled from besides `source` itself.
+    ///
+    /// The file is written under a temporary name and renamed into place, so
+    /// concurrent readers never observe a partial entry.
+    pub fn insert(
+        &self,
+        source: &str,
+        entry_point: &str,
+        spirv: &[u8],
+        dependencies: &[PathBuf],
+    ) -> Result<()> {
+        let path = self.path(source, entry_point);
+        let write_error = |e: std::io::Error| {
+            Error::Backend(format!(
+                "failed to write shader cache entry {}: {}",
+                path.display(),
+                e
+            ))
+        };
+
+        let mut entry = ENTRY_HEADER.to_vec();
+        for dependency in dependencies {
+            let contents = std::fs::read(dependency).map_err(|e| {
+                Error::Backend(format!(
+                    "failed to read shader dependency {}: {}",
+                    dependency.display(),
+                    e
+                ))
+            })?;
+            let recorded = dependency
+                .to_str()
+                .filter(|p| !p.contains('\n'))
+                .ok_or_else(|| {
+                    Error::Validation(format!(
+                        "shader dependency path {} cannot be recorded in the cache",
+                        dependency.display()
+                    ))
+                })?;
+            entry.extend_from_slice(
+                format!("{:016x} {}\n", fnv1a([contents.as_slice()]), recorded).as_bytes(),
+            );
+        }
+        entry.push(b'\n');
+        entry.extend_from_slice(spirv);
+
+        let temp_path = path.with_extension(format!("spv.{}.tmp", std::process::id()));
+        std::fs::write(&temp_path, entry)
+            .and_then(|()| std::fs::rename(&temp_path, &path))
+            .map_err(|e| {
+                let _ = std::fs::remove_file(&temp_path);
+                write_error(e)
+            })
+    }
+
+    /// Return cached SPIR-V, or run `compile` and cache its output.
+    ///
+    /// `compile` returns the SPIR-V and the files it read besides `source`.
+    /// Compilation errors are returned unchanged and nothing is cached.
+    pub fn get_or_insert_with(
+        &self,
+        source: &str,
+        entry_point: &str,
+        compile: impl FnOnce() -> Result<(Vec<u8>, Vec<PathBuf>)>,
+    ) -> Result<Vec<u8>> {
+        if let Some(spirv) = self.get(source, entry_point) {
+            return Ok(spirv);
+        }
+        let (spirv, dependencies) = compile()?;
+        self.insert(source, entry_point, &spirv, &dependencies)?;
+        Ok(spirv)
+    }
+
+    fn path(&self, source: &str, entry_point: &str) -> PathBuf {
+        self.dir
+            .join(format!("{:016x}.spv", Self::key(source, entry_point)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shader::Compiler;
+
+    fn temp_cache(name: &str) -> ShaderCache {
+        let dir = std::env::temp_dir().join(format!(
+            "moonfield-shader-cache-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        ShaderCache::new(dir).unwrap()
+    }
+
+    fn fake_spirv() -> Vec<u8> {
+        [0x0723_0203u32, 0x0001_0000, 0, 1, 0]
+            .iter()
+            .flat_map(|word| word.to_le_bytes())
+            .collect()
+    }
+
+    #[test]
+    fn test_compiled_shader_is_reused_until_an_import_changes() {
+        let compiler = match Compiler::new() {
+            Ok(compiler) => compiler,
+            Err(err) => {
+                eprintln!("skipping: Slang compiler not available ({err})");
+                return;
+            }
+        };
+        let cache = temp_cache("slang");
+        // Sources are compiled from the temp directory, so imports resolve
+        // there too.
+        let import = format!("moonfield_cache_import_{}", std::process::id());
+        let import_path = std::env::temp_dir().join(format!("{import}.slang"));
+        let write_import = |scale: f32| {
+            std::fs::write(
+                &import_path,
+                format!("public float scale() {{ return {scale:.2}; }}\n"),
+            )
+            .unwrap()
+        };
+        let source = format!(
+            "import {import};\n\
+             [shader(\"fragment\")]\n\
+             float4 main() : SV_TARGET {{ return float4(scale(), 0.0, 0.0, 1.0); }}\n"
+        );
+        let compile = |cache: &ShaderCache| {
+            compiler
+                .compile_source_to_spirv_cached(cache, "cache_test_fs", &source, "main")
+                .expect("fragment shader compilation")
+        };
+
+        write_import(0.25);
+        assert_eq!(cache.get(&source, "main"), None);
+        let first = compile(&cache);
+        assert_eq!(first.get(..4), Some(&SPIRV_MAGIC[..]));
+        assert_eq!(cache.get(&source, "main"), Some(first.clone()));
+        assert_eq!(compile(&cache), first);
+        // Another entry point name is another entry.
+        assert_eq!(cache.get(&source, "fs"), None);
+
+        // Editing the imported module invalidates the entry even though the
+        // top-level source is unchanged.
+        write_import(0.75);
+        assert_eq!(cache.get(&source, "main"), None);
+        let second = compile(&cache);
+        assert_ne!(second, first);
+        assert_eq!(cache.get(&source, "main"), Some(second));
+
+        let _ = std::fs::remove_file(&import_path);
+        let _ = std::fs::remove_dir_all(cache.dir());
+    }
+
+    #[test]
+    fn test_changed_or_missing_dependency_is_a_miss() {
+        let cache = temp_cache("deps");
+        let dependency = cache.dir().join("common.slang");
+        std::fs::write(&dependency, "float one() { return 1.0; }").unwrap();
+
+        cache
+            .insert(
+                "src",
+                "main",
+                &fake_spirv(),
+                std::slice::from_ref(&dependency),
+            )
+            .unwrap();
+        assert_eq!(cache.get("src", "main"), Some(fake_spirv()));
+
+        std::fs::write(&dependency, "float one() { return 2.0; }").unwrap();
+        assert_eq!(cache.get("src", "main"), None);
+
+        cache
+            .insert(
+                "src",
+                "main",
+                &fake_spirv(),
+                std::slice::from_ref(&dependency),
+            )
+            .unwrap();
+        std::fs::remove_file(&dependency).unwrap();
+        assert_eq!(cache.get("src", "main"), None);
+
+        let _ = std::fs::remove_dir_all(cache.dir());
+    }
+
+    #[test]
+    fn test_errors_and_corrupt_entries_are_not_cached() {
+        let cache = temp_cache("corrupt");
+        let result = cache.get_or_insert_with("bad", "main", || {
+            Err(Error::ShaderCompilation("syntax error".to_string()))
+        });
+        assert!(matches!(result, Err(Error::ShaderCompilation(_))));
+        assert_eq!(cache.get("bad", "main"), None);
+
+        std::fs::write(cache.path("src", "main"), b"not spirv").unwrap();
+        assert_eq!(cache.get("src", "main"), None);
+        let mut truncated = ENTRY_HEADER.to_vec();
+        truncated.extend_from_slice(b"\nnot spirv");
+        std::fs::write(cache.path("src", "main"), truncated).unwrap();
+        assert_eq!(cache.get("src", "main"), None);
+
+        let _ = std::fs::remove_dir_all(cache.dir());
+    }
+
+    #[test]
+    fn test_key_is_stable() {
+        assert_eq!(ShaderCache::key("a", "main"), ShaderCache::key("a", "main"));
+        assert_ne!(ShaderCache::key("ab", "c"), ShaderCache::key("a", "bc"));
+    }
+}
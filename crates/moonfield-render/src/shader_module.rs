@@ -2,11 +2,16 @@
 
 use crate::device::Device;
 use crate::error::{Error, Result};
+#[cfg(feature = "reflect")]
+use crate::reflect::ShaderReflection;
 use ash::vk;
 
 /// A Vulkan shader module created from SPIR-V bytecode.
 pub struct ShaderModule {
     module: vk::ShaderModule,
+    /// The SPIR-V words, kept for [`reflect`](Self::reflect).
+    #[cfg(feature = "reflect")]
+    code: Vec<u32>,
     device: ash::Device,
 }
 
@@ -36,6 +41,8 @@ impl ShaderModule {
 
         Ok(Self {
             module,
+            #[cfg(feature = "reflect")]
+            code,
             device: device.raw().clone(),
         })
     }
@@ -44,6 +51,13 @@ impl ShaderModule {
     pub fn raw(&self) -> vk::ShaderModule {
         self.module
     }
+
+    /// Reflect the entry points, stage interface and descriptor bindings
+    /// declared by the module's SPIR-V.
+    #[cfg(feature = "reflect")]
+    pub fn reflect(&self) -> Result<ShaderReflection> {
+        ShaderReflection::from_words(&self.code)
+    }
 }
 
 impl Drop for ShaderModule {
@@ -0,0 +1,211 @@
+//! Depth-only render target sampled with a comparison sampler.
+//!
+//! Provides [`ShadowMap`], a depth image + depth-only render pass +
+//! framebuffer bundle for shadow mapping: occluders are rendered from the
+//! light into the depth image, and the lighting pass samples it with the
+//! paired comparison sampler. Like [`OffscreenTarget`](crate::OffscreenTarget)
+//! the pass finishes in `SHADER_READ_ONLY_OPTIMAL`, so the depth is ready for
+//! sampling without explicit transitions.
+
+use crate::device::Device;
+use crate::error::{Error, Result};
+use crate::format;
+use crate::framebuffer::Framebuffer;
+use crate::instance::Instance;
+use crate::offscreen::{
+    create_image, create_image_view, image_barrier, log_free_error, read_image, submit_one_shot,
+};
+use crate::render_pass::{Attachment, RenderPass};
+use crate::sampler::{Sampler, SamplerDesc};
+use ash::vk;
+use gpu_allocator::vulkan::{Allocation, Allocator};
+use std::sync::{Arc, Mutex};
+
+/// A renderable depth target sampled with depth comparison.
+///
+/// `Drop` waits for the device to go idle, destroys the view and image and
+/// frees the allocation; the framebuffer, render pass and sampler are then
+/// destroyed as their fields drop. Nothing is in use after the wait, so the
+/// framebuffer may outlive the view it was created with.
+pub struct ShadowMap {
+    framebuffer: Framebuffer,
+    render_pass: RenderPass,
+    image_view: vk::ImageView,
+    sampler: Sampler,
+    image: vk::Image,
+    allocation: Option<Allocation>,
+    allocator: Arc<Mutex<Allocator>>,
+    device: ash::Device,
+    format: vk::Format,
+    extent: vk::Extent2D,
+}
+
+impl ShadowMap {
+    /// Create a `width`×`height` shadow map with the given depth format,
+    /// such as `D32_SFLOAT`.
+    ///
+    /// The format must be depth-only; the device must support rendering to
+    /// and sampling it. The sampler compares with `LESS_OR_EQUAL`, clamps to
+    /// the edge and filters linearly when the format allows it.
+    pub fn new(
+        device: &Device,
+        allocator: Arc<Mutex<Allocator>>,
+        width: u32,
+        height: u32,
+        format: vk::Format,
+    ) -> Result<Self> {
+        if width == 0 || height == 0 {
+            return Err(Error::Validation(format!(
+                "shadow map dimensions must be non-zero, got {}x{}",
+                width, height
+            )));
+        }
+        if !format::is_depth_format(format) || format::has_stencil(format) {
+            return Err(Error::Validation(format!(
+                "shadow map format {:?} is not a depth-only format",
+                format
+            )));
+        }
+
+        let render_pass =
+            RenderPass::new_with_attachments(device, &[], Some(Attachment::sampled_depth(format)))?;
+        let extent = vk::Extent2D { width, height };
+        let (image, allocation) = create_image(
+            device,
+            &allocator,
+            extent,
+            format,
+            vk::SampleCountFlags::TYPE_1,
+            vk::ImageUsageFlags::DEPTH_STENCIL_ATTACHMENT
+                | vk::ImageUsageFlags::SAMPLED
+                | vk::ImageUsageFlags::TRANSFER_SRC,
+            "shadow-map",
+        )?;
+        let image_view = create_image_view(device, image, format, vk::ImageAspectFlags::DEPTH)?;
+        let framebuffer = Framebuffer::new(device, &render_pass, &[image_view], extent)?;
+
+        let filter = if device
+            .format_properties(format)
+            .optimal_tiling_features
+            .contains(vk::FormatFeatureFlags::SAMPLED_IMAGE_FILTER_LINEAR)
+        {
+            vk::Filter::LINEAR
+        } else {
+            vk::Filter::NEAREST
+        };
+        let sampler = Sampler::new(
+            device,
+            &SamplerDesc {
+                address_modes: [vk::SamplerAddressMode::CLAMP_TO_EDGE; 3],
+                mag_filter: filter,
+                min_filter: filter,
+                mipmap_mode: vk::SamplerMipmapMode::NEAREST,
+                lod_max_clamp: 0.0,
+                compare_op: Some(vk::CompareOp::LESS_OR_EQUAL),
+                ..SamplerDesc::default()
+            },
+        )?;
+
+        transition_to_shader_read(device, image)?;
+
+        Ok(Self {
+            framebuffer,
+            render_pass,
+            image_view,
+            sampler,
+            image,
+            allocation: Some(allocation),
+            allocator,
+            device: device.raw().clone(),
+            format,
+            extent,
+        })
+    }
+
+    /// Copy the depth image back to the host as tightly packed texels, e.g.
+    /// `f32` values for `D32_SFLOAT`.
+    ///
+    /// The rendered pass must have completed, e.g. by waiting on its fence;
+    /// this call then blocks until the copy itself has finished. The image is
+    /// left in `SHADER_READ_ONLY_OPTIMAL`.
+    pub fn read_depth(&self, instance: &Instance, device: &Device) -> Result<Vec<u8>> {
+        read_image(
+            instance,
+            device,
+            self.image,
+            vk::ImageAspectFlags::DEPTH,
+            self.format,
+            self.extent,
+        )
+    }
+
+    /// Access the depth image view (for binding to the lighting pass).
+    pub fn image_view(&self) -> vk::ImageView {
+        self.image_view
+    }
+
+    /// Access the comparison sampler paired with the depth image.
+    pub fn sampler(&self) -> &Sampler {
+        &self.sampler
+    }
+
+    /// Access the depth-only render pass targeting this shadow map.
+    pub fn render_pass(&self) -> &RenderPass {
+        &self.render_pass
+    }
+
+    /// Access the framebuffer for recording the occluder pass.
+    pub fn framebuffer(&self) -> &Framebuffer {
+        &self.framebuffer
+    }
+
+    /// The depth format of the shadow map.
+    pub fn format(&self) -> vk::Format {
+        self.format
+    }
+
+    /// The `(width, height)` of the shadow map.
+    pub fn extent(&self) -> (u32, u32) {
+        (self.extent.width, self.extent.height)
+    }
+}
+
+impl Drop for ShadowMap {
+    fn drop(&mut self) {
+        // SAFETY: best-effort wait so the image is not destroyed while in
+        // use; afterwards the handles are no longer referenced.
+        unsafe {
+            let _ = self.device.device_wait_idle();
+            self.device.destroy_image_view(self.image_view, None);
+            self.device.destroy_image(self.image, None);
+        }
+        if let Some(allocation) = self.allocation.take() {
+            let mut allocator = self.allocator.lock().unwrap_or_else(|e| e.into_inner());
+            if let Err(e) = allocator.free(allocation) {
+                log_free_error(&e);
+            }
+        }
+    }
+}
+
+/// Transition the depth image from UNDEFINED to SHADER_READ_ONLY_OPTIMAL via
+/// a one-shot command buffer, so sampling is valid before the first render.
+fn transition_to_shader_read(device: &Device, image: vk::Image) -> Result<()> {
+    submit_one_shot(device, |command_buffer| {
+        command_buffer.pipeline_barrier(
+            vk::PipelineStageFlags::TOP_OF_PIPE,
+            vk::PipelineStageFlags::FRAGMENT_SHADER,
+            vk::DependencyFlags::empty(),
+            &[],
+            &[],
+            &[image_barrier(
+                image,
+                vk::ImageAspectFlags::DEPTH,
+                vk::AccessFlags::empty(),
+                vk::AccessFlags::SHADER_READ,
+                vk::ImageLayout::UNDEFINED,
+                vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+            )],
+        );
+    })
+}
@@ -0,0 +1,182 @@
+//! Shader stage conversions between Slang and Vulkan.
+
+use crate::error::{Error, Result};
+use ash::vk;
+use std::path::Path;
+
+/// A single programmable pipeline stage.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ShaderStage {
+    Vertex,
+    /// Tessellation control; Slang calls it `hull`.
+    Hull,
+    /// Tessellation evaluation; Slang calls it `domain`.
+    Domain,
+    Geometry,
+    Fragment,
+    Compute,
+}
+
+impl ShaderStage {
+    /// All stages, in pipeline order.
+    pub const ALL: [Self; 6] = [
+        Self::Vertex,
+        Self::Hull,
+        Self::Domain,
+        Self::Geometry,
+        Self::Fragment,
+        Self::Compute,
+    ];
+
+    /// The matching single-bit Vulkan stage flag.
+    pub fn to_vk(self) -> vk::ShaderStageFlags {
+        match self {
+            Self::Vertex => vk::ShaderStageFlags::VERTEX,
+            Self::Hull => vk::ShaderStageFlags::TESSELLATION_CONTROL,
+            Self::Domain => vk::ShaderStageFlags::TESSELLATION_EVALUATION,
+            Self::Geometry => vk::ShaderStageFlags::GEOMETRY,
+            Self::Fragment => vk::ShaderStageFlags::FRAGMENT,
+            Self::Compute => vk::ShaderStageFlags::COMPUTE,
+        }
+    }
+
+    /// Convert a Vulkan stage mask holding exactly one supported stage.
+    pub fn from_vk(flags: vk::ShaderStageFlags) -> Result<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|stage| stage.to_vk() == flags)
+            .ok_or_else(|| {
+                Error::Validation(format!(
+                    "expected a single supported shader stage, got {:?}",
+                    flags
+                ))
+            })
+    }
+
+    /// The glslang file extension for the stage, e.g. `vert` or `frag`.
+    pub fn file_extension(self) -> &'static str {
+        match self {
+            Self::Vertex => "vert",
+            Self::Hull => "tesc",
+            Self::Domain => "tese",
+            Self::Geometry => "geom",
+            Self::Fragment => "frag",
+            Self::Compute => "comp",
+        }
+    }
+
+    /// Infer the stage from a shader file name using the glslang extensions
+    /// (`.vert`, `.tesc`, `.tese`, `.geom`, `.frag`, `.comp`).
+    ///
+    /// The stage extension may be followed by others, so `triangle.vert.spv`
+    /// is a vertex shader. Returns `None` when no extension names a stage.
+    pub fn from_path(path: impl AsRef<Path>) -> Option<Self> {
+        let file_name = path.as_ref().file_name()?.to_str()?;
+        let (_stem, extensions) = file_name.split_once('.')?;
+        extensions.rsplit('.').find_map(|extension| {
+            Self::ALL
+                .into_iter()
+                .find(|stage| extension.eq_ignore_ascii_case(stage.file_extension()))
+        })
+    }
+}
+
+impl From<ShaderStage> for shader_slang::Stage {
+    fn from(stage: ShaderStage) -> Self {
+        match stage {
+            ShaderStage::Vertex => Self::Vertex,
+            ShaderStage::Hull => Self::Hull,
+            ShaderStage::Domain => Self::Domain,
+            ShaderStage::Geometry => Self::Geometry,
+            ShaderStage::Fragment => Self::Fragment,
+            ShaderStage::Compute => Self::Compute,
+        }
+    }
+}
+
+impl TryFrom<shader_slang::Stage> for ShaderStage {
+    type Error = Error;
+
+    /// Fails with [`Error::Unsupported`] for stages without a
+    /// [`ShaderStage`], such as ray tracing and mesh stages.
+    fn try_from(stage: shader_slang::Stage) -> Result<Self> {
+        match stage {
+            shader_slang::Stage::Vertex => Ok(Self::Vertex),
+            shader_slang::Stage::Hull => Ok(Self::Hull),
+            shader_slang::Stage::Domain => Ok(Self::Domain),
+            shader_slang::Stage::Geometry => Ok(Self::Geometry),
+            shader_slang::Stage::Fragment => Ok(Self::Fragment),
+            shader_slang::Stage::Compute => Ok(Self::Compute),
+            _ => Err(Error::Unsupported),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stage_round_trips() {
+        for stage in ShaderStage::ALL {
+            assert_eq!(ShaderStage::from_vk(stage.to_vk()).unwrap(), stage);
+            let slang = shader_slang::Stage::from(stage);
+            assert_eq!(ShaderStage::try_from(slang).unwrap(), stage);
+        }
+    }
+
+    #[test]
+    fn test_slang_stages() {
+        assert_eq!(
+            shader_slang::Stage::from(ShaderStage::Hull),
+            shader_slang::Stage::Hull
+        );
+        assert_eq!(
+            ShaderStage::try_from(shader_slang::Stage::Compute).map(ShaderStage::to_vk),
+            Ok(vk::ShaderStageFlags::COMPUTE)
+        );
+        assert_eq!(
+            ShaderStage::try_from(shader_slang::Stage::RayGeneration),
+            Err(Error::Unsupported)
+        );
+    }
+
+    #[test]
+    fn test_from_vk_rejects_masks() {
+        assert!(ShaderStage::from_vk(
+            vk::ShaderStageFlags::VERTEX | vk::ShaderStageFlags::FRAGMENT
+        )
+        .is_err());
+        assert!(ShaderStage::from_vk(vk::ShaderStageFlags::empty()).is_err());
+        assert!(ShaderStage::from_vk(vk::ShaderStageFlags::ALL_GRAPHICS).is_err());
+    }
+
+    #[test]
+    fn test_from_path_infers_stage_from_extensions() {
+        assert_eq!(
+            ShaderStage::from_path("shaders/triangle.vert.spv"),
+            Some(ShaderStage::Vertex)
+        );
+        assert_eq!(
+            ShaderStage::from_path("triangle.frag"),
+            Some(ShaderStage::Fragment)
+        );
+        assert_eq!(
+            ShaderStage::from_path("cull.COMP.spv"),
+            Some(ShaderStage::Compute)
+        );
+        assert_eq!(
+            ShaderStage::from_path("terrain.tese.spv"),
+            Some(ShaderStage::Domain)
+        );
+        for stage in ShaderStage::ALL {
+            let path = format!("shader.{}.spv", stage.file_extension());
+            assert_eq!(ShaderStage::from_path(path), Some(stage));
+        }
+
+        // The stem alone never names a stage.
+        assert_eq!(ShaderStage::from_path("vert.spv"), None);
+        assert_eq!(ShaderStage::from_path("triangle.slang"), None);
+        assert_eq!(ShaderStage::from_path("shaders/"), None);
+    }
+}
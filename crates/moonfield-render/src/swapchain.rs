@@ -2,6 +2,7 @@
 
 use crate::device::Device;
 use crate::error::{Error, Result};
+use crate::format;
 use crate::instance::Instance;
 use ash::vk;
 use raw_window_handle::{DisplayHandle, HasDisplayHandle, HasWindowHandle, WindowHandle};
@@ -113,12 +114,134 @@ impl Drop for Surface {
     }
 }
 
+/// Requested presentation mode, resolved against the modes a surface
+/// supports when the swapchain is created.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PresentMode {
+    /// Vsync with the lowest latency available: `FIFO_RELAXED`, else `FIFO`.
+    AutoVsync,
+    /// No vsync if possible: `IMMEDIATE`, else `MAILBOX`, else `FIFO`.
+    AutoNoVsync,
+    /// `FIFO`, which every surface supports.
+    Fifo,
+    /// `FIFO_RELAXED`, falling back to `FIFO`.
+    FifoRelaxed,
+    /// `IMMEDIATE`, falling back to `FIFO`.
+    Immediate,
+    /// `MAILBOX`, falling back to `FIFO`.
+    Mailbox,
+}
+
+impl PresentMode {
+    /// Pick the first mode of this request's fallback chain that appears in
+    /// `supported`.
+    ///
+    /// Every chain ends with `FIFO`, which Vulkan guarantees, so resolving
+    /// never fails.
+    pub fn resolve(self, supported: &[vk::PresentModeKHR]) -> vk::PresentModeKHR {
+        use vk::PresentModeKHR as Mode;
+        let chain: &[Mode] = match self {
+            PresentMode::AutoVsync => &[Mode::FIFO_RELAXED],
+            PresentMode::AutoNoVsync => &[Mode::IMMEDIATE, Mode::MAILBOX],
+            PresentMode::Fifo => &[],
+            PresentMode::FifoRelaxed => &[Mode::FIFO_RELAXED],
+            PresentMode::Immediate => &[Mode::IMMEDIATE],
+            PresentMode::Mailbox => &[Mode::MAILBOX],
+        };
+        chain
+            .iter()
+            .copied()
+            .find(|mode| supported.contains(mode))
+            .unwrap_or(Mode::FIFO)
+    }
+}
+
+/// Settings a [`Swapchain`] is created with and keeps across
+/// [`recreate`](Swapchain::recreate).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SwapchainConfig {
+    pub format: vk::SurfaceFormatKHR,
+    pub present_mode: PresentMode,
+    /// Frames that may be queued for presentation; the swapchain gets one
+    /// more image than this, within the surface's image count limits.
+    pub desired_maximum_frame_latency: u32,
+    pub composite_alpha: vk::CompositeAlphaFlagsKHR,
+}
+
+impl SwapchainConfig {
+    /// A configuration for a surface reporting `formats` and
+    /// `capabilities`.
+    ///
+    /// Takes the surface's preferred (first) format, switched to its sRGB
+    /// variant when the surface supports that, `FIFO` presentation, a frame
+    /// latency of 2 and the first supported composite alpha mode.
+    pub fn default_from(
+        formats: &[vk::SurfaceFormatKHR],
+        capabilities: &vk::SurfaceCapabilitiesKHR,
+    ) -> Result<Self> {
+        let preferred = formats.first().ok_or(Error::Unsupported)?;
+        let srgb = format::add_srgb_suffix(preferred.format);
+        let format = formats
+            .iter()
+            .find(|f| f.format == srgb && f.color_space == preferred.color_space)
+            .unwrap_or(preferred);
+
+        let alpha_bits = capabilities.supported_composite_alpha.as_raw();
+        if alpha_bits == 0 {
+            return Err(Error::Unsupported);
+        }
+        let composite_alpha =
+            vk::CompositeAlphaFlagsKHR::from_raw(alpha_bits & alpha_bits.wrapping_neg());
+
+        Ok(Self {
+            format: *format,
+            present_mode: PresentMode::Fifo,
+            desired_maximum_frame_latency: 2,
+            composite_alpha,
+        })
+    }
+}
+
+/// Pick a surface format from those the surface reports.
+///
+/// Prefers 8-bit BGRA, then RGBA, in the sRGB color space: their `_SRGB`
+/// variants when `prefer_srgb`, else their `_UNORM` ones. Otherwise takes
+/// the first reported format, switched to or from its sRGB variant when the
+/// surface supports that. Returns `None` only when `formats` is empty.
+pub fn choose_surface_format(
+    formats: &[vk::SurfaceFormatKHR],
+    prefer_srgb: bool,
+) -> Option<vk::SurfaceFormatKHR> {
+    let with_srgbness = |format| {
+        if prefer_srgb {
+            format::add_srgb_suffix(format)
+        } else {
+            format::remove_srgb_suffix(format)
+        }
+    };
+    let supports = |format, color_space| {
+        formats
+            .iter()
+            .find(|f| f.format == format && f.color_space == color_space)
+            .copied()
+    };
+
+    let preferred = [vk::Format::B8G8R8A8_UNORM, vk::Format::R8G8B8A8_UNORM]
+        .into_iter()
+        .find_map(|format| supports(with_srgbness(format), vk::ColorSpaceKHR::SRGB_NONLINEAR));
+    preferred.or_else(|| {
+        let first = formats.first()?;
+        supports(with_srgbness(first.format), first.color_space).or(Some(*first))
+    })
+}
+
 /// Vulkan swapchain and its image views.
 pub struct Swapchain {
     swapchain: vk::SwapchainKHR,
     images: Vec<vk::Image>,
     image_views: Vec<vk::ImageView>,
-    format: vk::SurfaceFormatKHR,
+    config: SwapchainConfig,
+    present_mode: vk::PresentModeKHR,
     extent: vk::Extent2D,
     loader: ash::khr::swapchain::Device,
     device: ash::Device,
@@ -126,126 +249,177 @@ pub struct Swapchain {
 
 impl Swapchain {
     /// Create a swapchain for the given surface and window size.
+    ///
+    /// Presents with `MAILBOX` when supported, else `FIFO`.
     pub fn new(
         instance: &Instance,
         device: &Device,
         surface: &Surface,
         window_size: [u32; 2],
+    ) -> Result<Self> {
+        Self::new_with_present_mode(instance, device, surface, window_size, PresentMode::Mailbox)
+    }
+
+    /// Create a swapchain presenting with `present_mode`, resolved against
+    /// the modes the surface supports (see [`PresentMode::resolve`]).
+    pub fn new_with_present_mode(
+        instance: &Instance,
+        device: &Device,
+        surface: &Surface,
+        window_size: [u32; 2],
+        present_mode: PresentMode,
     ) -> Result<Self> {
         let physical_device = device.physical_device();
+        let formats = surface.formats(physical_device)?;
         let capabilities = surface.capabilities(physical_device)?;
+
+        let format = choose_surface_format(&formats, false).ok_or(Error::Unsupported)?;
+
+        let config = SwapchainConfig {
+            format,
+            present_mode,
+            // One image more than the surface minimum.
+            desired_maximum_frame_latency: capabilities.min_image_count,
+            composite_alpha: vk::CompositeAlphaFlagsKHR::OPAQUE,
+        };
+        Self::new_with_config(instance, device, surface, window_size, &config)
+    }
+
+    /// Create a swapchain from an explicit configuration, e.g. one built
+    /// with [`SwapchainConfig::default_from`].
+    ///
+    /// The format and composite alpha mode must be supported by the
+    /// surface; the present mode is resolved against the supported modes.
+    pub fn new_with_config(
+        instance: &Instance,
+        device: &Device,
+        surface: &Surface,
+        window_size: [u32; 2],
+        config: &SwapchainConfig,
+    ) -> Result<Self> {
+        let physical_device = device.physical_device();
         let formats = surface.formats(physical_device)?;
+        let capabilities = surface.capabilities(physical_device)?;
         let present_modes = surface.present_modes(physical_device)?;
 
-        if formats.is_empty() {
-            return Err(Error::Unsupported);
+        if !formats.contains(&config.format) {
+            return Err(Error::Validation(format!(
+                "surface does not support format {:?}",
+                config.format
+            )));
+        }
+        if !capabilities
+            .supported_composite_alpha
+            .contains(config.composite_alpha)
+        {
+            return Err(Error::Validation(format!(
+                "surface does not support composite alpha {:?}",
+                config.composite_alpha
+            )));
+        }
+        if config.desired_maximum_frame_latency == 0 {
+            return Err(Error::Validation(
+                "desired maximum frame latency must be at least 1".to_string(),
+            ));
         }
 
-        let format = formats
-            .iter()
-            .find(|f| {
-                f.format == vk::Format::B8G8R8A8_UNORM
-                    && f.color_space == vk::ColorSpaceKHR::SRGB_NONLINEAR
-            })
-            .copied()
-            .unwrap_or(formats[0]);
+        let present_mode = config.present_mode.resolve(&present_modes);
 
-        let present_mode = present_modes
-            .iter()
-            .copied()
-            .find(|&mode| mode == vk::PresentModeKHR::MAILBOX)
-            .unwrap_or(vk::PresentModeKHR::FIFO);
+        let loader = ash::khr::swapchain::Device::new(instance.raw(), device.raw());
+        let (swapchain, extent) = create_swapchain(
+            &loader,
+            device,
+            surface,
+            window_size,
+            config,
+            present_mode,
+            vk::SwapchainKHR::null(),
+        )?;
 
-        let extent = if capabilities.current_extent.width != u32::MAX {
-            capabilities.current_extent
-        } else {
-            vk::Extent2D {
-                width: window_size[0].clamp(
-                    capabilities.min_image_extent.width,
-                    capabilities.max_image_extent.width,
-                ),
-                height: window_size[1].clamp(
-                    capabilities.min_image_extent.height,
-                    capabilities.max_image_extent.height,
-                ),
-            }
+        let mut this = Self {
+            swapchain,
+            images: Vec::new(),
+            image_views: Vec::new(),
+            config: *config,
+            present_mode,
+            extent,
+            loader,
+            device: device.raw().clone(),
         };
+        this.create_image_views()?;
+        Ok(this)
+    }
 
-        let mut image_count = capabilities.min_image_count + 1;
-        if capabilities.max_image_count > 0 && image_count > capabilities.max_image_count {
-            image_count = capabilities.max_image_count;
+    /// Rebuild the swapchain and its image views for a new window size,
+    /// keeping its configuration.
+    ///
+    /// Callers must recreate the swapchain whenever acquire or present
+    /// reports `ERROR_OUT_OF_DATE_KHR` (typically after a resize), and
+    /// should do so on `SUBOPTIMAL_KHR`. The caller must also ensure no
+    /// submitted work still references the old images, e.g. by waiting for
+    /// the device to go idle, and must rebuild anything created from
+    /// [`image_views`](Self::image_views) such as framebuffers.
+    pub fn recreate(
+        &mut self,
+        device: &Device,
+        surface: &Surface,
+        window_size: [u32; 2],
+    ) -> Result<()> {
+        let (swapchain, extent) = create_swapchain(
+            &self.loader,
+            device,
+            surface,
+            window_size,
+            &self.config,
+            self.present_mode,
+            self.swapchain,
+        )?;
+
+        // The old swapchain was retired by passing it as `old_swapchain`; it
+        // can be destroyed once the new one exists.
+        self.destroy_image_views();
+        // SAFETY: the caller guarantees the old images are no longer in use.
+        unsafe {
+            self.loader.destroy_swapchain(self.swapchain, None);
         }
+        self.swapchain = swapchain;
+        self.extent = extent;
+        self.create_image_views()
+    }
 
-        let indices = device.queue_family_indices();
-        let family_indices: Vec<u32> = if indices.graphics != indices.present {
-            vec![indices.graphics, indices.present]
-        } else {
-            vec![]
-        };
-        let sharing_mode = if family_indices.is_empty() {
-            vk::SharingMode::EXCLUSIVE
-        } else {
-            vk::SharingMode::CONCURRENT
-        };
-
-        let create_info = vk::SwapchainCreateInfoKHR::default()
-            .surface(surface.raw())
-            .min_image_count(image_count)
-            .image_format(format.format)
-            .image_color_space(format.color_space)
-            .image_extent(extent)
-            .image_array_layers(1)
-            .image_usage(vk::ImageUsageFlags::COLOR_ATTACHMENT)
-            .image_sharing_mode(sharing_mode)
-            .queue_family_indices(&family_indices)
-            .pre_transform(capabilities.current_transform)
-            .composite_alpha(vk::CompositeAlphaFlagsKHR::OPAQUE)
-            .present_mode(present_mode)
-            .clipped(true);
-
-        let loader = ash::khr::swapchain::Device::new(instance.raw(), device.raw());
-        let swapchain = unsafe { loader.create_swapchain(&create_info, None) }
-            .map_err(|e| Error::Backend(format!("failed to create swapchain: {:?}", e)))?;
-
-        let images = unsafe { loader.get_swapchain_images(swapchain) }
+    fn create_image_views(&mut self) -> Result<()> {
+        self.images = unsafe { self.loader.get_swapchain_images(self.swapchain) }
             .map_err(|e| Error::Backend(format!("failed to get swapchain images: {:?}", e)))?;
 
-        let image_views: Result<Vec<_>> = images
-            .iter()
-            .map(|image| {
-                let create_info = vk::ImageViewCreateInfo::default()
-                    .image(*image)
-                    .view_type(vk::ImageViewType::TYPE_2D)
-                    .format(format.format)
-                    .subresource_range(
-                        vk::ImageSubresourceRange::default()
-                            .aspect_mask(vk::ImageAspectFlags::COLOR)
-                            .base_mip_level(0)
-                            .level_count(1)
-                            .base_array_layer(0)
-                            .layer_count(1),
-                    );
-                unsafe {
-                    device
-                        .raw()
-                        .create_image_view(&create_info, None)
-                        .map_err(|e| {
-                            Error::Backend(format!("failed to create image view: {:?}", e))
-                        })
-                }
-            })
-            .collect();
-        let image_views = image_views?;
+        for image in &self.images {
+            let create_info = vk::ImageViewCreateInfo::default()
+                .image(*image)
+                .view_type(vk::ImageViewType::TYPE_2D)
+                .format(self.config.format.format)
+                .subresource_range(
+                    vk::ImageSubresourceRange::default()
+                        .aspect_mask(vk::ImageAspectFlags::COLOR)
+                        .base_mip_level(0)
+                        .level_count(1)
+                        .base_array_layer(0)
+                        .layer_count(1),
+                );
+            let view = unsafe {
+                self.device
+                    .create_image_view(&create_info, None)
+                    .map_err(|e| Error::Backend(format!("failed to create image view: {:?}", e)))?
+            };
+            self.image_views.push(view);
+        }
+        Ok(())
+    }
 
-        Ok(Self {
-            swapchain,
-            images,
-            image_views,
-            format,
-            extent,
-            loader,
-            device: device.raw().clone(),
-        })
+    fn destroy_image_views(&mut self) {
+        unsafe {
+            for view in self.image_views.drain(..) {
+                self.device.destroy_image_view(view, None);
+            }
+        }
     }
 
     /// Access the raw swapchain handle.
@@ -265,7 +439,17 @@ impl Swapchain {
 
     /// Access the selected surface format.
     pub fn format(&self) -> vk::SurfaceFormatKHR {
-        self.format
+        self.config.format
+    }
+
+    /// The configuration the swapchain was created with.
+    pub fn config(&self) -> &SwapchainConfig {
+        &self.config
+    }
+
+    /// Access the selected present mode.
+    pub fn present_mode(&self) -> vk::PresentModeKHR {
+        self.present_mode
     }
 
     /// Access the swapchain extent.
@@ -275,33 +459,34 @@ impl Swapchain {
 
     /// Acquire the next available swapchain image.
     ///
-    /// Returns `(image_index, suboptimal)` on success. Propagates ash's
-    /// `vk::Result` so callers can react to `ERROR_OUT_OF_DATE_KHR` (the
-    /// swapchain must be recreated) and `SUBOPTIMAL_KHR`.
+    /// Returns `(image_index, suboptimal)` on success. Fails with
+    /// [`Error::SurfaceOutdated`] when the swapchain must be recreated and
+    /// [`Error::Timeout`] when no image became available in time.
     pub fn acquire_next_image(
         &self,
         timeout_ns: u64,
         semaphore: vk::Semaphore,
-    ) -> std::result::Result<(u32, bool), vk::Result> {
+    ) -> Result<(u32, bool)> {
         // SAFETY: the swapchain and semaphore are valid handles; no fence is
         // used, the caller synchronizes with its own in-flight fence.
         unsafe {
             self.loader
                 .acquire_next_image(self.swapchain, timeout_ns, semaphore, vk::Fence::null())
+                .map_err(|e| Error::from_vk("failed to acquire swapchain image", e))
         }
     }
 
     /// Present a rendered swapchain image, waiting on the given semaphores.
     ///
     /// Returns `true` when the swapchain is suboptimal for the surface.
-    /// Propagates ash's `vk::Result` so callers can react to
-    /// `ERROR_OUT_OF_DATE_KHR`.
+    /// Fails with [`Error::SurfaceOutdated`] when the swapchain must be
+    /// recreated.
     pub fn queue_present(
         &self,
         queue: vk::Queue,
         wait_semaphores: &[vk::Semaphore],
         image_index: u32,
-    ) -> std::result::Result<bool, vk::Result> {
+    ) -> Result<bool> {
         let swapchains = [self.swapchain];
         let image_indices = [image_index];
         let present_info = vk::PresentInfoKHR::default()
@@ -310,17 +495,311 @@ impl Swapchain {
             .image_indices(&image_indices);
         // SAFETY: the swapchain, queue and semaphores are valid handles; the
         // image index was acquired from this swapchain.
-        unsafe { self.loader.queue_present(queue, &present_info) }
+        unsafe {
+            self.loader
+                .queue_present(queue, &present_info)
+                .map_err(|e| Error::from_vk("failed to present swapchain image", e))
+        }
     }
 }
 
 impl Drop for Swapchain {
     fn drop(&mut self) {
+        self.destroy_image_views();
         unsafe {
-            for view in self.image_views.drain(..) {
-                self.device.destroy_image_view(view, None);
-            }
             self.loader.destroy_swapchain(self.swapchain, None);
         }
     }
 }
+
+/// Run `acquire` on `target`; if the swapchain is out of date, run
+/// `recreate` and retry once.
+///
+/// The retry happens at most once: a swapchain that is still out of date
+/// right after recreation (e.g. during a continuous resize) is reported as
+/// [`Error::SurfaceOutdated`] instead of looping. Timeouts and other errors
+/// are returned unchanged. A suboptimal acquire is not retried, since the
+/// image is already acquired and must be presented; recreate after
+/// presenting it instead.
+pub fn acquire_with_recovery<S, T>(
+    target: &mut S,
+    mut acquire: impl FnMut(&mut S) -> Result<T>,
+    mut recreate: impl FnMut(&mut S) -> Result<()>,
+) -> Result<T> {
+    match acquire(target) {
+        Err(Error::SurfaceOutdated) => {
+            recreate(target)?;
+            acquire(target)
+        }
+        result => result,
+    }
+}
+
+/// Create a swapchain for `surface`, retiring `old_swapchain` if non-null.
+fn create_swapchain(
+    loader: &ash::khr::swapchain::Device,
+    device: &Device,
+    surface: &Surface,
+    window_size: [u32; 2],
+    config: &SwapchainConfig,
+    present_mode: vk::PresentModeKHR,
+    old_swapchain: vk::SwapchainKHR,
+) -> Result<(vk::SwapchainKHR, vk::Extent2D)> {
+    let capabilities = surface.capabilities(device.physical_device())?;
+
+    let extent = if capabilities.current_extent.width != u32::MAX {
+        capabilities.current_extent
+    } else {
+        vk::Extent2D {
+            width: window_size[0].clamp(
+                capabilities.min_image_extent.width,
+                capabilities.max_image_extent.width,
+            ),
+            height: window_size[1].clamp(
+                capabilities.min_image_extent.height,
+                capabilities.max_image_extent.height,
+            ),
+        }
+    };
+
+    let mut image_count =
+        (config.desired_maximum_frame_latency + 1).max(capabilities.min_image_count);
+    if capabilities.max_image_count > 0 && image_count > capabilities.max_image_count {
+        image_count = capabilities.max_image_count;
+    }
+
+    let indices = device.queue_family_indices();
+    let family_indices: Vec<u32> = if indices.graphics != indices.present {
+        vec![indices.graphics, indices.present]
+    } else {
+        vec![]
+    };
+    let sharing_mode = if family_indices.is_empty() {
+        vk::SharingMode::EXCLUSIVE
+    } else {
+        vk::SharingMode::CONCURRENT
+    };
+
+    let create_info = vk::SwapchainCreateInfoKHR::default()
+        .surface(surface.raw())
+        .min_image_count(image_count)
+        .image_format(config.format.format)
+        .image_color_space(config.format.color_space)
+        .image_extent(extent)
+        .image_array_layers(1)
+        .image_usage(vk::ImageUsageFlags::COLOR_ATTACHMENT)
+        .image_sharing_mode(sharing_mode)
+        .queue_family_indices(&family_indices)
+        .pre_transform(capabilities.current_transform)
+        .composite_alpha(config.composite_alpha)
+        .present_mode(present_mode)
+        .clipped(true)
+        .old_swapchain(old_swapchain);
+
+    let swapchain = unsafe { loader.create_swapchain(&create_info, None) }
+        .map_err(|e| Error::Backend(format!("failed to create swapchain: {:?}", e)))?;
+
+    Ok((swapchain, extent))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Stand-in swapchain replaying scripted acquire results.
+    struct MockSwapchain {
+        results: Vec<Result<u32>>,
+        recreated: u32,
+    }
+
+    impl MockSwapchain {
+        fn new(mut results: Vec<Result<u32>>) -> Self {
+            results.reverse();
+            Self {
+                results,
+                recreated: 0,
+            }
+        }
+
+        fn acquire(&mut self) -> Result<u32> {
+            self.results.pop().expect("unexpected acquire")
+        }
+
+        fn recreate(&mut self) -> Result<()> {
+            self.recreated += 1;
+            Ok(())
+        }
+    }
+
+    fn acquire(mock: &mut MockSwapchain) -> Result<u32> {
+        acquire_with_recovery(mock, MockSwapchain::acquire, MockSwapchain::recreate)
+    }
+
+    #[test]
+    fn test_present_mode_fallback_chains() {
+        use vk::PresentModeKHR as Mode;
+        let fifo_only = [Mode::FIFO];
+        let everything = [
+            Mode::FIFO,
+            Mode::FIFO_RELAXED,
+            Mode::MAILBOX,
+            Mode::IMMEDIATE,
+        ];
+        let mailbox = [Mode::MAILBOX, Mode::FIFO];
+
+        assert_eq!(
+            PresentMode::AutoVsync.resolve(&everything),
+            Mode::FIFO_RELAXED
+        );
+        assert_eq!(PresentMode::AutoVsync.resolve(&mailbox), Mode::FIFO);
+        assert_eq!(PresentMode::AutoVsync.resolve(&fifo_only), Mode::FIFO);
+
+        assert_eq!(
+            PresentMode::AutoNoVsync.resolve(&everything),
+            Mode::IMMEDIATE
+        );
+        assert_eq!(PresentMode::AutoNoVsync.resolve(&mailbox), Mode::MAILBOX);
+        assert_eq!(PresentMode::AutoNoVsync.resolve(&fifo_only), Mode::FIFO);
+
+        assert_eq!(PresentMode::Mailbox.resolve(&mailbox), Mode::MAILBOX);
+        assert_eq!(PresentMode::Immediate.resolve(&mailbox), Mode::FIFO);
+        assert_eq!(PresentMode::FifoRelaxed.resolve(&fifo_only), Mode::FIFO);
+        assert_eq!(PresentMode::Fifo.resolve(&everything), Mode::FIFO);
+        // An empty list should not happen, but FIFO is still guaranteed.
+        assert_eq!(PresentMode::AutoNoVsync.resolve(&[]), Mode::FIFO);
+    }
+
+    fn surface_format(format: vk::Format) -> vk::SurfaceFormatKHR {
+        vk::SurfaceFormatKHR {
+            format,
+            color_space: vk::ColorSpaceKHR::SRGB_NONLINEAR,
+        }
+    }
+
+    #[test]
+    fn test_default_config_from_capabilities() {
+        let capabilities = vk::SurfaceCapabilitiesKHR {
+            min_image_count: 2,
+            supported_composite_alpha: vk::CompositeAlphaFlagsKHR::INHERIT
+                | vk::CompositeAlphaFlagsKHR::PRE_MULTIPLIED,
+            ..Default::default()
+        };
+        let formats = [
+            surface_format(vk::Format::B8G8R8A8_UNORM),
+            surface_format(vk::Format::R8G8B8A8_UNORM),
+            surface_format(vk::Format::B8G8R8A8_SRGB),
+        ];
+
+        let config = SwapchainConfig::default_from(&formats, &capabilities).expect("config");
+        assert_eq!(config.format, surface_format(vk::Format::B8G8R8A8_SRGB));
+        assert_eq!(config.present_mode, PresentMode::Fifo);
+        assert_eq!(config.desired_maximum_frame_latency, 2);
+        assert_eq!(
+            config.composite_alpha,
+            vk::CompositeAlphaFlagsKHR::PRE_MULTIPLIED
+        );
+
+        // Without an sRGB variant the preferred format is kept.
+        let formats = [
+            surface_format(vk::Format::A2B10G10R10_UNORM_PACK32),
+            surface_format(vk::Format::B8G8R8A8_SRGB),
+        ];
+        let config = SwapchainConfig::default_from(&formats, &capabilities).expect("config");
+        assert_eq!(
+            config.format,
+            surface_format(vk::Format::A2B10G10R10_UNORM_PACK32)
+        );
+
+        assert_eq!(
+            SwapchainConfig::default_from(&[], &capabilities),
+            Err(Error::Unsupported)
+        );
+        let no_alpha = vk::SurfaceCapabilitiesKHR::default();
+        assert_eq!(
+            SwapchainConfig::default_from(&formats, &no_alpha),
+            Err(Error::Unsupported)
+        );
+    }
+
+    #[test]
+    fn test_choose_surface_format_prefers_8bit_variants() {
+        let formats = [
+            surface_format(vk::Format::A2B10G10R10_UNORM_PACK32),
+            surface_format(vk::Format::R8G8B8A8_UNORM),
+            surface_format(vk::Format::R8G8B8A8_SRGB),
+            surface_format(vk::Format::B8G8R8A8_SRGB),
+            surface_format(vk::Format::B8G8R8A8_UNORM),
+        ];
+        assert_eq!(
+            choose_surface_format(&formats, true),
+            Some(surface_format(vk::Format::B8G8R8A8_SRGB))
+        );
+        assert_eq!(
+            choose_surface_format(&formats, false),
+            Some(surface_format(vk::Format::B8G8R8A8_UNORM))
+        );
+
+        // RGBA when BGRA is missing.
+        assert_eq!(
+            choose_surface_format(&formats[..3], true),
+            Some(surface_format(vk::Format::R8G8B8A8_SRGB))
+        );
+        assert_eq!(
+            choose_surface_format(&formats[..3], false),
+            Some(surface_format(vk::Format::R8G8B8A8_UNORM))
+        );
+
+        // Only the opposite variant: the first format is adjusted if possible.
+        assert_eq!(
+            choose_surface_format(&formats[1..2], true),
+            Some(surface_format(vk::Format::R8G8B8A8_UNORM))
+        );
+        assert_eq!(
+            choose_surface_format(&formats[2..4], false),
+            Some(surface_format(vk::Format::R8G8B8A8_SRGB))
+        );
+    }
+
+    #[test]
+    fn test_choose_surface_format_falls_back_to_first() {
+        let exotic = [vk::SurfaceFormatKHR {
+            format: vk::Format::A2B10G10R10_UNORM_PACK32,
+            color_space: vk::ColorSpaceKHR::HDR10_ST2084_EXT,
+        }];
+        assert_eq!(choose_surface_format(&exotic, true), Some(exotic[0]));
+        assert_eq!(choose_surface_format(&exotic, false), Some(exotic[0]));
+        assert_eq!(choose_surface_format(&[], true), None);
+
+        // 8-bit formats only count in the sRGB color space.
+        let extended = [vk::SurfaceFormatKHR {
+            format: vk::Format::B8G8R8A8_UNORM,
+            color_space: vk::ColorSpaceKHR::EXTENDED_SRGB_LINEAR_EXT,
+        }];
+        assert_eq!(choose_surface_format(&extended, false), Some(extended[0]));
+    }
+
+    #[test]
+    fn test_acquire_recovers_from_one_outdated_swapchain() {
+        let mut mock = MockSwapchain::new(vec![Err(Error::SurfaceOutdated), Ok(2)]);
+        assert_eq!(acquire(&mut mock), Ok(2));
+        assert_eq!(mock.recreated, 1);
+
+        let mut mock = MockSwapchain::new(vec![Ok(0)]);
+        assert_eq!(acquire(&mut mock), Ok(0));
+        assert_eq!(mock.recreated, 0);
+    }
+
+    #[test]
+    fn test_acquire_retries_only_once() {
+        let mut mock = MockSwapchain::new(vec![
+            Err(Error::SurfaceOutdated),
+            Err(Error::SurfaceOutdated),
+        ]);
+        assert_eq!(acquire(&mut mock), Err(Error::SurfaceOutdated));
+        assert_eq!(mock.recreated, 1);
+
+        let mut mock = MockSwapchain::new(vec![Err(Error::Timeout)]);
+        assert_eq!(acquire(&mut mock), Err(Error::Timeout));
+        assert_eq!(mock.recreated, 0);
+    }
+}
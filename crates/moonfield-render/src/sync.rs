@@ -75,15 +75,30 @@ impl Fence {
     }
 
     /// Wait for the fence to be signaled.
+    ///
+    /// Fails with [`Error::Timeout`] if it is not signaled within
+    /// `timeout_ns`.
     pub fn wait(&self, timeout_ns: u64) -> Result<()> {
         unsafe {
             self.device
                 .wait_for_fences(std::slice::from_ref(&self.fence), true, timeout_ns)
-                .map_err(|e| Error::Backend(format!("failed to wait for fence: {:?}", e)))?;
+                .map_err(|e| Error::from_vk("failed to wait for fence", e))?;
         }
         Ok(())
     }
 
+    /// Query whether the fence is signaled without blocking.
+    ///
+    /// Use this to poll a submission for completion, e.g. before reading
+    /// back a buffer it writes to.
+    pub fn is_signaled(&self) -> Result<bool> {
+        unsafe {
+            self.device
+                .get_fence_status(self.fence)
+                .map_err(|e| Error::from_vk("failed to query fence status", e))
+        }
+    }
+
     /// Reset the fence to unsignaled.
     pub fn reset(&self) -> Result<()> {
         unsafe {
@@ -102,3 +117,22 @@ impl Drop for Fence {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Needs a Vulkan device; skipped when no driver is available.
+    #[test]
+    fn test_fence_status_tracks_signal_and_reset() {
+        let Some((_instance, device, _allocator)) = crate::test_util::headless_gpu() else {
+            return;
+        };
+
+        let fence = Fence::new(&device, true).expect("fence");
+        assert!(fence.is_signaled().expect("status"));
+        fence.reset().expect("reset");
+        assert!(!fence.is_signaled().expect("status"));
+        device.wait_idle().expect("wait idle");
+    }
+}
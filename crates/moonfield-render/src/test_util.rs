@@ -0,0 +1,89 @@
+//! Shared setup for tests that need a Vulkan device.
+//!
+//! Built into the unit tests as `crate::test_util` and into the integration
+//! tests through `tests/common`, so crate items are only named via `super`.
+
+use super::{CommandBuffer, Compiler, Device, Fence, Instance, InstanceOptions, ShaderModule};
+use ash::vk;
+use gpu_allocator::vulkan::{Allocator, AllocatorCreateDesc};
+use std::sync::{Arc, Mutex};
+
+/// Create a headless instance with default options, a device and an
+/// allocator for it.
+///
+/// Returns `None` after logging why when no Vulkan driver is available, so
+/// tests skip on GPU-less runners instead of failing.
+pub(crate) fn headless_gpu() -> Option<(Instance, Device, Arc<Mutex<Allocator>>)> {
+    headless_gpu_with(InstanceOptions::default())
+}
+
+/// Like [`headless_gpu`], with the instance created from `options`.
+pub(crate) fn headless_gpu_with(
+    options: InstanceOptions,
+) -> Option<(Instance, Device, Arc<Mutex<Allocator>>)> {
+    let instance = match Instance::new_with_options(&[], options) {
+        Ok(instance) => instance,
+        Err(err) => {
+            eprintln!("skipping: no Vulkan instance available ({err})");
+            return None;
+        }
+    };
+    let device = match Device::new(&instance, None) {
+        Ok(device) => device,
+        Err(err) => {
+            eprintln!("skipping: no Vulkan device available ({err})");
+            return None;
+        }
+    };
+    let allocator = Allocator::new(&AllocatorCreateDesc {
+        instance: instance.raw().clone(),
+        device: device.raw().clone(),
+        physical_device: device.physical_device(),
+        debug_settings: Default::default(),
+        buffer_device_address: false,
+        allocation_sizes: Default::default(),
+    })
+    .expect("allocator");
+    Some((instance, device, Arc::new(Mutex::new(allocator))))
+}
+
+/// Compile a Slang vertex and fragment shader, each with a `main` entry
+/// point, into shader modules.
+pub(crate) fn compile_shaders(
+    device: &Device,
+    vertex_source: &str,
+    fragment_source: &str,
+) -> (ShaderModule, ShaderModule) {
+    let compiler = Compiler::new().expect("compiler creation");
+    let vertex_spirv = compiler
+        .compile_source_to_spirv("test_vs", vertex_source, "main")
+        .expect("vertex shader compilation");
+    let fragment_spirv = compiler
+        .compile_source_to_spirv("test_fs", fragment_source, "main")
+        .expect("fragment shader compilation");
+    let vertex_shader =
+        ShaderModule::from_spirv(device, &vertex_spirv).expect("vertex shader module");
+    let fragment_shader =
+        ShaderModule::from_spirv(device, &fragment_spirv).expect("fragment shader module");
+    (vertex_shader, fragment_shader)
+}
+
+/// Submit a recorded command buffer to the graphics queue and block until it
+/// has finished executing.
+pub(crate) fn submit_and_wait(device: &Device, command_buffer: &CommandBuffer) {
+    let fence = Fence::new(device, false).expect("fence");
+    let command_buffers = [command_buffer.raw()];
+    let submit_info = vk::SubmitInfo::default().command_buffers(&command_buffers);
+    // SAFETY: the command buffer is fully recorded and the queue is valid.
+    unsafe {
+        device
+            .raw()
+            .queue_submit(
+                device.graphics_queue(),
+                std::slice::from_ref(&submit_info),
+                fence.raw(),
+            )
+            .expect("submit");
+    }
+    fence.wait(u64::MAX).expect("wait for submission");
+}
@@ -0,0 +1,386 @@
+//! GPU-only sampled and storage images.
+//!
+//! [`TextureDesc`] describes an image's dimension, extent, mip chain, array
+//! layers and sample count; [`Texture::new`] validates it against the
+//! device's limits and format support before creating the image.
+
+use crate::device::Device;
+use crate::error::{Error, Result};
+use crate::format;
+use crate::image_view::ImageView;
+use crate::offscreen::{allocate_image, log_free_error};
+use ash::vk;
+use gpu_allocator::vulkan::{Allocation, Allocator};
+use std::sync::{Arc, Mutex};
+
+/// Description of a [`Texture`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TextureDesc {
+    /// `TYPE_1D`, `TYPE_2D` or `TYPE_3D`.
+    pub dimension: vk::ImageType,
+    /// Size of mip level 0. Unused axes of 1D and 2D textures must be 1.
+    pub extent: vk::Extent3D,
+    /// Number of mip levels, at most [`format::max_mip_levels`] of `extent`.
+    pub mip_levels: u32,
+    /// Number of array layers; 3D textures have exactly one.
+    pub array_layers: u32,
+    /// Samples per texel; multisampled textures are 2D with a single mip.
+    pub samples: vk::SampleCountFlags,
+    pub format: vk::Format,
+    pub usage: vk::ImageUsageFlags,
+}
+
+impl TextureDesc {
+    /// A single-sampled 2D texture with one mip level and one layer.
+    pub fn new_2d(width: u32, height: u32, format: vk::Format, usage: vk::ImageUsageFlags) -> Self {
+        Self {
+            dimension: vk::ImageType::TYPE_2D,
+            extent: vk::Extent3D {
+                width,
+                height,
+                depth: 1,
+            },
+            mip_levels: 1,
+            array_layers: 1,
+            samples: vk::SampleCountFlags::TYPE_1,
+            format,
+            usage,
+        }
+    }
+
+    /// The view type covering every layer of the texture.
+    pub fn default_view_type(&self) -> vk::ImageViewType {
+        match (self.dimension, self.array_layers > 1) {
+            (vk::ImageType::TYPE_1D, false) => vk::ImageViewType::TYPE_1D,
+            (vk::ImageType::TYPE_1D, true) => vk::ImageViewType::TYPE_1D_ARRAY,
+            (vk::ImageType::TYPE_3D, _) => vk::ImageViewType::TYPE_3D,
+            (_, false) => vk::ImageViewType::TYPE_2D,
+            (_, true) => vk::ImageViewType::TYPE_2D_ARRAY,
+        }
+    }
+
+    /// Whether a view of `view_type` over every layer of the texture is
+    /// valid. Array view types accept any layer count; the others need a
+    /// single layer. Cube views are not supported.
+    pub fn supports_view_type(&self, view_type: vk::ImageViewType) -> bool {
+        let single_layer = self.array_layers == 1;
+        match (self.dimension, view_type) {
+            (vk::ImageType::TYPE_1D, vk::ImageViewType::TYPE_1D)
+            | (vk::ImageType::TYPE_2D, vk::ImageViewType::TYPE_2D) => single_layer,
+            (vk::ImageType::TYPE_1D, vk::ImageViewType::TYPE_1D_ARRAY)
+            | (vk::ImageType::TYPE_2D, vk::ImageViewType::TYPE_2D_ARRAY)
+            | (vk::ImageType::TYPE_3D, vk::ImageViewType::TYPE_3D) => true,
+            _ => false,
+        }
+    }
+
+    fn validate(&self, limits: &vk::PhysicalDeviceLimits) -> Result<()> {
+        let vk::Extent3D {
+            width,
+            height,
+            depth,
+        } = self.extent;
+        if width == 0 || height == 0 || depth == 0 {
+            return Err(Error::Validation(format!(
+                "texture extent must be non-zero, got {}x{}x{}",
+                width, height, depth
+            )));
+        }
+
+        let max_dimension = match self.dimension {
+            vk::ImageType::TYPE_1D if height != 1 || depth != 1 => {
+                return Err(Error::Validation(format!(
+                    "1D texture extent must be Nx1x1, got {}x{}x{}",
+                    width, height, depth
+                )));
+            }
+            vk::ImageType::TYPE_2D if depth != 1 => {
+                return Err(Error::Validation(format!(
+                    "2D texture extent must have a depth of 1, got {}",
+                    depth
+                )));
+            }
+            vk::ImageType::TYPE_3D if self.array_layers != 1 => {
+                return Err(Error::Validation(format!(
+                    "3D textures must have one array layer, got {}",
+                    self.array_layers
+                )));
+            }
+            vk::ImageType::TYPE_1D => limits.max_image_dimension1_d,
+            vk::ImageType::TYPE_2D => limits.max_image_dimension2_d,
+            vk::ImageType::TYPE_3D => limits.max_image_dimension3_d,
+            _ => return Err(Error::Unsupported),
+        };
+        if width.max(height).max(depth) > max_dimension {
+            return Err(Error::Validation(format!(
+                "texture extent {}x{}x{} exceeds the device limit of {} for {:?} images",
+                width, height, depth, max_dimension, self.dimension
+            )));
+        }
+        if !(1..=limits.max_image_array_layers).contains(&self.array_layers) {
+            return Err(Error::Validation(format!(
+                "texture array layer count {} is outside 1..={}",
+                self.array_layers, limits.max_image_array_layers
+            )));
+        }
+
+        let max_mips = format::max_mip_levels(self.extent);
+        if !(1..=max_mips).contains(&self.mip_levels) {
+            return Err(Error::Validation(format!(
+                "texture mip level count {} is outside 1..={} for a {}x{}x{} extent",
+                self.mip_levels, max_mips, width, height, depth
+            )));
+        }
+
+        if self.samples != vk::SampleCountFlags::TYPE_1
+            && (self.dimension != vk::ImageType::TYPE_2D || self.mip_levels != 1)
+        {
+            return Err(Error::Validation(format!(
+                "{:?} samples require a 2D texture with a single mip level",
+                self.samples
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// A GPU-only image with its own memory allocation.
+///
+/// The image starts in `UNDEFINED` layout; transitioning it is left to the
+/// caller.
+pub struct Texture {
+    image: vk::Image,
+    allocation: Option<Allocation>,
+    allocator: Arc<Mutex<Allocator>>,
+    device: ash::Device,
+    desc: TextureDesc,
+}
+
+impl Texture {
+    /// Create a texture from `desc`, validating it against the device's
+    /// limits and the format's supported usages and sample counts.
+    pub fn new(
+        device: &Device,
+        allocator: Arc<Mutex<Allocator>>,
+        desc: &TextureDesc,
+    ) -> Result<Self> {
+        desc.validate(&device.properties().limits)?;
+        device.validate_image_support(desc.format, desc.usage, desc.samples)?;
+
+        let image_info = vk::ImageCreateInfo::default()
+            .image_type(desc.dimension)
+            .format(desc.format)
+            .extent(desc.extent)
+            .mip_levels(desc.mip_levels)
+            .array_layers(desc.array_layers)
+            .samples(desc.samples)
+            .tiling(vk::ImageTiling::OPTIMAL)
+            .usage(desc.usage)
+            .sharing_mode(vk::SharingMode::EXCLUSIVE)
+            .initial_layout(vk::ImageLayout::UNDEFINED);
+        let (image, allocation) = allocate_image(device, &allocator, &image_info, "texture")?;
+
+        Ok(Self {
+            image,
+            allocation: Some(allocation),
+            allocator,
+            device: device.raw().clone(),
+            desc: *desc,
+        })
+    }
+
+    /// Create a view of `view_type` over every mip level and layer.
+    ///
+    /// Depth formats are viewed through their depth aspect.
+    pub fn create_view(&self, device: &Device, view_type: vk::ImageViewType) -> Result<ImageView> {
+        if !self.desc.supports_view_type(view_type) {
+            return Err(Error::Validation(format!(
+                "cannot view a {:?} texture with {} layers as {:?}",
+                self.desc.dimension, self.desc.array_layers, view_type
+            )));
+        }
+        let aspect = if format::is_depth_format(self.desc.format) {
+            vk::ImageAspectFlags::DEPTH
+        } else {
+            vk::ImageAspectFlags::COLOR
+        };
+        ImageView::new(
+            device,
+            self.image,
+            self.desc.format,
+            None,
+            view_type,
+            vk::ImageSubresourceRange::default()
+                .aspect_mask(aspect)
+                .base_mip_level(0)
+                .level_count(self.desc.mip_levels)
+                .base_array_layer(0)
+                .layer_count(self.desc.array_layers),
+        )
+    }
+
+    /// Access the raw `vk::Image` handle.
+    pub fn raw(&self) -> vk::Image {
+        self.image
+    }
+
+    /// The description the texture was created from.
+    pub fn desc(&self) -> &TextureDesc {
+        &self.desc
+    }
+}
+
+impl Drop for Texture {
+    fn drop(&mut self) {
+        // SAFETY: best-effort wait so the image is not destroyed while in
+        // use; afterwards the handle is no longer referenced.
+        unsafe {
+            let _ = self.device.device_wait_idle();
+            self.device.destroy_image(self.image, None);
+        }
+        if let Some(allocation) = self.allocation.take() {
+            let mut allocator = self.allocator.lock().unwrap_or_else(|e| e.into_inner());
+            if let Err(e) = allocator.free(allocation) {
+                log_free_error(&e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limits() -> vk::PhysicalDeviceLimits {
+        vk::PhysicalDeviceLimits {
+            max_image_dimension1_d: 4096,
+            max_image_dimension2_d: 4096,
+            max_image_dimension3_d: 256,
+            max_image_array_layers: 256,
+            ..Default::default()
+        }
+    }
+
+    fn sampled_2d(width: u32, height: u32) -> TextureDesc {
+        TextureDesc::new_2d(
+            width,
+            height,
+            vk::Format::R8G8B8A8_UNORM,
+            vk::ImageUsageFlags::SAMPLED,
+        )
+    }
+
+    #[test]
+    fn test_mip_count_is_bounded_by_extent() {
+        let full_chain = TextureDesc {
+            mip_levels: 9,
+            ..sampled_2d(256, 128)
+        };
+        assert!(full_chain.validate(&limits()).is_ok());
+
+        let too_many = TextureDesc {
+            mip_levels: 10,
+            ..full_chain
+        };
+        assert!(matches!(
+            too_many.validate(&limits()),
+            Err(Error::Validation(_))
+        ));
+        let no_mips = TextureDesc {
+            mip_levels: 0,
+            ..full_chain
+        };
+        assert!(no_mips.validate(&limits()).is_err());
+    }
+
+    #[test]
+    fn test_dimension_and_sample_rules() {
+        assert!(sampled_2d(0, 16).validate(&limits()).is_err());
+        assert!(sampled_2d(8192, 16).validate(&limits()).is_err());
+
+        let flat_1d = TextureDesc {
+            dimension: vk::ImageType::TYPE_1D,
+            ..sampled_2d(64, 1)
+        };
+        assert!(flat_1d.validate(&limits()).is_ok());
+        let tall_1d = TextureDesc {
+            dimension: vk::ImageType::TYPE_1D,
+            ..sampled_2d(64, 2)
+        };
+        assert!(tall_1d.validate(&limits()).is_err());
+
+        let volume = TextureDesc {
+            dimension: vk::ImageType::TYPE_3D,
+            extent: vk::Extent3D {
+                width: 32,
+                height: 32,
+                depth: 32,
+            },
+            ..sampled_2d(1, 1)
+        };
+        assert!(volume.validate(&limits()).is_ok());
+        let volume_array = TextureDesc {
+            array_layers: 2,
+            ..volume
+        };
+        assert!(volume_array.validate(&limits()).is_err());
+
+        let msaa = TextureDesc {
+            samples: vk::SampleCountFlags::TYPE_4,
+            ..sampled_2d(64, 64)
+        };
+        assert!(msaa.validate(&limits()).is_ok());
+        let msaa_mipped = TextureDesc {
+            mip_levels: 2,
+            ..msaa
+        };
+        assert!(msaa_mipped.validate(&limits()).is_err());
+    }
+
+    #[test]
+    fn test_view_type_compatibility() {
+        let single = sampled_2d(64, 64);
+        assert_eq!(single.default_view_type(), vk::ImageViewType::TYPE_2D);
+        assert!(single.supports_view_type(vk::ImageViewType::TYPE_2D));
+        assert!(single.supports_view_type(vk::ImageViewType::TYPE_2D_ARRAY));
+        assert!(!single.supports_view_type(vk::ImageViewType::TYPE_3D));
+        assert!(!single.supports_view_type(vk::ImageViewType::CUBE));
+
+        let layered = TextureDesc {
+            array_layers: 4,
+            ..single
+        };
+        assert_eq!(
+            layered.default_view_type(),
+            vk::ImageViewType::TYPE_2D_ARRAY
+        );
+        assert!(!layered.supports_view_type(vk::ImageViewType::TYPE_2D));
+    }
+
+    /// Needs a Vulkan device; skipped when no driver is available.
+    #[test]
+    fn test_create_mipped_2d_texture() {
+        let Some((_instance, device, allocator)) = crate::test_util::headless_gpu() else {
+            return;
+        };
+
+        let desc = TextureDesc {
+            mip_levels: 8,
+            ..sampled_2d(128, 128)
+        };
+        let texture = Texture::new(&device, allocator.clone(), &desc).expect("mipped texture");
+        assert_eq!(texture.desc().mip_levels, 8);
+        texture
+            .create_view(&device, desc.default_view_type())
+            .expect("full view");
+        assert!(texture
+            .create_view(&device, vk::ImageViewType::TYPE_3D)
+            .is_err());
+
+        let too_many = TextureDesc {
+            mip_levels: 9,
+            ..desc
+        };
+        assert!(Texture::new(&device, allocator, &too_many).is_err());
+    }
+}
@@ -0,0 +1,129 @@
+//! Vertex input layouts computed from attribute formats.
+
+use crate::error::{Error, Result};
+use crate::format;
+use ash::vk;
+
+/// The vertex input layout of one interleaved vertex buffer binding.
+///
+/// Attributes are laid out in order, get shader locations `0, 1, 2, ...`
+/// and offsets computed from their format sizes, so they cannot drift out of
+/// sync with hand-written offsets.
+#[derive(Debug, Clone)]
+pub struct VertexLayout {
+    binding: u32,
+    stride: u32,
+    attributes: Vec<vk::VertexInputAttributeDescription>,
+}
+
+impl VertexLayout {
+    /// Lay out `formats` tightly packed, as for a `#[repr(C)]` vertex struct
+    /// whose fields need no padding.
+    pub fn new(binding: u32, formats: &[vk::Format]) -> Result<Self> {
+        Self::new_with_alignment(binding, formats, 1)
+    }
+
+    /// Lay out `formats` with every attribute offset and the stride rounded
+    /// up to a multiple of `alignment`, which must be a power of two.
+    pub fn new_with_alignment(
+        binding: u32,
+        formats: &[vk::Format],
+        alignment: u32,
+    ) -> Result<Self> {
+        if !alignment.is_power_of_two() {
+            return Err(Error::Validation(format!(
+                "vertex attribute alignment {} is not a power of two",
+                alignment
+            )));
+        }
+        let align = |value: u32| (value + alignment - 1) & !(alignment - 1);
+
+        let mut offset = 0;
+        let mut attributes = Vec::with_capacity(formats.len());
+        for (location, &format) in formats.iter().enumerate() {
+            let size = format::texel_size(format).ok_or_else(|| {
+                Error::Validation(format!("unsupported vertex attribute format {:?}", format))
+            })?;
+            offset = align(offset);
+            attributes.push(
+                vk::VertexInputAttributeDescription::default()
+                    .binding(binding)
+                    .location(location as u32)
+                    .format(format)
+                    .offset(offset),
+            );
+            offset += size;
+        }
+
+        Ok(Self {
+            binding,
+            stride: align(offset),
+            attributes,
+        })
+    }
+
+    /// Size in bytes of one vertex.
+    pub fn stride(&self) -> u32 {
+        self.stride
+    }
+
+    /// The per-vertex binding description for this layout.
+    pub fn binding_description(&self) -> vk::VertexInputBindingDescription {
+        vk::VertexInputBindingDescription::default()
+            .binding(self.binding)
+            .stride(self.stride)
+            .input_rate(vk::VertexInputRate::VERTEX)
+    }
+
+    /// The attribute descriptions, in location order.
+    pub fn attributes(&self) -> &[vk::VertexInputAttributeDescription] {
+        &self.attributes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn offsets(layout: &VertexLayout) -> Vec<u32> {
+        layout.attributes().iter().map(|a| a.offset).collect()
+    }
+
+    #[test]
+    fn test_packed_layout_offsets_and_stride() {
+        let layout = VertexLayout::new(
+            0,
+            &[vk::Format::R32G32_SFLOAT, vk::Format::R32G32B32_SFLOAT],
+        )
+        .unwrap();
+        assert_eq!(offsets(&layout), [0, 8]);
+        assert_eq!(layout.stride(), 20);
+
+        let locations: Vec<u32> = layout.attributes().iter().map(|a| a.location).collect();
+        assert_eq!(locations, [0, 1]);
+        assert_eq!(layout.binding_description().stride, 20);
+    }
+
+    #[test]
+    fn test_aligned_layout_pads_offsets_and_stride() {
+        let layout = VertexLayout::new_with_alignment(
+            1,
+            &[
+                vk::Format::R32G32B32_SFLOAT,
+                vk::Format::R8G8B8A8_UNORM,
+                vk::Format::R32G32_SFLOAT,
+            ],
+            16,
+        )
+        .unwrap();
+        assert_eq!(offsets(&layout), [0, 16, 32]);
+        assert_eq!(layout.stride(), 48);
+        assert!(layout.attributes().iter().all(|a| a.binding == 1));
+    }
+
+    #[test]
+    fn test_invalid_layouts_are_rejected() {
+        assert!(VertexLayout::new(0, &[vk::Format::BC1_RGBA_UNORM_BLOCK]).is_err());
+        assert!(VertexLayout::new_with_alignment(0, &[vk::Format::R32_SFLOAT], 3).is_err());
+    }
+}
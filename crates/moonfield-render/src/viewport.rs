@@ -0,0 +1,230 @@
+//! Viewport and scissor rectangles.
+//!
+//! Pipelines bake in a viewport and scissor covering their extent unless
+//! [`PipelineState::dynamic_viewport`](crate::PipelineState::dynamic_viewport)
+//! is set, in which case both are set while recording with
+//! [`CommandBuffer::set_viewport`](crate::CommandBuffer::set_viewport) and
+//! [`CommandBuffer::set_scissor`](crate::CommandBuffer::set_scissor).
+
+use crate::error::{Error, Result};
+use ash::vk;
+
+/// The region of the framebuffer that normalized device coordinates map to,
+/// and the depth range that NDC depth maps to.
+///
+/// A negative `height` flips the Y axis, e.g. to match OpenGL conventions,
+/// with `y` then naming the bottom edge.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Viewport {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+    pub min_depth: f32,
+    pub max_depth: f32,
+}
+
+impl Viewport {
+    /// A viewport covering `extent` with the full `[0, 1]` depth range.
+    pub fn from_extent(extent: vk::Extent2D) -> Self {
+        Self {
+            x: 0.0,
+            y: 0.0,
+            width: extent.width as f32,
+            height: extent.height as f32,
+            min_depth: 0.0,
+            max_depth: 1.0,
+        }
+    }
+
+    /// Check that the width is positive, the height non-zero, both fit
+    /// within the device's `max_viewport_dimensions`, and that
+    /// `0.0 <= min_depth <= max_depth <= 1.0`.
+    pub fn validate(&self, limits: &vk::PhysicalDeviceLimits) -> Result<()> {
+        if !(self.width > 0.0 && self.height != 0.0 && !self.height.is_nan()) {
+            return Err(Error::Validation(format!(
+                "viewport must have a positive width and a non-zero height, got {}x{}",
+                self.width, self.height
+            )));
+        }
+        let [max_width, max_height] = limits.max_viewport_dimensions;
+        if self.width > max_width as f32 || self.height.abs() > max_height as f32 {
+            return Err(Error::Validation(format!(
+                "viewport {}x{} exceeds the device maximum of {}x{}",
+                self.width, self.height, max_width, max_height
+            )));
+        }
+        if !(0.0 <= self.min_depth && self.min_depth <= self.max_depth && self.max_depth <= 1.0) {
+            return Err(Error::Validation(format!(
+                "viewport depth range {}..{} must satisfy 0 <= min <= max <= 1",
+                self.min_depth, self.max_depth
+            )));
+        }
+        Ok(())
+    }
+
+    pub(crate) fn to_vk(self) -> vk::Viewport {
+        vk::Viewport {
+            x: self.x,
+            y: self.y,
+            width: self.width,
+            height: self.height,
+            min_depth: self.min_depth,
+            max_depth: self.max_depth,
+        }
+    }
+}
+
+/// The framebuffer rectangle outside of which fragments are discarded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Scissor {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Scissor {
+    /// A scissor covering `extent`.
+    pub fn from_extent(extent: vk::Extent2D) -> Self {
+        Self {
+            x: 0,
+            y: 0,
+            width: extent.width,
+            height: extent.height,
+        }
+    }
+
+    /// Check that the offset is non-negative and that the rectangle does not
+    /// extend past `i32::MAX`, as Vulkan requires.
+    pub fn validate(&self) -> Result<()> {
+        let fits = |offset: i32, size: u32| {
+            offset >= 0 && i64::from(offset) + i64::from(size) <= i64::from(i32::MAX)
+        };
+        if !fits(self.x, self.width) || !fits(self.y, self.height) {
+            return Err(Error::Validation(format!(
+                "scissor {}x{} at ({}, {}) must have a non-negative offset and end within i32::MAX",
+                self.width, self.height, self.x, self.y
+            )));
+        }
+        Ok(())
+    }
+
+    pub(crate) fn to_vk(self) -> vk::Rect2D {
+        vk::Rect2D {
+            offset: vk::Offset2D {
+                x: self.x,
+                y: self.y,
+            },
+            extent: vk::Extent2D {
+                width: self.width,
+                height: self.height,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limits() -> vk::PhysicalDeviceLimits {
+        vk::PhysicalDeviceLimits {
+            max_viewport_dimensions: [4096, 4096],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_viewport_depth_range() {
+        let extent = vk::Extent2D {
+            width: 800,
+            height: 600,
+        };
+        let full = Viewport::from_extent(extent);
+        assert!(full.validate(&limits()).is_ok());
+        assert_eq!(full.to_vk().max_depth, 1.0);
+
+        // A restricted range, e.g. for drawing a skybox at the far plane.
+        let far_only = Viewport {
+            min_depth: 0.99,
+            ..full
+        };
+        assert!(far_only.validate(&limits()).is_ok());
+        let vk_viewport = far_only.to_vk();
+        assert_eq!((vk_viewport.min_depth, vk_viewport.max_depth), (0.99, 1.0));
+
+        let inverted = Viewport {
+            min_depth: 0.75,
+            max_depth: 0.25,
+            ..full
+        };
+        assert!(matches!(
+            inverted.validate(&limits()),
+            Err(Error::Validation(_))
+        ));
+        let beyond = Viewport {
+            max_depth: 1.5,
+            ..full
+        };
+        assert!(beyond.validate(&limits()).is_err());
+        let negative = Viewport {
+            min_depth: -0.1,
+            ..full
+        };
+        assert!(negative.validate(&limits()).is_err());
+        let empty = Viewport { width: 0.0, ..full };
+        assert!(empty.validate(&limits()).is_err());
+    }
+
+    #[test]
+    fn test_viewport_size_limits() {
+        let full = Viewport::from_extent(vk::Extent2D {
+            width: 800,
+            height: 600,
+        });
+
+        // A negative height flips Y and is allowed.
+        let flipped = Viewport {
+            y: 600.0,
+            height: -600.0,
+            ..full
+        };
+        assert!(flipped.validate(&limits()).is_ok());
+        assert_eq!(flipped.to_vk().height, -600.0);
+
+        let flat = Viewport {
+            height: 0.0,
+            ..full
+        };
+        assert!(flat.validate(&limits()).is_err());
+        let too_wide = Viewport {
+            width: 8192.0,
+            ..full
+        };
+        assert!(too_wide.validate(&limits()).is_err());
+        let too_tall_flipped = Viewport {
+            height: -8192.0,
+            ..full
+        };
+        assert!(too_tall_flipped.validate(&limits()).is_err());
+    }
+
+    #[test]
+    fn test_scissor_bounds() {
+        let scissor = Scissor::from_extent(vk::Extent2D {
+            width: 64,
+            height: 32,
+        });
+        assert!(scissor.validate().is_ok());
+        assert_eq!(scissor.to_vk().extent.width, 64);
+
+        let negative = Scissor { x: -1, ..scissor };
+        assert!(negative.validate().is_err());
+        let overflowing = Scissor {
+            y: i32::MAX - 16,
+            ..scissor
+        };
+        assert!(overflowing.validate().is_err());
+    }
+}
@@ -10,18 +10,16 @@
 
 use crate::device::Device;
 use crate::error::{Error, Result};
+use crate::frame::FrameRing;
 use crate::framebuffer::Framebuffer;
 use crate::instance::Instance;
 use crate::render_pass::RenderPass;
-use crate::swapchain::{Surface, Swapchain};
-use crate::{CommandBuffer, CommandPool, Fence, Semaphore};
+use crate::swapchain::{acquire_with_recovery, Surface, Swapchain};
+use crate::{CommandBuffer, CommandPool, Semaphore};
 use ash::vk;
 use raw_window_handle::{HasDisplayHandle, HasWindowHandle};
 use std::ffi::CStr;
 
-/// Number of frames that may be in flight concurrently.
-const MAX_FRAMES_IN_FLIGHT: usize = 2;
-
 /// Swapchain frame loop for a window.
 ///
 /// Fields are ordered so that Rust drops them in the correct Vulkan
@@ -31,10 +29,8 @@ const MAX_FRAMES_IN_FLIGHT: usize = 2;
 pub struct WindowRenderer {
     image_available: Vec<Semaphore>,
     render_finished: Vec<Semaphore>,
-    in_flight: Vec<Fence>,
-    command_buffers: Vec<CommandBuffer>,
-    /// Held for drop order only: the pool must outlive its command buffers.
-    #[allow(dead_code)]
+    frames: FrameRing,
+    /// Must outlive the frame ring's command buffers.
     command_pool: CommandPool,
     framebuffers: Vec<Framebuffer>,
     render_pass: RenderPass,
@@ -42,7 +38,6 @@ pub struct WindowRenderer {
     device: Device,
     surface: Surface,
     instance: Instance,
-    current_frame: usize,
     current_image: Option<u32>,
     needs_recreate: bool,
 }
@@ -73,22 +68,13 @@ impl WindowRenderer {
         let framebuffers = create_framebuffers(&device, &render_pass, &swapchain)?;
 
         let command_pool = CommandPool::new(&device, device.queue_family_indices().graphics)?;
-        let mut command_buffers = Vec::with_capacity(MAX_FRAMES_IN_FLIGHT);
-        let mut image_available = Vec::with_capacity(MAX_FRAMES_IN_FLIGHT);
-        let mut render_finished = Vec::with_capacity(MAX_FRAMES_IN_FLIGHT);
-        let mut in_flight = Vec::with_capacity(MAX_FRAMES_IN_FLIGHT);
-        for _ in 0..MAX_FRAMES_IN_FLIGHT {
-            command_buffers.push(command_pool.allocate_command_buffer()?);
-            image_available.push(Semaphore::new(&device)?);
-            render_finished.push(Semaphore::new(&device)?);
-            in_flight.push(Fence::new(&device, true)?);
-        }
+        let (frames, image_available, render_finished) =
+            create_frames(&device, &command_pool, &swapchain)?;
 
         Ok(Self {
             image_available,
             render_finished,
-            in_flight,
-            command_buffers,
+            frames,
             command_pool,
             framebuffers,
             render_pass,
@@ -96,7 +82,6 @@ impl WindowRenderer {
             device,
             surface,
             instance,
-            current_frame: 0,
             current_image: None,
             needs_recreate: false,
         })
@@ -114,32 +99,53 @@ impl WindowRenderer {
             ));
         }
 
-        let frame = self.current_frame;
-        self.in_flight[frame].wait(u64::MAX)?;
+        let frame = self.frames.current_index();
+        self.frames.wait_current()?;
 
         let (image_index, suboptimal) = match self
             .swapchain
             .acquire_next_image(u64::MAX, self.image_available[frame].raw())
         {
             Ok(result) => result,
-            Err(vk::Result::ERROR_OUT_OF_DATE_KHR) => {
+            Err(Error::SurfaceOutdated) => {
                 self.needs_recreate = true;
                 return Ok(false);
             }
-            Err(e) => return Err(e.into()),
+            Err(e) => return Err(e),
         };
         if suboptimal {
             self.needs_recreate = true;
         }
 
-        self.in_flight[frame].reset()?;
+        // Only reset once an image is acquired, so an early return above
+        // leaves the fence signaled for the next attempt.
+        let current = self.frames.current();
+        current.fence().reset()?;
         self.current_image = Some(image_index);
 
-        let command_buffer = &mut self.command_buffers[frame];
-        command_buffer.begin(vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT)?;
+        current
+            .command_buffer()
+            .begin(vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT)?;
         Ok(true)
     }
 
+    /// Like [`begin_frame`](Self::begin_frame), but recreates the swapchain
+    /// for a `width` x `height` window and retries once when it is out of
+    /// date (see [`acquire_with_recovery`]).
+    pub fn begin_frame_with_recovery(&mut self, width: u32, height: u32) -> Result<()> {
+        acquire_with_recovery(
+            self,
+            |renderer| {
+                if renderer.begin_frame()? {
+                    Ok(())
+                } else {
+                    Err(Error::SurfaceOutdated)
+                }
+            },
+            |renderer| renderer.recreate(width, height),
+        )
+    }
+
     /// The command buffer recording the current frame.
     ///
     /// Panics if called outside `begin_frame`/`end_frame`.
@@ -148,7 +154,7 @@ impl WindowRenderer {
             self.current_image.is_some(),
             "no frame in progress; call begin_frame first"
         );
-        &mut self.command_buffers[self.current_frame]
+        self.frames.current().command_buffer()
     }
 
     /// The render pass targeting the swapchain images.
@@ -173,14 +179,14 @@ impl WindowRenderer {
             .current_image
             .take()
             .expect("no frame in progress; call begin_frame first");
-        let frame = self.current_frame;
-
-        self.command_buffers[frame].end()?;
+        let frame = self.frames.current_index();
+        let current = self.frames.current();
+        current.command_buffer().end()?;
 
         let wait_semaphores = [self.image_available[frame].raw()];
         let wait_stages = [vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT];
         let signal_semaphores = [self.render_finished[frame].raw()];
-        let command_buffers = [self.command_buffers[frame].raw()];
+        let command_buffers = [current.command_buffer().raw()];
         let submit_info = vk::SubmitInfo::default()
             .wait_semaphores(&wait_semaphores)
             .wait_dst_stage_mask(&wait_stages)
@@ -195,9 +201,9 @@ impl WindowRenderer {
                 .queue_submit(
                     self.device.graphics_queue(),
                     std::slice::from_ref(&submit_info),
-                    self.in_flight[frame].raw(),
+                    current.fence().raw(),
                 )
-                .map_err(|e| Error::Backend(format!("failed to submit frame: {:?}", e)))?;
+                .map_err(|e| Error::from_vk("failed to submit frame", e))?;
         }
 
         match self.swapchain.queue_present(
@@ -210,13 +216,13 @@ impl WindowRenderer {
                     self.needs_recreate = true;
                 }
             }
-            Err(vk::Result::ERROR_OUT_OF_DATE_KHR) => {
+            Err(Error::SurfaceOutdated) => {
                 self.needs_recreate = true;
             }
-            Err(e) => return Err(e.into()),
+            Err(e) => return Err(e),
         }
 
-        self.current_frame = (frame + 1) % MAX_FRAMES_IN_FLIGHT;
+        self.frames.advance();
         Ok(())
     }
 
@@ -226,7 +232,8 @@ impl WindowRenderer {
         self.needs_recreate
     }
 
-    /// Recreate the swapchain and its framebuffers for a new window size.
+    /// Recreate the swapchain, its framebuffers and the frames in flight for a
+    /// new window size.
     ///
     /// Waits for the device to go idle first. Zero dimensions are ignored
     /// (e.g. a minimized window).
@@ -235,20 +242,15 @@ impl WindowRenderer {
             return Ok(());
         }
 
-        // SAFETY: the device is valid; waiting for idle guarantees no frame
-        // still references the old swapchain images.
-        unsafe {
-            self.device
-                .raw()
-                .device_wait_idle()
-                .map_err(|e| Error::Backend(format!("failed to wait for device idle: {:?}", e)))?;
-        }
+        // Waiting for idle guarantees no frame still references the old
+        // swapchain images.
+        self.device.wait_idle()?;
 
-        // The old swapchain is dropped after the new one is created; multiple
-        // swapchains per surface are legal, and the device is idle.
-        self.swapchain =
-            Swapchain::new(&self.instance, &self.device, &self.surface, [width, height])?;
+        self.swapchain
+            .recreate(&self.device, &self.surface, [width, height])?;
         self.framebuffers = create_framebuffers(&self.device, &self.render_pass, &self.swapchain)?;
+        (self.frames, self.image_available, self.render_finished) =
+            create_frames(&self.device, &self.command_pool, &self.swapchain)?;
         self.needs_recreate = false;
         Ok(())
     }
@@ -284,6 +286,24 @@ impl Drop for WindowRenderer {
     }
 }
 
+/// One frame in flight, with its acquire and present semaphores, per frame of
+/// the swapchain's `desired_maximum_frame_latency`.
+fn create_frames(
+    device: &Device,
+    command_pool: &CommandPool,
+    swapchain: &Swapchain,
+) -> Result<(FrameRing, Vec<Semaphore>, Vec<Semaphore>)> {
+    let frames_in_flight = swapchain.config().desired_maximum_frame_latency as usize;
+    let frames = FrameRing::new(device, command_pool, frames_in_flight)?;
+    let image_available = (0..frames_in_flight)
+        .map(|_| Semaphore::new(device))
+        .collect::<Result<_>>()?;
+    let render_finished = (0..frames_in_flight)
+        .map(|_| Semaphore::new(device))
+        .collect::<Result<_>>()?;
+    Ok((frames, image_available, render_finished))
+}
+
 fn create_framebuffers(
     device: &Device,
     render_pass: &RenderPass,
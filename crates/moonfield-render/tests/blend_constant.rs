@@ -0,0 +1,116 @@
+//! Headless blend constant test.
+//!
+//! Draws opaque red over a blue clear with `CONSTANT_COLOR` /
+//! `ONE_MINUS_CONSTANT_COLOR` blending and a constant of 0.5, and checks that
+//! the target reads back as an even mix of the two.
+
+mod common;
+
+use ash::vk;
+use moonfield_render::{
+    BlendState, Color, CommandPool, GraphicsPipeline, GraphicsPipelineDesc, OffscreenTarget,
+    PipelineState,
+};
+use std::panic::AssertUnwindSafe;
+
+const SIZE: u32 = 16;
+
+const VERTEX_SOURCE: &str = r#"
+[shader("vertex")]
+float4 main(uint vertex_id : SV_VertexID) : SV_POSITION
+{
+    float2 positions[3] = { float2(-1.0, -1.0), float2(3.0, -1.0), float2(-1.0, 3.0) };
+    return float4(positions[vertex_id], 0.0, 1.0);
+}
+"#;
+
+const FRAGMENT_SOURCE: &str = r#"
+[shader("fragment")]
+float4 main() : SV_TARGET
+{
+    return float4(1.0, 0.0, 0.0, 1.0);
+}
+"#;
+
+#[test]
+fn blend_constant_mixes_source_and_destination() {
+    let Some((instance, device, allocator)) = common::headless_gpu() else {
+        return;
+    };
+    let target = OffscreenTarget::new(&device, allocator, SIZE, SIZE, vk::Format::R8G8B8A8_UNORM)
+        .expect("offscreen target");
+
+    let (vertex_shader, fragment_shader) =
+        common::compile_shaders(&device, VERTEX_SOURCE, FRAGMENT_SOURCE);
+
+    let extent = vk::Extent2D {
+        width: SIZE,
+        height: SIZE,
+    };
+    let desc = GraphicsPipelineDesc {
+        state: PipelineState {
+            blend: BlendState {
+                enabled: true,
+                src_color_factor: vk::BlendFactor::CONSTANT_COLOR,
+                dst_color_factor: vk::BlendFactor::ONE_MINUS_CONSTANT_COLOR,
+                ..BlendState::REPLACE
+            },
+            ..PipelineState::default()
+        },
+        ..GraphicsPipelineDesc::new(&vertex_shader, &fragment_shader, extent)
+    };
+    let pipeline = GraphicsPipeline::from_desc(&device, target.render_pass(), &desc)
+        .expect("constant-blended pipeline");
+    assert!(pipeline.uses_blend_constant());
+    let opaque_pipeline = GraphicsPipeline::from_desc(
+        &device,
+        target.render_pass(),
+        &GraphicsPipelineDesc::new(&vertex_shader, &fragment_shader, extent),
+    )
+    .expect("opaque pipeline");
+    assert!(!opaque_pipeline.uses_blend_constant());
+
+    let queue_family_index = device.queue_family_indices().graphics;
+    let command_pool = CommandPool::new(&device, queue_family_index).expect("command pool");
+    let mut command_buffer = command_pool
+        .allocate_command_buffer()
+        .expect("command buffer");
+
+    let clear_values = [Color::new(0.0, 0.0, 1.0, 1.0).to_clear_value()];
+    let begin_info = vk::RenderPassBeginInfo::default()
+        .render_pass(target.render_pass().raw())
+        .framebuffer(target.framebuffer().raw())
+        .render_area(vk::Rect2D {
+            offset: vk::Offset2D { x: 0, y: 0 },
+            extent,
+        })
+        .clear_values(&clear_values);
+
+    command_buffer
+        .begin(vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT)
+        .expect("begin command buffer");
+    command_buffer.begin_render_pass(&begin_info, vk::SubpassContents::INLINE);
+    if cfg!(debug_assertions) {
+        // Setting a constant the bound pipeline ignores is a bug.
+        command_buffer.bind_pipeline(&opaque_pipeline);
+        let result = std::panic::catch_unwind(AssertUnwindSafe(|| {
+            command_buffer.set_blend_constant(Color::WHITE)
+        }));
+        assert!(result.is_err());
+    }
+    command_buffer.bind_pipeline(&pipeline);
+    command_buffer.set_blend_constant(Color::new(0.5, 0.5, 0.5, 0.5));
+    command_buffer.draw(3, 1, 0, 0);
+    command_buffer.end_render_pass();
+    command_buffer.end().expect("end command buffer");
+
+    common::submit_and_wait(&device, &command_buffer);
+
+    let pixels = target.read_pixels(&instance, &device).expect("readback");
+    for texel in pixels.chunks_exact(4) {
+        // 0.5 * red + 0.5 * blue, within 8-bit rounding.
+        assert!(texel[0].abs_diff(128) <= 1, "red {}", texel[0]);
+        assert_eq!(texel[1], 0);
+        assert!(texel[2].abs_diff(128) <= 1, "blue {}", texel[2]);
+    }
+}
@@ -0,0 +1,13 @@
+//! Shared setup for the integration tests that need a Vulkan device.
+//!
+//! Each test binary uses a different subset of the helpers.
+#![allow(dead_code)]
+
+use moonfield_render::{
+    CommandBuffer, Compiler, Device, Fence, Instance, InstanceOptions, ShaderModule,
+};
+
+#[path = "../../src/test_util.rs"]
+mod test_util;
+
+pub(crate) use test_util::*;
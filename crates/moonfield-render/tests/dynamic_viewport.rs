@@ -0,0 +1,117 @@
+//! Headless dynamic viewport test.
+//!
+//! Draws a full-screen triangle with a dynamic viewport over the left half
+//! of the target and a scissor over the top half, and checks that only the
+//! top-left quadrant was covered.
+
+mod common;
+
+use ash::vk;
+use moonfield_render::{
+    Color, CommandPool, GraphicsPipeline, GraphicsPipelineDesc, OffscreenTarget, PipelineState,
+    Scissor, Viewport,
+};
+
+const SIZE: u32 = 16;
+const VERTEX_SOURCE: &str = r#"
+[shader("vertex")]
+float4 main(uint vertex_id : SV_VertexID) : SV_POSITION
+{
+    float2 positions[3] = { float2(-1.0, -1.0), float2(3.0, -1.0), float2(-1.0, 3.0) };
+    return float4(positions[vertex_id], 0.0, 1.0);
+}
+"#;
+
+const FRAGMENT_SOURCE: &str = r#"
+[shader("fragment")]
+float4 main() : SV_TARGET
+{
+    return float4(1.0, 0.0, 0.0, 1.0);
+}
+"#;
+
+#[test]
+fn dynamic_viewport_and_scissor_limit_coverage() {
+    let Some((instance, device, allocator)) = common::headless_gpu() else {
+        return;
+    };
+    let target = OffscreenTarget::new(&device, allocator, SIZE, SIZE, vk::Format::R8G8B8A8_UNORM)
+        .expect("offscreen target");
+
+    let (vertex_shader, fragment_shader) =
+        common::compile_shaders(&device, VERTEX_SOURCE, FRAGMENT_SOURCE);
+
+    let extent = vk::Extent2D {
+        width: SIZE,
+        height: SIZE,
+    };
+    let desc = GraphicsPipelineDesc {
+        state: PipelineState {
+            dynamic_viewport: true,
+            ..PipelineState::default()
+        },
+        ..GraphicsPipelineDesc::new(&vertex_shader, &fragment_shader, extent)
+    };
+    let pipeline = GraphicsPipeline::from_desc(&device, target.render_pass(), &desc)
+        .expect("dynamic viewport pipeline");
+
+    let queue_family_index = device.queue_family_indices().graphics;
+    let command_pool = CommandPool::new(&device, queue_family_index).expect("command pool");
+    let mut command_buffer = command_pool
+        .allocate_command_buffer()
+        .expect("command buffer");
+
+    let clear_values = [Color::new(0.0, 0.0, 1.0, 1.0).to_clear_value()];
+    let begin_info = vk::RenderPassBeginInfo::default()
+        .render_pass(target.render_pass().raw())
+        .framebuffer(target.framebuffer().raw())
+        .render_area(vk::Rect2D {
+            offset: vk::Offset2D { x: 0, y: 0 },
+            extent,
+        })
+        .clear_values(&clear_values);
+
+    command_buffer
+        .begin(vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT)
+        .expect("begin command buffer");
+    command_buffer.begin_render_pass(&begin_info, vk::SubpassContents::INLINE);
+    command_buffer.bind_pipeline(&pipeline);
+    let viewport = Viewport {
+        width: (SIZE / 2) as f32,
+        ..Viewport::from_extent(extent)
+    };
+    let inverted = Viewport {
+        min_depth: 1.0,
+        max_depth: 0.0,
+        ..viewport
+    };
+    assert!(command_buffer.set_viewport(&inverted).is_err());
+    let oversized = Viewport {
+        width: device.properties().limits.max_viewport_dimensions[0] as f32 * 2.0,
+        ..viewport
+    };
+    assert!(command_buffer.set_viewport(&oversized).is_err());
+    command_buffer.set_viewport(&viewport).expect("viewport");
+    command_buffer
+        .set_scissor(&Scissor {
+            height: SIZE / 2,
+            ..Scissor::from_extent(extent)
+        })
+        .expect("scissor");
+    command_buffer.draw(3, 1, 0, 0);
+    command_buffer.end_render_pass();
+    command_buffer.end().expect("end command buffer");
+
+    common::submit_and_wait(&device, &command_buffer);
+
+    let pixels = target.read_pixels(&instance, &device).expect("readback");
+    let at = |x: u32, y: u32| {
+        let offset = ((y * SIZE + x) * 4) as usize;
+        [pixels[offset], pixels[offset + 1], pixels[offset + 2]]
+    };
+    let quarter = SIZE / 4;
+    assert_eq!(at(quarter, quarter), [255, 0, 0]);
+    assert_eq!(at(3 * quarter, quarter), [0, 0, 255]);
+    assert_eq!(at(quarter, 3 * quarter), [0, 0, 255]);
+    assert_eq!(at(3 * quarter, 3 * quarter), [0, 0, 255]);
+}
@@ -4,9 +4,12 @@
 //! render pass, graphics pipeline, and buffer can be created and that a command
 //! buffer can be recorded with a pipeline bind and draw command.
 
+mod common;
+
 use ash::vk;
 use moonfield_render::{
-    Buffer, CommandPool, Compiler, Device, GraphicsPipeline, Instance, RenderPass, ShaderModule,
+    Attachment, Buffer, CommandPool, Compiler, DepthState, Device, GraphicsPipeline,
+    GraphicsPipelineDesc, Instance, PipelineState, RenderPass, ShaderModule,
 };
 
 #[repr(C)]
@@ -16,28 +19,7 @@ struct Vertex {
     color: [f32; 3],
 }
 
-#[test]
-fn headless_pipeline_and_command_buffer() {
-    // CI runners without a GPU/Vulkan driver (Windows, macOS) skip this test;
-    // Linux CI runs it against lavapipe (Mesa software Vulkan).
-    let instance = match Instance::new_headless() {
-        Ok(instance) => instance,
-        Err(err) => {
-            eprintln!("skipping: no Vulkan instance available ({err})");
-            return;
-        }
-    };
-    let device = match Device::new(&instance, None) {
-        Ok(device) => device,
-        Err(err) => {
-            eprintln!("skipping: no Vulkan device available ({err})");
-            return;
-        }
-    };
-
-    let compiler = Compiler::new().expect("compiler creation");
-
-    let vertex_source = r#"
+const VERTEX_SOURCE: &str = r#"
 struct VsInput
 {
     float3 position : POSITION;
@@ -60,7 +42,7 @@ VsOutput main(VsInput input)
 }
 "#;
 
-    let fragment_source = r#"
+const FRAGMENT_SOURCE: &str = r#"
 struct PsInput
 {
     float3 color : COLOR;
@@ -80,11 +62,32 @@ PsOutput main(PsInput input)
 }
 "#;
 
+#[test]
+fn headless_pipeline_and_command_buffer() {
+    // CI runners without a GPU/Vulkan driver (Windows, macOS) skip this test;
+    // Linux CI runs it against lavapipe (Mesa software Vulkan).
+    let instance = match Instance::new_headless() {
+        Ok(instance) => instance,
+        Err(err) => {
+            eprintln!("skipping: no Vulkan instance available ({err})");
+            return;
+        }
+    };
+    let device = match Device::new(&instance, None) {
+        Ok(device) => device,
+        Err(err) => {
+            eprintln!("skipping: no Vulkan device available ({err})");
+            return;
+        }
+    };
+
+    let compiler = Compiler::new().expect("compiler creation");
+
     let vertex_spirv = compiler
-        .compile_source_to_spirv("triangle_vs", vertex_source, "main")
+        .compile_source_to_spirv("triangle_vs", VERTEX_SOURCE, "main")
         .expect("vertex shader compilation");
     let fragment_spirv = compiler
-        .compile_source_to_spirv("triangle_fs", fragment_source, "main")
+        .compile_source_to_spirv("triangle_fs", FRAGMENT_SOURCE, "main")
         .expect("fragment shader compilation");
 
     let vertex_shader =
@@ -165,3 +168,59 @@ PsOutput main(PsInput input)
     command_buffer.draw(3, 1, 0, 0);
     command_buffer.end().expect("end command buffer");
 }
+
+#[test]
+fn headless_depth_tested_pipeline() {
+    let Some((_instance, device, _allocator)) = common::headless_gpu() else {
+        return;
+    };
+
+    let (vertex_shader, fragment_shader) =
+        common::compile_shaders(&device, VERTEX_SOURCE, FRAGMENT_SOURCE);
+
+    let render_pass = RenderPass::new_with_attachments(
+        &device,
+        &[Attachment::color(
+            vk::Format::R8G8B8A8_UNORM,
+            vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+        )],
+        Some(Attachment::depth(vk::Format::D32_SFLOAT)),
+    )
+    .expect("render pass");
+
+    let binding = vk::VertexInputBindingDescription::default()
+        .binding(0)
+        .stride(std::mem::size_of::<Vertex>() as u32)
+        .input_rate(vk::VertexInputRate::VERTEX);
+    let attributes = [
+        vk::VertexInputAttributeDescription::default()
+            .binding(0)
+            .location(0)
+            .format(vk::Format::R32G32B32_SFLOAT)
+            .offset(0),
+        vk::VertexInputAttributeDescription::default()
+            .binding(0)
+            .location(1)
+            .format(vk::Format::R32G32B32_SFLOAT)
+            .offset(std::mem::size_of::<[f32; 3]>() as u32),
+    ];
+
+    let desc = GraphicsPipelineDesc {
+        vertex_input_bindings: &[binding],
+        vertex_input_attributes: &attributes,
+        state: PipelineState {
+            cull_mode: vk::CullModeFlags::BACK,
+            depth: Some(DepthState::LESS),
+            ..PipelineState::default()
+        },
+        ..GraphicsPipelineDesc::new(
+            &vertex_shader,
+            &fragment_shader,
+            vk::Extent2D {
+                width: 64,
+                height: 64,
+            },
+        )
+    };
+    GraphicsPipeline::from_desc(&device, &render_pass, &desc).expect("depth-tested pipeline");
+}
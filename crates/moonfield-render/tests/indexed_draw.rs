@@ -0,0 +1,112 @@
+//! Headless indexed draw test.
+//!
+//! Draws a quad covering the left half of the target from four vertices and
+//! six 16-bit indices, and checks that exactly that half was filled.
+
+mod common;
+
+use ash::vk;
+use moonfield_render::{
+    Buffer, Color, CommandPool, GraphicsPipeline, GraphicsPipelineDesc, OffscreenTarget,
+    VertexLayout,
+};
+
+const SIZE: u32 = 16;
+
+const VERTEX_SOURCE: &str = r#"
+[shader("vertex")]
+float4 main(float2 position : POSITION) : SV_POSITION
+{
+    return float4(position, 0.0, 1.0);
+}
+"#;
+
+const FRAGMENT_SOURCE: &str = r#"
+[shader("fragment")]
+float4 main() : SV_TARGET
+{
+    return float4(1.0, 1.0, 1.0, 1.0);
+}
+"#;
+
+#[test]
+fn indexed_quad_covers_left_half() {
+    let Some((instance, device, allocator)) = common::headless_gpu() else {
+        return;
+    };
+    let target = OffscreenTarget::new(&device, allocator, SIZE, SIZE, vk::Format::R8G8B8A8_UNORM)
+        .expect("offscreen target");
+
+    let (vertex_shader, fragment_shader) =
+        common::compile_shaders(&device, VERTEX_SOURCE, FRAGMENT_SOURCE);
+
+    let layout = VertexLayout::new(0, &[vk::Format::R32G32_SFLOAT]).expect("vertex layout");
+    let bindings = [layout.binding_description()];
+    let extent = vk::Extent2D {
+        width: SIZE,
+        height: SIZE,
+    };
+    let desc = GraphicsPipelineDesc {
+        vertex_input_bindings: &bindings,
+        vertex_input_attributes: layout.attributes(),
+        ..GraphicsPipelineDesc::new(&vertex_shader, &fragment_shader, extent)
+    };
+    let pipeline =
+        GraphicsPipeline::from_desc(&device, target.render_pass(), &desc).expect("pipeline");
+
+    // Corners of the left half, shared between the quad's two triangles.
+    let vertices: [[f32; 2]; 4] = [[-1.0, -1.0], [0.0, -1.0], [0.0, 1.0], [-1.0, 1.0]];
+    let indices: [u16; 6] = [0, 1, 2, 2, 3, 0];
+    let vertex_buffer = Buffer::new_with_data(
+        &instance,
+        &device,
+        &vertices,
+        vk::BufferUsageFlags::VERTEX_BUFFER,
+    )
+    .expect("vertex buffer");
+    let index_buffer = Buffer::new_with_data(
+        &instance,
+        &device,
+        &indices,
+        vk::BufferUsageFlags::INDEX_BUFFER,
+    )
+    .expect("index buffer");
+
+    let queue_family_index = device.queue_family_indices().graphics;
+    let command_pool = CommandPool::new(&device, queue_family_index).expect("command pool");
+    let mut command_buffer = command_pool
+        .allocate_command_buffer()
+        .expect("command buffer");
+
+    let clear_values = [Color::BLACK.to_clear_value()];
+    let begin_info = vk::RenderPassBeginInfo::default()
+        .render_pass(target.render_pass().raw())
+        .framebuffer(target.framebuffer().raw())
+        .render_area(vk::Rect2D {
+            offset: vk::Offset2D { x: 0, y: 0 },
+            extent,
+        })
+        .clear_values(&clear_values);
+
+    command_buffer
+        .begin(vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT)
+        .expect("begin command buffer");
+    command_buffer.begin_render_pass(&begin_info, vk::SubpassContents::INLINE);
+    command_buffer.bind_pipeline(&pipeline);
+    command_buffer.bind_vertex_buffers(0, &[vertex_buffer.raw()], &[0]);
+    command_buffer
+        .bind_index_buffer(index_buffer.raw(), 0, vk::IndexType::UINT16)
+        .expect("bind index buffer");
+    command_buffer.draw_indexed(indices.len() as u32, 1, 0, 0, 0);
+    command_buffer.end_render_pass();
+    command_buffer.end().expect("end command buffer");
+
+    common::submit_and_wait(&device, &command_buffer);
+
+    let pixels = target.read_pixels(&instance, &device).expect("readback");
+    for (index, texel) in pixels.chunks_exact(4).enumerate() {
+        let x = index as u32 % SIZE;
+        let expected = if x < SIZE / 2 { 255 } else { 0 };
+        assert_eq!(texel[..3], [expected; 3], "texel {index}");
+    }
+}
@@ -0,0 +1,107 @@
+//! Headless MSAA resolve test.
+//!
+//! Draws a white triangle at 4x MSAA into a multisampled offscreen target,
+//! lets the render pass resolve it into the single-sampled image and checks
+//! that the triangle edges read back with partial coverage.
+
+mod common;
+
+use ash::vk;
+use moonfield_render::{
+    Color, CommandPool, GraphicsPipeline, GraphicsPipelineDesc, OffscreenTarget, PipelineState,
+};
+
+const SIZE: u32 = 16;
+
+const VERTEX_SOURCE: &str = r#"
+[shader("vertex")]
+float4 main(uint vertex_id : SV_VertexID) : SV_POSITION
+{
+    float2 positions[3] = { float2(-0.8, -0.9), float2(0.9, 0.7), float2(-0.6, 0.8) };
+    return float4(positions[vertex_id], 0.0, 1.0);
+}
+"#;
+
+const FRAGMENT_SOURCE: &str = r#"
+[shader("fragment")]
+float4 main() : SV_TARGET
+{
+    return float4(1.0, 1.0, 1.0, 1.0);
+}
+"#;
+
+#[test]
+fn msaa_triangle_resolves_to_partial_coverage() {
+    let Some((instance, device, allocator)) = common::headless_gpu() else {
+        return;
+    };
+    let target = OffscreenTarget::new_multisampled(
+        &device,
+        allocator,
+        SIZE,
+        SIZE,
+        vk::Format::R8G8B8A8_UNORM,
+        vk::SampleCountFlags::TYPE_4,
+    )
+    .expect("multisampled offscreen target");
+    assert_eq!(target.render_pass().samples(), vk::SampleCountFlags::TYPE_4);
+
+    let (vertex_shader, fragment_shader) =
+        common::compile_shaders(&device, VERTEX_SOURCE, FRAGMENT_SOURCE);
+
+    let extent = vk::Extent2D {
+        width: SIZE,
+        height: SIZE,
+    };
+    let single_sampled = GraphicsPipelineDesc::new(&vertex_shader, &fragment_shader, extent);
+    assert!(
+        GraphicsPipeline::from_desc(&device, target.render_pass(), &single_sampled).is_err(),
+        "sample count mismatch with the render pass must be rejected"
+    );
+    let desc = GraphicsPipelineDesc {
+        state: PipelineState {
+            samples: vk::SampleCountFlags::TYPE_4,
+            ..PipelineState::default()
+        },
+        ..single_sampled
+    };
+    let pipeline = GraphicsPipeline::from_desc(&device, target.render_pass(), &desc)
+        .expect("multisampled pipeline");
+
+    let queue_family_index = device.queue_family_indices().graphics;
+    let command_pool = CommandPool::new(&device, queue_family_index).expect("command pool");
+    let mut command_buffer = command_pool
+        .allocate_command_buffer()
+        .expect("command buffer");
+
+    let clear_values = [Color::from_rgba8(0, 0, 0, 255).to_clear_value()];
+    let begin_info = vk::RenderPassBeginInfo::default()
+        .render_pass(target.render_pass().raw())
+        .framebuffer(target.framebuffer().raw())
+        .render_area(vk::Rect2D {
+            offset: vk::Offset2D { x: 0, y: 0 },
+            extent,
+        })
+        .clear_values(&clear_values);
+
+    command_buffer
+        .begin(vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT)
+        .expect("begin command buffer");
+    command_buffer.begin_render_pass(&begin_info, vk::SubpassContents::INLINE);
+    command_buffer.bind_graphics_pipeline(pipeline.raw());
+    command_buffer.draw(3, 1, 0, 0);
+    command_buffer.end_render_pass();
+    command_buffer.end().expect("end command buffer");
+
+    common::submit_and_wait(&device, &command_buffer);
+
+    let pixels = target.read_pixels(&instance, &device).expect("readback");
+    assert_eq!(pixels.len(), (SIZE * SIZE * 4) as usize);
+    let reds: Vec<u8> = pixels.chunks_exact(4).map(|texel| texel[0]).collect();
+    assert!(reds.contains(&0), "background must stay black");
+    assert!(reds.contains(&255), "triangle interior must be white");
+    assert!(
+        reds.iter().any(|&red| red > 0 && red < 255),
+        "resolved edges must have partial coverage"
+    );
+}
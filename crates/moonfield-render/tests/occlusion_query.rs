@@ -0,0 +1,190 @@
+//! Headless occlusion query test.
+//!
+//! Draws a full-screen occluder at mid depth, then counts the samples of a
+//! quad in front of it and of a quad behind it with occlusion queries.
+
+mod common;
+
+use ash::vk;
+use moonfield_render::{
+    Attachment, Buffer, Color, CommandPool, DepthState, Framebuffer, GraphicsPipeline,
+    GraphicsPipelineDesc, PipelineState, QueryPool, RenderPass, Texture, TextureDesc,
+};
+
+const SIZE: u32 = 16;
+const COLOR_FORMAT: vk::Format = vk::Format::R8G8B8A8_UNORM;
+const DEPTH_FORMAT: vk::Format = vk::Format::D32_SFLOAT;
+
+// Six vertices per quad: a full-screen occluder at depth 0.5, then a
+// half-size quad in front of it and one behind it.
+const VERTEX_SOURCE: &str = r#"
+[shader("vertex")]
+float4 main(uint vertex_id : SV_VertexID) : SV_POSITION
+{
+    float2 corners[6] = {
+        float2(-1.0, -1.0), float2(1.0, -1.0), float2(1.0, 1.0),
+        float2(-1.0, -1.0), float2(1.0, 1.0), float2(-1.0, 1.0)
+    };
+    float depths[3] = { 0.5, 0.2, 0.8 };
+    uint quad = vertex_id / 6;
+    float scale = quad == 0 ? 1.0 : 0.5;
+    return float4(corners[vertex_id % 6] * scale, depths[quad], 1.0);
+}
+"#;
+
+const FRAGMENT_SOURCE: &str = r#"
+[shader("fragment")]
+float4 main() : SV_TARGET
+{
+    return float4(1.0, 1.0, 1.0, 1.0);
+}
+"#;
+
+#[test]
+fn occlusion_queries_count_visible_samples() {
+    let Some((instance, device, allocator)) = common::headless_gpu() else {
+        return;
+    };
+    let color = Texture::new(
+        &device,
+        allocator.clone(),
+        &TextureDesc::new_2d(
+            SIZE,
+            SIZE,
+            COLOR_FORMAT,
+            vk::ImageUsageFlags::COLOR_ATTACHMENT,
+        ),
+    )
+    .expect("color texture");
+    let depth = Texture::new(
+        &device,
+        allocator,
+        &TextureDesc::new_2d(
+            SIZE,
+            SIZE,
+            DEPTH_FORMAT,
+            vk::ImageUsageFlags::DEPTH_STENCIL_ATTACHMENT,
+        ),
+    )
+    .expect("depth texture");
+    let color_view = color
+        .create_view(&device, vk::ImageViewType::TYPE_2D)
+        .expect("color view");
+    let depth_view = depth
+        .create_view(&device, vk::ImageViewType::TYPE_2D)
+        .expect("depth view");
+
+    let render_pass = RenderPass::new_with_attachments(
+        &device,
+        &[Attachment::color(
+            COLOR_FORMAT,
+            vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL,
+        )],
+        Some(Attachment::depth(DEPTH_FORMAT)),
+    )
+    .expect("render pass");
+    let extent = vk::Extent2D {
+        width: SIZE,
+        height: SIZE,
+    };
+    let framebuffer = Framebuffer::new(
+        &device,
+        &render_pass,
+        &[color_view.raw(), depth_view.raw()],
+        extent,
+    )
+    .expect("framebuffer");
+
+    let (vertex_shader, fragment_shader) =
+        common::compile_shaders(&device, VERTEX_SOURCE, FRAGMENT_SOURCE);
+    let desc = GraphicsPipelineDesc {
+        state: PipelineState {
+            depth: Some(DepthState::LESS),
+            ..PipelineState::default()
+        },
+        ..GraphicsPipelineDesc::new(&vertex_shader, &fragment_shader, extent)
+    };
+    let pipeline =
+        GraphicsPipeline::from_desc(&device, &render_pass, &desc).expect("depth-tested pipeline");
+
+    let queries = QueryPool::new_occlusion(&device, 2).expect("occlusion queries");
+    let timestamps = QueryPool::new_timestamps(&device, 1).expect("timestamp queries");
+    let results = Buffer::new(
+        &instance,
+        &device,
+        2 * std::mem::size_of::<u64>() as vk::DeviceSize,
+        vk::BufferUsageFlags::TRANSFER_DST,
+    )
+    .expect("result buffer");
+
+    let queue_family_index = device.queue_family_indices().graphics;
+    let command_pool = CommandPool::new(&device, queue_family_index).expect("command pool");
+    let mut command_buffer = command_pool
+        .allocate_command_buffer()
+        .expect("command buffer");
+
+    let clear_values = [
+        Color::BLACK.to_clear_value(),
+        vk::ClearValue {
+            depth_stencil: vk::ClearDepthStencilValue {
+                depth: 1.0,
+                stencil: 0,
+            },
+        },
+    ];
+    let begin_info = vk::RenderPassBeginInfo::default()
+        .render_pass(render_pass.raw())
+        .framebuffer(framebuffer.raw())
+        .render_area(vk::Rect2D {
+            offset: vk::Offset2D { x: 0, y: 0 },
+            extent,
+        })
+        .clear_values(&clear_values);
+
+    command_buffer
+        .begin(vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT)
+        .expect("begin command buffer");
+    command_buffer
+        .reset_query_pool(&queries, 0, 2)
+        .expect("reset queries");
+    command_buffer.begin_render_pass(&begin_info, vk::SubpassContents::INLINE);
+    command_buffer.bind_pipeline(&pipeline);
+    command_buffer.draw(6, 1, 0, 0);
+    assert!(command_buffer
+        .begin_occlusion_query(&timestamps, 0)
+        .is_err());
+    assert!(command_buffer.begin_occlusion_query(&queries, 2).is_err());
+    for (query, first_vertex) in [(0, 6), (1, 12)] {
+        command_buffer
+            .begin_occlusion_query(&queries, query)
+            .expect("begin query");
+        command_buffer.draw(6, 1, first_vertex, 0);
+        command_buffer
+            .end_occlusion_query(&queries, query)
+            .expect("end query");
+    }
+    command_buffer.end_render_pass();
+    command_buffer
+        .copy_query_results(&queries, 0, 2, &results, 0)
+        .expect("copy query results");
+    command_buffer.pipeline_barrier(
+        vk::PipelineStageFlags::TRANSFER,
+        vk::PipelineStageFlags::HOST,
+        vk::DependencyFlags::empty(),
+        &[],
+        &[results.host_read_barrier()],
+        &[],
+    );
+    command_buffer.end().expect("end command buffer");
+
+    common::submit_and_wait(&device, &command_buffer);
+
+    let bytes = results.download().expect("result readback");
+    let samples: Vec<u64> = bytes
+        .chunks_exact(8)
+        .map(|result| u64::from_ne_bytes(result.try_into().unwrap()))
+        .collect();
+    assert!(samples[0] > 0, "the quad in front must be visible");
+    assert_eq!(samples[1], 0, "the quad behind must be occluded");
+    assert_eq!(queries.results(0, 2).expect("host results"), samples);
+}
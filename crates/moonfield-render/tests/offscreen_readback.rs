@@ -0,0 +1,52 @@
+//! Headless readback test for the offscreen color target.
+//!
+//! Clears a small offscreen target without any window or surface and checks
+//! the texels copied back to the host.
+
+mod common;
+
+use ash::vk;
+use moonfield_render::{Color, CommandPool, OffscreenTarget};
+
+#[test]
+fn offscreen_clear_reads_back_red() {
+    let Some((instance, device, allocator)) = common::headless_gpu() else {
+        return;
+    };
+    let target = OffscreenTarget::new(&device, allocator, 4, 4, vk::Format::R8G8B8A8_UNORM)
+        .expect("offscreen target");
+
+    let queue_family_index = device.queue_family_indices().graphics;
+    let command_pool = CommandPool::new(&device, queue_family_index).expect("command pool");
+    let mut command_buffer = command_pool
+        .allocate_command_buffer()
+        .expect("command buffer");
+
+    let clear_values = [Color::from_rgba8(255, 0, 0, 255).to_clear_value()];
+    let begin_info = vk::RenderPassBeginInfo::default()
+        .render_pass(target.render_pass().raw())
+        .framebuffer(target.framebuffer().raw())
+        .render_area(vk::Rect2D {
+            offset: vk::Offset2D { x: 0, y: 0 },
+            extent: vk::Extent2D {
+                width: 4,
+                height: 4,
+            },
+        })
+        .clear_values(&clear_values);
+
+    command_buffer
+        .begin(vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT)
+        .expect("begin command buffer");
+    command_buffer.begin_render_pass(&begin_info, vk::SubpassContents::INLINE);
+    command_buffer.end_render_pass();
+    command_buffer.end().expect("end command buffer");
+
+    common::submit_and_wait(&device, &command_buffer);
+
+    let pixels = target.read_pixels(&instance, &device).expect("readback");
+    assert_eq!(pixels.len(), 4 * 4 * 4);
+    for texel in pixels.chunks_exact(4) {
+        assert_eq!(texel, [255, 0, 0, 255]);
+    }
+}
@@ -0,0 +1,126 @@
+//! Headless push constant test.
+//!
+//! Pushes a matrix scaling a full-screen quad to half size and checks that
+//! only the center of the target is covered.
+
+mod common;
+
+use ash::vk;
+use moonfield_render::{
+    Color, CommandPool, GraphicsPipeline, GraphicsPipelineDesc, OffscreenTarget,
+};
+
+const SIZE: u32 = 16;
+
+const VERTEX_SOURCE: &str = r#"
+struct PushConstants
+{
+    float4x4 transform;
+};
+
+[[vk::push_constant]]
+ConstantBuffer<PushConstants> push;
+
+[shader("vertex")]
+float4 main(uint vertex_id : SV_VertexID) : SV_POSITION
+{
+    float2 positions[6] = {
+        float2(-1.0, -1.0), float2(1.0, -1.0), float2(1.0, 1.0),
+        float2(-1.0, -1.0), float2(1.0, 1.0), float2(-1.0, 1.0)
+    };
+    return mul(push.transform, float4(positions[vertex_id], 0.0, 1.0));
+}
+"#;
+
+const FRAGMENT_SOURCE: &str = r#"
+[shader("fragment")]
+float4 main() : SV_TARGET
+{
+    return float4(1.0, 1.0, 1.0, 1.0);
+}
+"#;
+
+#[test]
+fn pushed_matrix_scales_quad() {
+    let Some((instance, device, allocator)) = common::headless_gpu() else {
+        return;
+    };
+    let target = OffscreenTarget::new(&device, allocator, SIZE, SIZE, vk::Format::R8G8B8A8_UNORM)
+        .expect("offscreen target");
+
+    let (vertex_shader, fragment_shader) =
+        common::compile_shaders(&device, VERTEX_SOURCE, FRAGMENT_SOURCE);
+
+    let extent = vk::Extent2D {
+        width: SIZE,
+        height: SIZE,
+    };
+    let push_constant_ranges = [vk::PushConstantRange {
+        stage_flags: vk::ShaderStageFlags::VERTEX,
+        offset: 0,
+        size: std::mem::size_of::<[f32; 16]>() as u32,
+    }];
+    let desc = GraphicsPipelineDesc {
+        push_constant_ranges: &push_constant_ranges,
+        ..GraphicsPipelineDesc::new(&vertex_shader, &fragment_shader, extent)
+    };
+    let pipeline = GraphicsPipeline::from_desc(&device, target.render_pass(), &desc)
+        .expect("pipeline with push constants");
+
+    let queue_family_index = device.queue_family_indices().graphics;
+    let command_pool = CommandPool::new(&device, queue_family_index).expect("command pool");
+    let mut command_buffer = command_pool
+        .allocate_command_buffer()
+        .expect("command buffer");
+
+    let clear_values = [Color::BLACK.to_clear_value()];
+    let begin_info = vk::RenderPassBeginInfo::default()
+        .render_pass(target.render_pass().raw())
+        .framebuffer(target.framebuffer().raw())
+        .render_area(vk::Rect2D {
+            offset: vk::Offset2D { x: 0, y: 0 },
+            extent,
+        })
+        .clear_values(&clear_values);
+
+    // A diagonal matrix reads the same in row- and column-major order.
+    #[rustfmt::skip]
+    let half_scale: [f32; 16] = [
+        0.5, 0.0, 0.0, 0.0,
+        0.0, 0.5, 0.0, 0.0,
+        0.0, 0.0, 1.0, 0.0,
+        0.0, 0.0, 0.0, 1.0,
+    ];
+    let half_scale: Vec<u8> = half_scale.iter().flat_map(|v| v.to_ne_bytes()).collect();
+
+    command_buffer
+        .begin(vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT)
+        .expect("begin command buffer");
+    command_buffer.begin_render_pass(&begin_info, vk::SubpassContents::INLINE);
+    command_buffer.bind_pipeline(&pipeline);
+    assert!(command_buffer
+        .set_push_constants(
+            &pipeline,
+            vk::ShaderStageFlags::VERTEX,
+            2,
+            &half_scale[..16]
+        )
+        .is_err());
+    assert!(command_buffer
+        .set_push_constants(&pipeline, vk::ShaderStageFlags::FRAGMENT, 0, &half_scale)
+        .is_err());
+    command_buffer
+        .set_push_constants(&pipeline, vk::ShaderStageFlags::VERTEX, 0, &half_scale)
+        .expect("push matrix");
+    command_buffer.draw(6, 1, 0, 0);
+    command_buffer.end_render_pass();
+    command_buffer.end().expect("end command buffer");
+
+    common::submit_and_wait(&device, &command_buffer);
+
+    let pixels = target.read_pixels(&instance, &device).expect("readback");
+    let red_at = |x: u32, y: u32| pixels[((y * SIZE + x) * 4) as usize];
+    assert_eq!(red_at(SIZE / 2, SIZE / 2), 255, "center must be covered");
+    assert_eq!(red_at(1, 1), 0, "corners must stay clear");
+    assert_eq!(red_at(SIZE - 2, SIZE - 2), 0, "corners must stay clear");
+}
@@ -0,0 +1,114 @@
+//! Headless shadow map test.
+//!
+//! Renders a far full-screen occluder and a near occluder in the center into
+//! a depth-only `D32_SFLOAT` shadow map and checks the depth read back.
+
+mod common;
+
+use ash::vk;
+use moonfield_render::{
+    CommandPool, DepthState, GraphicsPipeline, GraphicsPipelineDesc, PipelineState, ShadowMap,
+};
+
+const SIZE: u32 = 64;
+
+const VERTEX_SOURCE: &str = r#"
+[shader("vertex")]
+float4 main(uint vertex_id : SV_VertexID) : SV_POSITION
+{
+    float3 positions[6] = {
+        float3(-1.0, -1.0, 0.8), float3(3.0, -1.0, 0.8), float3(-1.0, 3.0, 0.8),
+        float3(-0.5, -0.5, 0.2), float3(0.5, -0.5, 0.2), float3(0.0, 0.5, 0.2)
+    };
+    return float4(positions[vertex_id], 1.0);
+}
+"#;
+
+const FRAGMENT_SOURCE: &str = r#"
+[shader("fragment")]
+void main()
+{
+}
+"#;
+
+#[test]
+fn shadow_map_stores_nearest_occluder_depth() {
+    let Some((instance, device, allocator)) = common::headless_gpu() else {
+        return;
+    };
+    assert!(ShadowMap::new(
+        &device,
+        allocator.clone(),
+        SIZE,
+        SIZE,
+        vk::Format::R8G8B8A8_UNORM
+    )
+    .is_err());
+    let shadow_map =
+        ShadowMap::new(&device, allocator, SIZE, SIZE, vk::Format::D32_SFLOAT).expect("shadow map");
+    assert_eq!(shadow_map.render_pass().color_attachment_count(), 0);
+
+    let (vertex_shader, fragment_shader) =
+        common::compile_shaders(&device, VERTEX_SOURCE, FRAGMENT_SOURCE);
+
+    let extent = vk::Extent2D {
+        width: SIZE,
+        height: SIZE,
+    };
+    let desc = GraphicsPipelineDesc {
+        state: PipelineState {
+            depth: Some(DepthState::LESS),
+            ..PipelineState::default()
+        },
+        ..GraphicsPipelineDesc::new(&vertex_shader, &fragment_shader, extent)
+    };
+    let pipeline = GraphicsPipeline::from_desc(&device, shadow_map.render_pass(), &desc)
+        .expect("depth-only pipeline");
+
+    let queue_family_index = device.queue_family_indices().graphics;
+    let command_pool = CommandPool::new(&device, queue_family_index).expect("command pool");
+    let mut command_buffer = command_pool
+        .allocate_command_buffer()
+        .expect("command buffer");
+
+    let clear_values = [vk::ClearValue {
+        depth_stencil: vk::ClearDepthStencilValue {
+            depth: 1.0,
+            stencil: 0,
+        },
+    }];
+    let begin_info = vk::RenderPassBeginInfo::default()
+        .render_pass(shadow_map.render_pass().raw())
+        .framebuffer(shadow_map.framebuffer().raw())
+        .render_area(vk::Rect2D {
+            offset: vk::Offset2D { x: 0, y: 0 },
+            extent,
+        })
+        .clear_values(&clear_values);
+
+    command_buffer
+        .begin(vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT)
+        .expect("begin command buffer");
+    command_buffer.begin_render_pass(&begin_info, vk::SubpassContents::INLINE);
+    command_buffer.bind_graphics_pipeline(pipeline.raw());
+    command_buffer.draw(6, 1, 0, 0);
+    command_buffer.end_render_pass();
+    command_buffer.end().expect("end command buffer");
+
+    common::submit_and_wait(&device, &command_buffer);
+
+    let bytes = shadow_map
+        .read_depth(&instance, &device)
+        .expect("depth readback");
+    let depth: Vec<f32> = bytes
+        .chunks_exact(4)
+        .map(|texel| f32::from_ne_bytes(texel.try_into().unwrap()))
+        .collect();
+    assert_eq!(depth.len(), (SIZE * SIZE) as usize);
+
+    let at = |x: u32, y: u32| depth[(y * SIZE + x) as usize];
+    let (center, corner) = (at(SIZE / 2, SIZE / 2), at(1, 1));
+    assert!((center - 0.2).abs() < 1e-3, "center depth {center}");
+    assert!((corner - 0.8).abs() < 1e-3, "corner depth {corner}");
+    assert!(center < corner);
+}
@@ -0,0 +1,244 @@
+//! Headless stencil masking test.
+//!
+//! Marks the left half of the target in the stencil buffer with one draw,
+//! then draws a full-screen triangle that only passes where the stencil was
+//! marked, and checks that only the left half was covered.
+
+mod common;
+
+use ash::vk;
+use moonfield_render::{
+    Attachment, Buffer, CommandPool, Framebuffer, GraphicsPipeline, GraphicsPipelineDesc,
+    ImageView, PipelineState, RenderPass, StencilState, Texture, TextureDesc,
+};
+
+const SIZE: u32 = 64;
+const COLOR_FORMAT: vk::Format = vk::Format::R8G8B8A8_UNORM;
+
+// Vertices 0..6 cover the left half in green; 6..9 cover the whole target
+// in red.
+const VERTEX_SOURCE: &str = r#"
+struct VsOutput
+{
+    float4 position : SV_POSITION;
+    float4 color : COLOR;
+};
+
+[shader("vertex")]
+VsOutput main(uint vertex_id : SV_VertexID)
+{
+    float2 positions[9] = {
+        float2(-1.0, -1.0), float2(0.0, -1.0), float2(0.0, 1.0),
+        float2(-1.0, -1.0), float2(0.0, 1.0), float2(-1.0, 1.0),
+        float2(-1.0, -1.0), float2(3.0, -1.0), float2(-1.0, 3.0)
+    };
+    VsOutput output;
+    output.position = float4(positions[vertex_id], 0.5, 1.0);
+    output.color = vertex_id < 6 ? float4(0.0, 1.0, 0.0, 1.0) : float4(1.0, 0.0, 0.0, 1.0);
+    return output;
+}
+"#;
+
+const FRAGMENT_SOURCE: &str = r#"
+[shader("fragment")]
+float4 main(float4 color : COLOR) : SV_TARGET
+{
+    return color;
+}
+"#;
+
+#[test]
+fn stencil_masks_second_draw() {
+    let Some((instance, device, allocator)) = common::headless_gpu() else {
+        return;
+    };
+    let Some(stencil_format) = [
+        vk::Format::D24_UNORM_S8_UINT,
+        vk::Format::D32_SFLOAT_S8_UINT,
+    ]
+    .into_iter()
+    .find(|&format| {
+        device
+            .validate_image_support(
+                format,
+                vk::ImageUsageFlags::DEPTH_STENCIL_ATTACHMENT,
+                vk::SampleCountFlags::TYPE_1,
+            )
+            .is_ok()
+    }) else {
+        eprintln!("skipping: no depth/stencil attachment format supported");
+        return;
+    };
+
+    let color = Texture::new(
+        &device,
+        allocator.clone(),
+        &TextureDesc::new_2d(
+            SIZE,
+            SIZE,
+            COLOR_FORMAT,
+            vk::ImageUsageFlags::COLOR_ATTACHMENT | vk::ImageUsageFlags::TRANSFER_SRC,
+        ),
+    )
+    .expect("color texture");
+    let depth_stencil = Texture::new(
+        &device,
+        allocator,
+        &TextureDesc::new_2d(
+            SIZE,
+            SIZE,
+            stencil_format,
+            vk::ImageUsageFlags::DEPTH_STENCIL_ATTACHMENT,
+        ),
+    )
+    .expect("depth/stencil texture");
+    let color_view = color
+        .create_view(&device, vk::ImageViewType::TYPE_2D)
+        .expect("color view");
+    let depth_stencil_view = ImageView::new(
+        &device,
+        depth_stencil.raw(),
+        stencil_format,
+        None,
+        vk::ImageViewType::TYPE_2D,
+        vk::ImageSubresourceRange::default()
+            .aspect_mask(vk::ImageAspectFlags::DEPTH | vk::ImageAspectFlags::STENCIL)
+            .level_count(1)
+            .layer_count(1),
+    )
+    .expect("depth/stencil view");
+
+    let render_pass = RenderPass::new_with_attachments(
+        &device,
+        &[Attachment::color(
+            COLOR_FORMAT,
+            vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+        )],
+        Some(Attachment::depth(stencil_format)),
+    )
+    .expect("render pass");
+    assert!(render_pass.has_stencil());
+    let extent = vk::Extent2D {
+        width: SIZE,
+        height: SIZE,
+    };
+    let framebuffer = Framebuffer::new(
+        &device,
+        &render_pass,
+        &[color_view.raw(), depth_stencil_view.raw()],
+        extent,
+    )
+    .expect("framebuffer");
+
+    let (vertex_shader, fragment_shader) =
+        common::compile_shaders(&device, VERTEX_SOURCE, FRAGMENT_SOURCE);
+
+    let pipeline = |stencil: StencilState| {
+        let desc = GraphicsPipelineDesc {
+            state: PipelineState {
+                stencil: Some(stencil),
+                ..PipelineState::default()
+            },
+            ..GraphicsPipelineDesc::new(&vertex_shader, &fragment_shader, extent)
+        };
+        GraphicsPipeline::from_desc(&device, &render_pass, &desc)
+    };
+    let mark_pipeline = pipeline(StencilState::WRITE_REFERENCE).expect("stencil write pipeline");
+    let masked_pipeline = pipeline(StencilState::EQUAL_REFERENCE).expect("stencil test pipeline");
+
+    let readback = Buffer::new(
+        &instance,
+        &device,
+        (SIZE * SIZE * 4) as vk::DeviceSize,
+        vk::BufferUsageFlags::TRANSFER_DST,
+    )
+    .expect("readback buffer");
+
+    let queue_family_index = device.queue_family_indices().graphics;
+    let command_pool = CommandPool::new(&device, queue_family_index).expect("command pool");
+    let mut command_buffer = command_pool
+        .allocate_command_buffer()
+        .expect("command buffer");
+
+    let clear_values = [
+        vk::ClearValue {
+            color: vk::ClearColorValue {
+                float32: [0.0, 0.0, 0.0, 1.0],
+            },
+        },
+        vk::ClearValue {
+            depth_stencil: vk::ClearDepthStencilValue {
+                depth: 1.0,
+                stencil: 0,
+            },
+        },
+    ];
+    let begin_info = vk::RenderPassBeginInfo::default()
+        .render_pass(render_pass.raw())
+        .framebuffer(framebuffer.raw())
+        .render_area(vk::Rect2D {
+            offset: vk::Offset2D { x: 0, y: 0 },
+            extent,
+        })
+        .clear_values(&clear_values);
+
+    command_buffer
+        .begin(vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT)
+        .expect("begin command buffer");
+    command_buffer.begin_render_pass(&begin_info, vk::SubpassContents::INLINE);
+    command_buffer.set_stencil_reference(1);
+    command_buffer.bind_graphics_pipeline(mark_pipeline.raw());
+    command_buffer.draw(6, 1, 0, 0);
+    command_buffer.bind_graphics_pipeline(masked_pipeline.raw());
+    command_buffer.draw(3, 1, 6, 0);
+    command_buffer.end_render_pass();
+    // The render pass leaves the image in TRANSFER_SRC_OPTIMAL; order its
+    // color writes before the copy reads them.
+    command_buffer.pipeline_barrier(
+        vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT,
+        vk::PipelineStageFlags::TRANSFER,
+        vk::DependencyFlags::empty(),
+        &[vk::MemoryBarrier::default()
+            .src_access_mask(vk::AccessFlags::COLOR_ATTACHMENT_WRITE)
+            .dst_access_mask(vk::AccessFlags::TRANSFER_READ)],
+        &[],
+        &[],
+    );
+    let region = vk::BufferImageCopy::default()
+        .image_subresource(
+            vk::ImageSubresourceLayers::default()
+                .aspect_mask(vk::ImageAspectFlags::COLOR)
+                .layer_count(1),
+        )
+        .image_extent(vk::Extent3D {
+            width: SIZE,
+            height: SIZE,
+            depth: 1,
+        });
+    command_buffer.copy_image_to_buffer(
+        color.raw(),
+        vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+        readback.raw(),
+        &[region],
+    );
+    command_buffer.pipeline_barrier(
+        vk::PipelineStageFlags::TRANSFER,
+        vk::PipelineStageFlags::HOST,
+        vk::DependencyFlags::empty(),
+        &[],
+        &[readback.host_read_barrier()],
+        &[],
+    );
+    command_buffer.end().expect("end command buffer");
+
+    common::submit_and_wait(&device, &command_buffer);
+
+    let pixels = readback.download().expect("readback");
+    let at = |x: u32, y: u32| {
+        let offset = ((y * SIZE + x) * 4) as usize;
+        [pixels[offset], pixels[offset + 1], pixels[offset + 2]]
+    };
+    // The masked draw replaced the marked half and left the rest cleared.
+    assert_eq!(at(SIZE / 4, SIZE / 2), [255, 0, 0]);
+    assert_eq!(at(3 * SIZE / 4, SIZE / 2), [0, 0, 0]);
+}
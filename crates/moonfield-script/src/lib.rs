@@ -19,7 +19,7 @@ pub use time::{new_shared_time, register_time_api, ScriptTimeState, SharedTimeSt
 pub use window::register_window_api;
 
 use moonfield_app::prelude::World;
-use moonfield_app::{App, Plugin};
+use moonfield_app::{App, FixedTimestep, Plugin};
 use moonfield_log::{error, info, warn};
 
 #[cfg(all(feature = "v8-backend", not(feature = "quickjs-backend")))]
@@ -192,8 +192,9 @@ struct ScriptState {
     entry_loaded: bool,
     /// Wall-clock of the previous update, used to compute frame deltas.
     last_frame: Instant,
-    /// Fixed-timestep accumulator feeding `on_fixed_update`.
-    fixed: FixedStepAccumulator,
+    /// Fixed-timestep accumulator feeding `on_fixed_update`; capped at
+    /// [`FixedTimestep::DEFAULT_MAX_STEPS`] steps per frame.
+    fixed: FixedTimestep,
     /// Input state shared with the `input_*` host functions; mirrored from
     /// the world's `InputState` resource each frame.
     input: Arc<Mutex<ScriptInputState>>,
@@ -269,44 +270,6 @@ impl HotReloadHandler for ReloadReset<'_> {
     }
 }
 
-/// Maximum fixed steps per frame; excess accumulated time is dropped so a
-/// long stall cannot cause a catch-up ("death") spiral.
-const MAX_FIXED_STEPS_PER_FRAME: u32 = 5;
-
-/// Fixed-timestep accumulator driving the `on_fixed_update` hook
-/// (Godot `_physics_process` style): real frame time is collected and
-/// consumed in fixed slices, so gameplay logic runs at a stable rate
-/// regardless of frame rate.
-#[derive(Debug)]
-struct FixedStepAccumulator {
-    timestep: Duration,
-    accumulated: Duration,
-}
-
-impl FixedStepAccumulator {
-    fn new(timestep: Duration) -> Self {
-        Self {
-            timestep,
-            accumulated: Duration::ZERO,
-        }
-    }
-
-    /// Add one frame's elapsed time and return how many fixed steps should
-    /// run this frame (0..=[`MAX_FIXED_STEPS_PER_FRAME`]). When the cap is
-    /// hit the backlog is dropped — running slow beats a catch-up spiral.
-    fn advance(&mut self, frame_time: Duration) -> u32 {
-        self.accumulated += frame_time;
-        let mut steps = (self.accumulated.as_nanos() / self.timestep.as_nanos()) as u32;
-        if steps > MAX_FIXED_STEPS_PER_FRAME {
-            steps = MAX_FIXED_STEPS_PER_FRAME;
-            self.accumulated = Duration::ZERO;
-        } else {
-            self.accumulated -= self.timestep * steps;
-        }
-        steps
-    }
-}
-
 /// Consecutive hook failures logged in full before throttling kicks in.
 const HOOK_ERROR_FULL_LOGS: u64 = 3;
 /// Once throttled, a summary line is logged every this many failures.
@@ -450,7 +413,7 @@ impl Plugin for ScriptPlugin {
                 entry_path,
                 entry_loaded,
                 last_frame: startup,
-                fixed: FixedStepAccumulator::new(fixed_timestep),
+                fixed: FixedTimestep::new(fixed_timestep),
                 input,
                 time,
                 error_log: HookErrorLog::default(),
@@ -550,7 +513,7 @@ impl Plugin for ScriptPlugin {
             // Unity `FixedUpdate` style), runs before `on_update`.
             if state.runtime.has_function("on_fixed_update") {
                 let dt = fixed_timestep.as_secs_f64();
-                for _ in 0..state.fixed.advance(frame_time) {
+                for _ in 0..state.fixed.accumulate(frame_time) {
                     lock_input(&state.input).begin_fixed_step();
                     match state
                         .runtime
@@ -697,28 +660,6 @@ mod tests {
         std::fs::remove_dir_all(&dir).ok();
     }
 
-    #[test]
-    fn fixed_step_accumulator_slices_frame_time() {
-        let mut acc = FixedStepAccumulator::new(Duration::from_millis(10));
-        assert_eq!(acc.advance(Duration::from_millis(25)), 2); // 5ms carried over
-        assert_eq!(acc.advance(Duration::from_millis(4)), 0); // 9ms total
-        assert_eq!(acc.advance(Duration::from_millis(1)), 1); // 10ms — one step
-        assert_eq!(acc.advance(Duration::ZERO), 0);
-    }
-
-    #[test]
-    fn fixed_step_accumulator_caps_and_drops_backlog() {
-        let mut acc = FixedStepAccumulator::new(Duration::from_millis(10));
-        // A 500ms stall yields far more than the cap: clamp, then drop the
-        // backlog so the next frame starts clean.
-        assert_eq!(
-            acc.advance(Duration::from_millis(500)),
-            MAX_FIXED_STEPS_PER_FRAME
-        );
-        assert_eq!(acc.advance(Duration::ZERO), 0);
-        assert_eq!(acc.advance(Duration::from_millis(10)), 1);
-    }
-
     #[test]
     fn hook_error_log_throttles_repeated_failures() {
         let mut log = HookErrorLog::default();